@@ -0,0 +1,6 @@
+use include_assets_build::{Archive, Compression};
+
+fn main() {
+    // compress the assets here instead of in the include_dir! proc macro; this reruns only if the assets change
+    Archive::new("assets").compression(Compression::Zstd { level: 19 }).write("assets").unwrap();
+}
@@ -0,0 +1,14 @@
+use include_assets::{include_archive, NamedArchive};
+
+fn main() {
+    // the archive has been compressed by build.rs
+    let archive = NamedArchive::load(include_archive!("assets"));
+
+    println!("the following {} assets included in this executable:", archive.number_of_assets());
+    for (name, data) in archive.assets() {
+        println!("{}: {} bytes", name, data.len());
+    }
+    println!();
+
+    print!("{}", std::str::from_utf8(&archive["greeting.txt"]).unwrap());
+}
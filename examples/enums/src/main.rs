@@ -8,7 +8,8 @@ enum Asset {
     #[asset(path = "src/main.rs")]
     Main,
     #[asset(path = "Cargo.toml")]
-    Cargo, // There should be a warning: "variant `Cargo` is never constructed"
+    #[expect(dead_code)] // Without this, there would be a warning: "variant `Cargo` is never constructed"
+    Cargo,
 }
 
 fn main() {
@@ -0,0 +1,38 @@
+//! Assets embedded into a WebAssembly module.
+//!
+//! Build with `wasm-pack build --target web examples/wasm`, then use it from JavaScript:
+//!
+//! ```js
+//! import init, { asset, asset_names } from "./pkg/wasm.js";
+//! await init();
+//! console.log(asset_names());
+//! console.log(new TextDecoder().decode(asset("greeting.txt")));
+//! ```
+
+use include_assets::{include_dir_lazy, wasm};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+// include_dir! path is relative to the directory of Cargo.toml
+include_dir_lazy!(static ASSETS = "assets");
+
+/// Names of all included assets
+#[wasm_bindgen]
+pub fn asset_names() -> Vec<String> {
+    ASSETS.names().map(String::from).collect()
+}
+
+/// Content of the asset with the given name, copied into a new `Uint8Array`
+#[wasm_bindgen]
+pub fn asset(name: &str) -> Option<js_sys::Uint8Array> {
+    ASSETS.get(name).map(wasm::uint8_array)
+}
+
+/// Content of the asset with the given name as a `Uint8Array` viewing WebAssembly memory, e.g. for uploading to WebGL
+///
+/// The view must not be modified, and becomes empty once WebAssembly memory grows, so it should be used right away.
+#[wasm_bindgen]
+pub fn asset_view(name: &str) -> Option<js_sys::Uint8Array> {
+    let data: &'static [u8] = ASSETS.get(name)?;
+    // SAFETY: the asset data lives for the rest of the program, and the documentation of this function tells JavaScript code not to modify the view
+    Some(unsafe { wasm::uint8_array_view(data) })
+}
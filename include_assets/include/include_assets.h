@@ -0,0 +1,43 @@
+/* C interface to archives of the include_assets crate (feature "ffi").
+ *
+ * Archives are declared on the Rust side with include_dir_ffi!, e.g.
+ *
+ *     include_assets::include_dir_ffi!(fn game_assets = "assets");
+ *
+ * which exports a function returning the archive, declared in C as
+ *
+ *     const IncludeAssetsArchive *game_assets(void);
+ *
+ * The archive is decompressed on the first call and lives for the rest of the program.
+ * Asset data and names are not null-terminated, their lengths are returned via the `len` parameters.
+ */
+
+#ifndef INCLUDE_ASSETS_H
+#define INCLUDE_ASSETS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct IncludeAssetsArchive IncludeAssetsArchive;
+
+/* Look up an asset by null-terminated name; returns NULL if there is no such asset. */
+const uint8_t *include_assets_get(const IncludeAssetsArchive *archive, const char *name, size_t *len);
+
+/* Look up an asset by name given as pointer and length; returns NULL if there is no such asset. */
+const uint8_t *include_assets_get_n(const IncludeAssetsArchive *archive, const uint8_t *name, size_t name_len, size_t *len);
+
+/* Number of assets in the archive. */
+size_t include_assets_count(const IncludeAssetsArchive *archive);
+
+/* Name of the asset with the given index (less than include_assets_count), in unspecified order; NULL if out of range. */
+const uint8_t *include_assets_name(const IncludeAssetsArchive *archive, size_t index, size_t *len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,155 @@
+//! `cargo include-assets`: list and extract the archives embedded in an executable (requires feature `cli`).
+//!
+//! Archives can be found if they were included with the `link_section` option of `include_dir!`,
+//! which places them in the archive file format (see [`include_assets::file`]).
+
+use include_assets::{file, NamedArchive};
+use object::{Object as _, ObjectSection as _};
+
+const USAGE: &str = "\
+Usage: cargo include-assets list <executable> [--section <name>]
+       cargo include-assets extract <executable> <directory> [--section <name>] [--archive <index>]
+
+Lists or extracts the asset archives which include_dir! placed in a link section (option link_section).
+
+Options:
+    --section <name>   only search the given section (by default, the whole executable is searched)
+    --archive <index>  archive to extract if the executable contains more than one (as numbered by list)
+    -h, --help         print this help";
+
+type Error = std::boxed::Box<dyn std::error::Error>;
+
+struct Args {
+    command: std::string::String,
+    positional: std::vec::Vec<std::string::String>,
+    section: Option<std::string::String>,
+    archive: Option<usize>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = std::string::String>) -> Result<Option<Args>, Error> {
+    let (mut positional, mut section, mut archive) = (vec![], None, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--section" => section = Some(args.next().ok_or("--section requires a value")?),
+            "--archive" => {
+                archive = Some(
+                    args.next()
+                        .ok_or("--archive requires a value")?
+                        .parse()
+                        .map_err(|_| "--archive requires a number")?,
+                )
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option {arg}").into()),
+            _ => positional.push(arg),
+        }
+    }
+    if positional.is_empty() {
+        return Ok(None);
+    }
+    let command = positional.remove(0);
+    Ok(Some(Args {
+        command,
+        positional,
+        section,
+        archive,
+    }))
+}
+
+/// The bytes in which archives are searched: the given section, or the whole file
+fn search_bytes<'a>(data: &'a [u8], section: Option<&str>) -> Result<&'a [u8], Error> {
+    let Some(section) = section else {
+        return Ok(data);
+    };
+    let object = object::File::parse(data)?;
+    // Mach-O section names are given to link_section with their segment, e.g. "__DATA,__assets"
+    let name = section.rsplit(',').next().unwrap_or(section);
+    let section_data = object.section_by_name(name).ok_or_else(|| format!("no section named {section}"))?.data()?;
+    Ok(section_data)
+}
+
+fn hex(bytes: &[u8]) -> std::string::String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn list(bytes: &[u8]) -> Result<(), Error> {
+    let archives = file::find(bytes);
+    if archives.is_empty() {
+        println!("no archives found (were they included with the link_section option?)");
+    }
+    for (index, (offset, compressed)) in archives.into_iter().enumerate() {
+        println!(
+            "archive {index} at offset {offset:#x}: {:?}, {} compressed bytes{}",
+            compressed.codec,
+            compressed.data.len(),
+            if compressed.signature.is_some() { ", signed" } else { "" }
+        );
+        let archive = NamedArchive::try_load(compressed).map_err(|err| format!("couldn't load archive {index}: {err}"))?;
+        for (name, data) in archive.assets() {
+            let checksum = archive.checksum(name).expect("asset is in the archive");
+            println!("{:>12}  {}  {name}", data.len(), hex(checksum));
+        }
+    }
+    Ok(())
+}
+
+fn extract(bytes: &[u8], directory: &std::path::Path, index: Option<usize>) -> Result<(), Error> {
+    let mut archives = file::find(bytes);
+    let (_, compressed) = match (index, archives.len()) {
+        (_, 0) => return Err("no archives found (were they included with the link_section option?)".into()),
+        (None, 1) => archives.remove(0),
+        (None, n) => return Err(format!("found {n} archives, select one with --archive").into()),
+        (Some(index), n) if index < n => archives.remove(index),
+        (Some(index), n) => return Err(format!("archive {index} doesn't exist, found {n} archives").into()),
+    };
+    let archive = NamedArchive::try_load(compressed)?;
+    for (name, data) in archive.assets() {
+        // don't write outside of the target directory, whatever the names in the archive
+        let relative = std::path::Path::new(name);
+        if !relative.components().all(|component| matches!(component, std::path::Component::Normal(_))) {
+            return Err(format!("refusing to extract asset with unsafe name {name:?}").into());
+        }
+        let path = directory.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, data)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+fn run(args: Args) -> Result<(), Error> {
+    match (args.command.as_str(), args.positional.as_slice()) {
+        ("list", [executable]) => {
+            let data = std::fs::read(executable).map_err(|err| format!("couldn't read {executable}: {err}"))?;
+            list(search_bytes(&data, args.section.as_deref())?)
+        }
+        ("extract", [executable, directory]) => {
+            let data = std::fs::read(executable).map_err(|err| format!("couldn't read {executable}: {err}"))?;
+            extract(search_bytes(&data, args.section.as_deref())?, directory.as_ref(), args.archive)
+        }
+        _ => Err(format!("invalid arguments\n\n{USAGE}").into()),
+    }
+}
+
+fn main() -> std::process::ExitCode {
+    let mut args = std::env::args().skip(1).peekable();
+    // cargo passes the name of the subcommand as first argument
+    args.next_if(|arg| arg == "include-assets");
+    let result = match parse_args(args) {
+        Ok(Some(args)) => run(args),
+        Ok(None) => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
@@ -32,7 +32,19 @@ assert_eq!(hello_asset, b"Hello, world!");
 println!("{} assets were included", archive.number_of_assets());
 ```
 
-For more examples, see [`include_dir!`].
+Loading only fails in case of data corruption or internal bugs, in which case [`NamedArchive::load`] panics.
+Use [`NamedArchive::try_load`] to handle a [`LoadError`](error::LoadError) instead:
+
+```
+use include_assets::{NamedArchive, include_dir};
+
+match NamedArchive::try_load(include_dir!("assets")) {
+    Ok(archive) => println!("{} assets were included", archive.number_of_assets()),
+    Err(err) => eprintln!("couldn't load assets: {err}"),
+}
+```
+
+For more examples, see [`include_dir!`] and [`NamedArchive`].
 
 
 # Include assets and look up data by enum variant
@@ -66,11 +78,222 @@ assert_eq!(&strings[Asset::Hello], "Hello, world!");
 As indicated by the code comments, this method has the advantage that use of assets is checked at compile time.
 Assets that are not present cannot be used, and unused assets cause compile-time warnings.
 
-On the other hand, assets have to be declared manually, and cannot be iterated over.
-(A `map` function is provided though.)
+On the other hand, assets have to be declared manually.
+They can be transformed with a `map` function, and iterated over with [`EnumArchive::iter`] if the enum is declared with `iterable = true`.
 
 For more examples, see [`EnumArchive`].
 
+# Archive files
+
+Instead of embedding an archive in the executable, it can be stored in a file, e.g. next to the executable.
+This keeps the executable small, which may be useful during development.
+Write an archive file with `write_to` (typically from a small helper program), then load it at runtime with [`NamedArchive::load_file`]:
+
+```
+use include_assets::{NamedArchive, include_dir};
+
+let path = std::env::temp_dir().join("include_assets_doctest_archive_file.bin");
+include_dir!("assets").write_to(std::fs::File::create(&path).unwrap()).unwrap();
+
+let archive = NamedArchive::load_file(&path).unwrap();
+assert_eq!(&archive["hello.txt"], b"Hello, world!");
+# include_dir!("assets", checksum_size = 8).write_to(std::fs::File::create(&path).unwrap()).unwrap();
+# assert_eq!(NamedArchive::load_file(&path).unwrap().checksum("hello.txt"), archive.checksum("hello.txt"));
+# std::fs::remove_file(&path).unwrap();
+#
+# // sizes stored as `u32`s, as in files of version 1 and 2, can still be loaded
+# let mut compressed = include_dir!("assets", compression = "uncompressed");
+# let sizes: Vec<u8> = NamedArchive::load(compressed).assets().flat_map(|(_name, data)| (data.len() as u32).to_le_bytes()).collect();
+# assert!((compressed.uncompressed_sizes_size as usize) < sizes.len());
+# compressed.compressed_sizes = &sizes;
+# compressed.uncompressed_sizes_size = 0;
+# assert_eq!(NamedArchive::load(compressed).assets().collect::<Vec<_>>(), archive.assets().collect::<Vec<_>>());
+```
+
+Once loaded, there is no difference between embedded archives and archives loaded from a file.
+The file format is described in [`file`].
+
+With the `mmap` feature, very large archive files can be memory mapped instead of read into main memory using `file::MappedArchive`.
+Opening the file is unsafe, since the program must ensure that it isn't modified while it is mapped.
+
+```
+# #[cfg(feature = "mmap")] {
+use include_assets::{file::MappedArchive, include_dir};
+
+let path = std::env::temp_dir().join("include_assets_doctest_mapped_archive.bin");
+include_dir!("assets").write_to(std::fs::File::create(&path).unwrap()).unwrap();
+
+// SAFETY: nothing modifies the file while it is mapped
+let mapped = unsafe { MappedArchive::open(&path) }.unwrap();
+assert_eq!(mapped.get("hello.txt"), Some(b"Hello, world!".as_slice()));
+# assert!(mapped.try_archive().is_ok());
+# drop(mapped);
+# // uncompressed assets are served in place, sharded ones decompress only their shards, and single streams the whole archive
+# for compressed in [
+#     include_dir!("assets", compression = "uncompressed").into_any_codec(),
+#     include_dir!("assets", compression = "uncompressed", single_stream = true, checksum_size = 8).into_any_codec(),
+#     include_dir!("assets", compression = "lz4", shard_size = 4).into_any_codec(),
+#     include_dir!("assets", compression = "lz4", shard_size = 4, single_stream = true).into_any_codec(),
+#     include_dir!("assets", compression = "lz4", single_stream = true).into_any_codec(),
+#     include_dir!("assets", compression = "lz4", align = 16, shard_size = 4).into_any_codec(),
+# ] {
+#     compressed.write_to(std::fs::File::create(&path).unwrap()).unwrap();
+#     let mapped = unsafe { MappedArchive::open(&path) }.unwrap();
+#     assert_eq!(mapped.get("hello.txt"), Some(b"Hello, world!".as_slice()));
+#     assert_eq!(mapped.try_get("unused.txt").unwrap(), include_assets::NamedArchive::load(compressed).get("unused.txt"));
+#     assert_eq!(mapped.try_get("missing.txt").unwrap(), None);
+#     if compressed.codec.tag() == 0 {
+#         assert!(mapped.compressed().data.as_ptr_range().contains(&mapped.get("hello.txt").unwrap().as_ptr()));
+#     }
+# }
+# let mut bytes = include_dir!("assets", compression = "uncompressed").to_file_bytes().unwrap();
+# let start = bytes.windows(5).position(|window| window == b"Hello").unwrap();
+# bytes[start] = b'J';
+# std::fs::write(&path, &bytes).unwrap();
+# let mapped = unsafe { MappedArchive::open(&path) }.unwrap();
+# assert!(mapped.try_get("hello.txt").is_err());
+# assert!(mapped.try_get("unused.txt").is_ok());
+# assert!(mapped.archive().try_get("hello.txt").is_err());
+# drop(mapped);
+# std::fs::remove_file(&path).unwrap();
+# }
+```
+
+Archive files from untrusted sources, e.g. downloaded ones, should be loaded with [`NamedArchive::try_load_untrusted`] instead.
+It limits the memory used for loading, and rejects malformed files with an error instead of panicking:
+
+```
+use include_assets::{NamedArchive, file, include_dir};
+
+let bytes = include_dir!("assets").to_file_bytes().unwrap();
+let archive = NamedArchive::try_load_untrusted(file::parse(&bytes).unwrap(), 1 << 20).unwrap();
+assert_eq!(&archive["hello.txt"], b"Hello, world!");
+assert!(NamedArchive::try_load_untrusted(file::parse(&bytes).unwrap(), 100).is_err());
+
+// corrupted files fail to load, but never panic
+for i in 0..bytes.len() {
+    let mut corrupted = bytes.clone();
+    corrupted[i] ^= 0xff;
+    if let Ok(compressed) = file::parse(&corrupted) {
+        let _ = NamedArchive::try_load_untrusted(compressed, 1 << 20);
+    }
+}
+# #[cfg(all(feature = "lz4", feature = "deflate"))]
+# for bytes in [
+#     include_dir!("assets", compression = "uncompressed", single_stream = true).to_file_bytes().unwrap(),
+#     include_dir!("assets", compression = "lz4", shard_size = 8).to_file_bytes().unwrap(),
+#     include_dir!("assets", compression = "deflate", checksum_size = 8, align = 16).to_file_bytes().unwrap(),
+# ] {
+#     assert_eq!(&NamedArchive::try_load_untrusted(file::parse(&bytes).unwrap(), 1 << 20).unwrap()["hello.txt"], b"Hello, world!");
+#     for i in 0..bytes.len() {
+#         let mut corrupted = bytes.clone();
+#         corrupted[i] ^= 0xff;
+#         if let Ok(compressed) = file::parse(&corrupted) {
+#             let _ = NamedArchive::try_load_untrusted(compressed, 1 << 20);
+#         }
+#     }
+# }
+```
+
+# Signatures
+
+With the `signature` feature, named archives can be signed at compile time using the `signing_key` option.
+At runtime, `NamedArchive::load_verified` rejects archives whose signature doesn't match the given ed25519 public key.
+This is mostly useful for archive files, which may be modified by users.
+Everything that affects the loaded assets is signed, including the codec, the names of private assets, and the MIME type overrides.
+Since archive files don't store private names and MIME type overrides, archives with these options don't verify once written to a file.
+The key in the example below is public, so it is only fit for tests: generate your own secret key and keep it out of version control.
+
+```
+# #[cfg(feature = "signature")] {
+use include_assets::{NamedArchive, include_dir};
+
+const PUBLIC_KEY: [u8; 32] = [
+    121, 181, 86, 46, 143, 230, 84, 249, 64, 120, 177, 18, 232, 169, 139, 167, 144, 31, 133, 58, 230, 149, 190, 215, 224, 227, 145, 11, 173, 4, 150, 100,
+];
+
+let archive = NamedArchive::load_verified(include_dir!("assets", signing_key = "keys/insecure_test_only_signing_key.hex"), &PUBLIC_KEY).unwrap();
+assert_eq!(&archive["hello.txt"], b"Hello, world!");
+
+// unsigned archives are rejected
+assert!(NamedArchive::load_verified(include_dir!("assets"), &PUBLIC_KEY).is_err());
+# let signed = include_dir!("assets", signing_key = "keys/insecure_test_only_signing_key.hex", checksum_size = 16);
+# assert!(NamedArchive::load_verified(signed, &PUBLIC_KEY).is_ok());
+# let mut sharded = include_dir!("assets", signing_key = "keys/insecure_test_only_signing_key.hex", shard_size = 4);
+# assert!(NamedArchive::load_verified(sharded, &PUBLIC_KEY).is_ok());
+# let mut shards = sharded.shards.to_vec();
+# shards[0] ^= 1;
+# sharded.shards = &shards;
+# assert!(matches!(NamedArchive::load_verified(sharded, &PUBLIC_KEY), Err(include_assets::error::LoadError::Signature(_))));
+# let mut private = include_dir!("assets", signing_key = "keys/insecure_test_only_signing_key.hex", private = "unused.txt");
+# assert!(NamedArchive::load_verified(private, &PUBLIC_KEY).is_ok());
+# private.private = &[];
+# assert!(matches!(NamedArchive::load_verified(private, &PUBLIC_KEY), Err(include_assets::error::LoadError::Signature(_))));
+# let mut typed = include_dir!("assets", signing_key = "keys/insecure_test_only_signing_key.hex", mime_types = "txt=text/markdown");
+# assert!(NamedArchive::load_verified(typed, &PUBLIC_KEY).is_ok());
+# typed.mime_types = &[("txt", "text/html")];
+# assert!(matches!(NamedArchive::load_verified(typed, &PUBLIC_KEY), Err(include_assets::error::LoadError::Signature(_))));
+# let mut relabeled = include_dir!("assets", signing_key = "keys/insecure_test_only_signing_key.hex", compression = "zstd", level = 3);
+# assert!(NamedArchive::load_verified(relabeled, &PUBLIC_KEY).is_ok());
+# relabeled.codec.level = 4;
+# assert!(matches!(NamedArchive::load_verified(relabeled, &PUBLIC_KEY), Err(include_assets::error::LoadError::Signature(_))));
+# let file = include_dir!("assets", signing_key = "keys/insecure_test_only_signing_key.hex").to_file_bytes().unwrap();
+# assert!(NamedArchive::load_verified(include_assets::file::parse(&file).unwrap(), &PUBLIC_KEY).is_ok());
+# }
+```
+
+# Testing
+
+Code that takes a [`NamedArchive`] can be tested without the `include_dir!` macro or the file system, by building an archive from names and contents in memory:
+
+```
+use include_assets::NamedArchive;
+
+fn greeting(assets: &NamedArchive) -> Option<&str> {
+    assets.get_str("greeting.txt")?.ok()
+}
+
+let assets = NamedArchive::from_iter([("greeting.txt", b"Hi!".as_slice())]);
+assert_eq!(greeting(&assets), Some("Hi!"));
+assert_eq!(greeting(&NamedArchive::from_iter(Vec::<(String, Vec<u8>)>::new())), None);
+```
+
+# WebAssembly
+
+Assets can be embedded into `wasm32-unknown-unknown` bundles.
+The zstd codec depends on a C library, so disable the default features and enable `wasm` instead:
+
+```toml
+include_assets = { version = "0.1", default-features = false, features = ["wasm"] }
+```
+
+This makes deflate, which has a small pure Rust decoder, the default compression.
+Loading doesn't need threads or a file system (except for `dev = true`, see [Options](#options)).
+Functions using threads, e.g. [`NamedArchive::load_in_background`], work on the current thread instead.
+The [`wasm`] module provides helpers to pass assets to JavaScript as `Uint8Array`s.
+See `examples/wasm` for a complete example.
+
+# Embedded (`no_std`)
+
+Without the default feature `std`, the crate only needs `alloc`, so firmware can keep compressed assets in flash and decompress them into RAM.
+lz4 is the only codec without `std`:
+
+```toml
+include_assets = { version = "0.1", default-features = false, features = ["lz4"] }
+```
+
+Everything that needs a file system, threads or synchronization is unavailable, e.g. [`include_dir_lazy!`], [`LazyEnumArchive`], the [`AssetStruct`](derive@AssetStruct) derive, archive files and `dev = true`.
+Load archives with [`NamedArchive::load`] or [`EnumArchive::load`] instead, and [`NamedArchive::leak`] them to get `'static` assets.
+
+```
+# #[cfg(feature = "lz4")] {
+use include_assets::{include_dir, NamedArchive};
+
+let assets: &'static NamedArchive = NamedArchive::load(include_dir!("assets", compression = "lz4")).leak();
+assert_eq!(&assets["hello.txt"], b"Hello, world!");
+# }
+```
+
 # Build script
 
 It is probably a good idea to tell Cargo to rebuild the executable whenever an asset changes.
@@ -89,14 +312,15 @@ The macros that include assets have a few optional arguments.
 These options must always be specified in the form of a `identifier = literal` assignment, where `identifier` is one of the following values:
 
 - `compression`:
-   Specifies the compression algorithm to be used.
-   The default choice depends on crate features and is not bound by semver.
-   It attempts to strike a balance between compression speed, decompression speed, and size reduction.
-   The following values are potentially allowed:
-   - `"zstd"` (requires feature `zstd`),
-   - `"lz4"` (requires feature `lz4`),
-   - `"deflate"` (requires feature `deflate`), and
-   - `"uncompressed"`. This option should generally not be used except for assets which are already compressed (e.g. JPEG/PNG/FLAC).
+  Specifies the compression algorithm to be used.
+  The default choice depends on crate features and is not bound by semver.
+  It attempts to strike a balance between compression speed, decompression speed, and size reduction.
+  The following values are potentially allowed:
+  - `"zstd"` (requires feature `zstd`),
+  - `"lz4"` (requires feature `lz4`),
+  - `"deflate"` (requires feature `deflate`), and
+  - `"uncompressed"`. This option should generally not be used except for assets which are already compressed (e.g. JPEG/PNG/FLAC),
+    or for very large assets which `NamedArchive::load_static` should leave in the executable image.
 - `level`:
   Compression level parameter.
   Meaning and allowed values depend on the chosen compression algorithm.
@@ -123,12 +347,103 @@ These options must always be specified in the form of a `identifier = literal` a
     If the link points to a directory, files in the directory are not included via the link.
   - `links = "follow"`:
     Symbolic links are treated as if they were the target directory or file.
+    Links must point inside the directory, unless `allow_outside_base = true` is given.
+- `allow_outside_base`:
+  By default, files outside the asset directory are rejected at compile time, so that a path like `"../../secret"` can't include files by accident:
+  asset paths of the [`AssetEnum`](derive@AssetEnum) derive must be inside their base path, and symbolic links followed by the [`include_dir!`] macro must point inside the directory
+  (after resolving `..` and symbolic links).
+  Use `allow_outside_base = true` to allow files anywhere.
+- `signing_key`:
+  Path of a file containing an ed25519 secret key as 64 hexadecimal digits, e.g. `signing_key = "keys/secret.hex"`.
+  The compressed archive is signed with this key, see [Signatures](#signatures).
+  This option is only available for the [`include_dir!`] macro and requires feature `signature`.
+  Never include the secret key file itself in an archive!
+- `dev`:
+  If `dev = true`, assets are not included in debug builds.
+  Instead, they are read from the directory when the archive is loaded, which makes compilation much faster for big asset sets.
+  Release builds include the assets as usual.
+  Symbolic links are always followed in this mode.
+  The directory is still listed (but no file is read) at compile time, so that `private` names are checked and `names_module` can be generated.
+  This option is only available for the [`include_dir!`] macro.
+  (Strictly speaking, this depends on whether the macro itself is compiled with debug assertions, which is the case for debug builds with Cargo's default settings.)
+- `mime_types`:
+  MIME types for file extensions, overriding the types guessed by `NamedArchive::mime` (requires feature `mime`), e.g. `mime_types = "wgsl=text/wgsl, glb=model/gltf-binary"`.
+  Extensions are case insensitive.
+  This option is only available for the [`include_dir!`] macro.
+- `private`:
+  Names of assets that can be looked up by name, but are never listed, separated by commas, e.g. `private = "config/secrets.toml, internal/"`.
+  A name ending with `/` applies to all assets in that directory and its subdirectories.
+  Private assets are left out of `NamedArchive::names`, `NamedArchive::assets` and everything based on them, e.g. `NamedArchive::list_dir` and directory listings of the integrations,
+  so that one archive can hold both public files (e.g. a web root) and internal data.
+  Each name must match at least one asset.
+  This option is only available for the [`include_dir!`] macro, and is not stored in [archive files](file).
+- `shard_size`:
+  Split the asset data into shards of the given number of (uncompressed) bytes, which are compressed independently, e.g. `shard_size = 1048576`.
+  This allows reporting progress while loading (see `NamedArchive::try_load_with_progress`), at the cost of slightly worse compression.
+  Compressed shards are kept between builds (in `OUT_DIR` if the crate has a build script, otherwise in the target directory), so that only shards whose data changed are compressed again.
+  It also lowers the memory needed to compile large asset directories, since only one shard is held uncompressed at a time.
+  By default, all asset data is compressed as a single stream.
+  This option is only available for the [`include_dir!`] macro.
+- `checksum_size`:
+  Store asset checksums truncated to the given number of bytes, between 8 and 64, e.g. `checksum_size = 16`.
+  By default, the full 64-byte Blake2b checksum is stored for each asset, which dominates the size of the archive for thousands of small assets.
+  Shorter checksums detect corruption only slightly less reliably, but make [signatures](#signatures) weaker since asset data with the same truncated checksum is easier to find.
+  `NamedArchive::checksum` still returns full checksums, which are computed while loading.
+  This option is only available for the [`include_dir!`] macro.
+- `single_stream`:
+  If `single_stream = true`, the asset names, sizes, and checksums are compressed in one stream together with the asset data, instead of separately.
+  This makes small archives smaller and faster to load, since each separately compressed section has some overhead.
+  Opening a single asset with `CompressedNamedArchive::reader` needs to decompress the end of the data then.
+  This option is only available for the [`include_dir!`] macro.
+- `names_module`:
+  Instead of the compressed archive, generate a module with the given name, e.g. `names_module = "assets"`, so the macro must be used where items are allowed.
+  The module contains a `&str` constant for the name of each asset, e.g. `assets::TEXTURES_PLAYER_PNG` for `textures/player.png`, and a function `archive()` returning the compressed archive.
+  This turns typos in asset names into compilation errors.
+  Names are converted to constants by replacing all characters except ASCII letters and digits with `_` and converting to uppercase, prefixed with `_` if they don't start with a letter.
+  Names which map to the same constant are rejected.
+  This option is only available for the [`include_dir!`] macro.
+- `link_section`:
+  Name of the linker section in which the compressed asset data is placed, e.g. `link_section = ".assets"`.
+  This allows post-build tooling to locate, strip, or replace the data without recompiling.
+  The [`include_dir!`] macro places the whole archive there in the [archive file format](file), so that it can be found with [`file::find`],
+  e.g. by the `cargo include-assets` tool (feature `cli`), which lists and extracts the archives in an executable.
+  The section name must be valid for the target's object file format (e.g. `"__DATA,__assets"` on macOS).
+  Note that this is an unsafe attribute, so it is rejected by `#![forbid(unsafe_code)]`.
+  By default, the compiler chooses the section.
+- `align`:
+  Alignment of the compressed asset data in bytes, e.g. `align = 4096`.
+  Must be a power of two.
+  For the [`include_dir!`] macro, each asset also starts at a multiple of the alignment (at most 64 bytes) in the decompressed data,
+  so that assets can be reinterpreted as slices of typed data (e.g. with `bytemuck`) without copying, see [`NamedArchive`].
+  This pads the decompressed data with up to `align - 1` zero bytes per asset.
+  By default, the data is not aligned.
+- `iterable`:
+  If `iterable = true`, the [`AssetEnum`](derive@AssetEnum) derive generates a function `variants()` returning an iterator of all variants, and [`EnumArchive::iter`] can be used.
+  Since this constructs every variant, unused variants no longer cause compile-time warnings.
+  This option is only available for the [`AssetEnum`](derive@AssetEnum) derive.
+- `methods`:
+  If `methods = true`, the [`AssetEnum`](derive@AssetEnum) derive generates the methods `path` and `const fn size` for the enum,
+  returning the path of the asset file relative to the base path and the size of the asset in bytes.
+  These methods are opt-in, so that they don't clash with methods of the same name which the enum already has.
+  Otherwise, use [`EmbeddedAssets::asset_path`] and [`EmbeddedAssets::asset_size`], or the constants `AssetEnum::PATHS` and `AssetEnum::SIZES`.
+  This option is only available for the [`AssetEnum`](derive@AssetEnum) derive.
+- `dedup`:
+  By default, variants whose files are the same (after resolving symbolic links) share their data, which is only included once.
+  Use `dedup = false` to include the data for each variant separately.
+  This option is only available for the [`AssetEnum`](derive@AssetEnum) derive.
+- `per_asset`:
+  If `per_asset = true`, each asset is compressed independently instead of all assets as a single stream.
+  This usually compresses worse, but allows [`LazyEnumArchive`] to decompress only the assets which are actually used.
+  This option is only available for the [`AssetEnum`](derive@AssetEnum) derive.
 
 # Limitations
 
-At runtime, main memory needs to be big enough to hold all assets at the same time in compressed and uncompressed form.
-At compile time, main memory needs to be big enough to hold all assets at the same time in compressed form and twice in uncompressed form.
-(It would be possible to optimize compile time memory use, but if you can only barely compile it, users probably can't run it.)
+At runtime, main memory needs to be big enough to hold all assets at the same time in compressed and uncompressed form,
+unless uncompressed archives are loaded with `NamedArchive::load_static`.
+At compile time, the [`include_dir!`] macro reads the asset files one at a time.
+With `shard_size`, it holds only the current shard and the file being read in uncompressed form, plus the compressed output;
+otherwise main memory needs to be big enough to hold all assets once in uncompressed form, plus the compressed output.
+The [`AssetEnum`](derive@AssetEnum) derive holds all assets once in uncompressed form, plus the compressed output.
 
 The total size of each asset archive cannot exceed `u32::MAX` (4 GiB).
 Each asset archive can contain at most `u32::MAX` (roughly 4e9) distinct assets.
@@ -137,183 +452,2309 @@ If your use case exceeds these limits, reconsider if this is really the right ap
 `usize` is required to be at least 32 bits wide.
 */
 
-pub use include_assets_decode::named::NamedArchive;
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::needless_doctest_main)] // the build script example needs a main function
 
-/// Include all files in a directory in compressed form.
-/// At runtime, the files can be decompressed and their contents looked up by relative path name.
+/// Archive of named assets, loaded (decompressed) at runtime.
 ///
-/// # Usage
+/// # Examples
 ///
-/// The first argument must be a string literal specifying the path of the directory to be included.
-/// This can be an absolute path or a path relative to the [`CARGO_MANIFEST_DIR`](https://doc.rust-lang.org/cargo/reference/environment-variables.html#environment-variables-cargo-sets-for-crates).
-/// This path can be absolute (though this should be avoided) or relative to `cargo`'s working directory.
+/// Look up assets by name, as string, or by path:
 ///
-/// In addition, any of the options described in the [`crate`] level documentation may be used to specify compression options.
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
 ///
-/// # Examples
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// assert_eq!(archive.get("hello.txt"), Some(&b"Hello, world!"[..]));
+/// assert_eq!(archive.get_str("hello.txt"), Some(Ok("Hello, world!")));
+/// assert_eq!(archive.get_path(std::path::Path::new("./hello.txt")), Some(&b"Hello, world!"[..]));
+/// ```
 ///
-/// Include the directory "assets":
+/// Paths are only accepted by `get_path` and `contains_path`, while `get` and indexing take the asset name as `&str`.
+///
+/// Look up several assets at once, with a single check that all of them exist:
 ///
 /// ```
 /// use include_assets::{NamedArchive, include_dir};
+///
 /// let archive = NamedArchive::load(include_dir!("assets"));
-/// println!("{} assets were included", archive.number_of_assets());
+/// let [hello, unused] = archive.get_many(["hello.txt", "unused.txt"]).unwrap();
+/// assert_eq!(hello, b"Hello, world!");
+/// assert_eq!(archive.get_all(vec![String::from("unused.txt")]), Some(vec![unused]));
+/// assert_eq!(archive.get_many(["hello.txt", "missing.txt"]), None);
 /// ```
 ///
-/// Include the directory "assets".
-/// Assets will be compressed using zstd at level 5.
-/// Symbolic links will be treated as if they were the file/directory pointed to:
+/// Checksums are computed at compile time and can be used e.g. as HTTP ETags:
 ///
 /// ```
 /// use include_assets::{NamedArchive, include_dir};
-/// let archive = NamedArchive::load(include_dir!("assets", compression = "zstd", level = 5, links = "follow"));
-/// println!("{} assets were included", archive.number_of_assets());
+///
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// let etag = format!("\"{}\"", archive.checksum_hex("hello.txt").unwrap());
+/// assert_eq!(archive.checksum("hello.txt").unwrap().len(), 64);
 /// ```
 ///
-/// Include the two directories "assets" (compressed with zstd level 22) and "other_assets" (lz4 compressed):
+/// Get everything about an asset at once, e.g. for serving it:
 ///
 /// ```
 /// use include_assets::{NamedArchive, include_dir};
-/// let archive1 = NamedArchive::load(include_dir!("assets", compression = "zstd", level = 22));
-/// let archive2 = NamedArchive::load(include_dir!("other_assets", compression = "lz4"));
-/// println!("{} assets were included", archive1.number_of_assets() + archive2.number_of_assets());
+///
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// let entry = archive.entry("hello.txt").unwrap();
+/// assert_eq!(entry.data, b"Hello, world!");
+/// assert_eq!(entry.size(), 13);
+/// assert_eq!(entry.checksum_hex(), archive.checksum_hex("hello.txt").unwrap());
+/// # #[cfg(feature = "mime")]
+/// assert_eq!(entry.mime, "text/plain");
 /// ```
 ///
-/// # Limitations
+/// Report progress while loading a big archive:
 ///
-/// - The directory may only contains files, directories, or symbolic links which point (directly or indirectly) to a file or directory.
-///   Special files are not allowed.
-/// - Paths must be UTF-8
-/// - Paths must not contain null bytes (U+0000)
-pub use include_assets_encode::include_dir;
-
-/// Derive the AssetEnum trait.
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
 ///
-/// The trait should _never_ be implemented or used manually, _only_ with this derive macro.
-/// Details (methods, associated types/constants) for the trait are not bound by semver!
+/// let archive = NamedArchive::try_load_with_progress(include_dir!("assets", shard_size = 4), |done, total| {
+///     println!("loading assets: {done}/{total} bytes");
+/// })
+/// .unwrap();
+/// assert_eq!(&archive["hello.txt"], b"Hello, world!");
+/// ```
 ///
-/// This macro only works for enums.
-/// Every enum variant must be unit (i.e. have no fields), and must not have an explicit discriminator.
-/// There needs to be an outer attribute `#[archive(base_path = "path")]` on the enum specifying the base path of all assets.
-/// This can be an absolute path or a path relative to the [`CARGO_MANIFEST_DIR`](https://doc.rust-lang.org/cargo/reference/environment-variables.html#environment-variables-cargo-sets-for-crates).
-/// Every variant needs to have an attribute `#[asset(path = "relative path")]` specifying the (compile time) path of the asset relative to the base path.
+/// Stream a big asset without decompressing the whole archive, e.g. a video:
 ///
-/// Additionally, options described in the [`crate`] level documentation may be added to the outer enum attribute to specify compression options.
+/// ```
+/// use include_assets::include_dir;
+/// use std::io::Read as _;
 ///
-/// # Examples
+/// let compressed = include_dir!("assets", shard_size = 4);
+/// let mut reader = compressed.reader("hello.txt").unwrap().unwrap();
+/// let mut chunk = [0u8; 5];
+/// reader.read_exact(&mut chunk).unwrap();
+/// assert_eq!(&chunk, b"Hello");
+/// assert_eq!(reader.remaining(), 8);
+/// ```
 ///
-/// Basic use.
-/// Include `"assets/hello.txt"` and `"assets/unused.txt"` allowing lookup by `Asset::Hello` and `Asset::Unused`, respectively.
-/// If the variant `Unused` is never used (as in this example), this will cause a compile-time warning.
+/// Checksums of big archives are always verified on several threads, and the shards of a big archive can also be decompressed on several threads:
 ///
 /// ```
-/// use include_assets::EnumArchive;
+/// use include_assets::{NamedArchive, include_dir};
 ///
-/// #[derive(include_assets::AssetEnum)]
-/// #[archive(base_path = "assets")]
-/// enum Asset {
-///     #[asset(path = "hello.txt")]
-///     Hello,
-///     #[asset(path = "unused.txt")]
-///     Unused,
-/// }
+/// let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+/// let archive = NamedArchive::try_load_parallel(include_dir!("assets", shard_size = 4), threads).unwrap();
+/// assert_eq!(&archive["hello.txt"], b"Hello, world!");
+/// ```
+///
+/// Load an archive on another thread while doing other work:
 ///
-/// let archive = EnumArchive::<Asset>::load();
-/// assert_eq!(&archive[Asset::Hello], b"Hello, world!");
 /// ```
+/// use include_assets::{NamedArchive, include_dir};
 ///
-/// For more information on how to use the decompressed assets, see [`EnumArchive`].
+/// let handle = NamedArchive::load_in_background(include_dir!("assets"));
+/// // e.g. create a window or show a splash screen
+/// let archive = handle.join();
+/// assert_eq!(&archive["hello.txt"], b"Hello, world!");
+/// ```
 ///
-/// A more specific `derive` with the same assets.
-/// Assets will be compressed using zstd at level 5.
-/// The enum representation is explicitely chosen as `u8`:
+/// In async code, e.g. in a web server, an archive can be loaded without blocking the runtime:
 ///
 /// ```
-/// #[derive(include_assets::AssetEnum)]
-/// #[archive(base_path = "assets", compression = "zstd", level = 5)]
-/// #[repr(u8)]
-/// enum Asset {
-///     #[asset(path = "hello.txt")]
-///     Hello,
-///     #[asset(path = "unused.txt")]
-///     Unused,
+/// use include_assets::{NamedArchive, include_dir};
+/// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+/// #     struct Unparker(std::thread::Thread);
+/// #     impl std::task::Wake for Unparker {
+/// #         fn wake(self: std::sync::Arc<Self>) {
+/// #             self.0.unpark();
+/// #         }
+/// #     }
+/// #     let waker = std::sync::Arc::new(Unparker(std::thread::current())).into();
+/// #     let mut context = std::task::Context::from_waker(&waker);
+/// #     let mut future = std::pin::pin!(future);
+/// #     loop {
+/// #         match future.as_mut().poll(&mut context) {
+/// #             std::task::Poll::Ready(output) => return output,
+/// #             std::task::Poll::Pending => std::thread::park(),
+/// #         }
+/// #     }
+/// # }
+///
+/// async fn start_server() {
+///     let archive = NamedArchive::load_async(include_dir!("assets")).await.unwrap();
+///     assert_eq!(&archive["hello.txt"], b"Hello, world!");
 /// }
+/// # block_on(start_server());
 /// ```
 ///
-/// Assets may not have fields or explicit discriminators:
+/// The decompressed data can be stored in a buffer provided by the caller, e.g. from a memory pool:
 ///
-/// ```compile_fail
-/// #[derive(include_assets::AssetEnum)]
-/// #[archive(base_path = "assets")]
-/// enum Asset {
-///     #[asset(path = "hello.txt")]
-///     Hello(String), // field is not allowed
-///     #[asset(path = "hello.txt")]
-///     Hello2 { who: String }, // struct-like variant is not allowed
-///     #[asset(path = "unused.txt")]
-///     Unused = 42, // explicit discriminator is not allowed
-/// }
 /// ```
-pub use include_assets_encode::AssetEnum;
-
-#[doc(hidden)]
-pub use include_assets_decode::enums::AssetEnum;
-
-/// Archive holding uncompressed data for an [`AssetEnum`](derive@`AssetEnum`).
+/// use include_assets::{NamedArchive, include_dir};
 ///
-/// An `AssetEnum` is an `enum` type with unit variants.
-/// Each variant corresponds to an asset.
-/// An `EnumArchive` for a given `AssetEnum` allows looking up the enum data via indexing.
+/// let compressed = include_dir!("assets");
+/// let buffer = Vec::with_capacity(compressed.uncompressed_data_size as usize);
+/// let archive = NamedArchive::try_load_into(compressed, buffer).unwrap();
+/// assert_eq!(archive.get("hello.txt"), Some(b"Hello, world!".as_slice()));
+/// println!("archive uses {} bytes", archive.memory_usage());
 ///
-/// Iteration over all assets is not possible, but mapping the data is.
+/// // return the buffer to the pool
+/// let buffer = archive.into_buffer();
+/// assert_eq!(buffer.len(), compressed.uncompressed_data_size as usize);
+/// ```
 ///
-/// # Examples
+/// Checksums of large archives can be verified the first time each asset is accessed instead of while loading, which also skips unused assets:
 ///
-/// Include assets and look up data by name:
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
 ///
+/// let archive = NamedArchive::try_load_lazy(include_dir!("assets")).unwrap();
+/// assert_eq!(&archive["hello.txt"], b"Hello, world!");
+///
+/// // corrupted assets are only detected once they are accessed
+/// let mut compressed = include_dir!("assets", compression = "uncompressed");
+/// let mut data = compressed.data.to_vec();
+/// let start = data.windows(5).position(|window| window == b"Hello").unwrap();
+/// data[start] = b'J';
+/// compressed.data = &data;
+/// let archive = NamedArchive::try_load_lazy(compressed).unwrap();
+/// assert!(archive.try_get("hello.txt").is_err());
+/// assert!(archive.try_get(".gitkeep").unwrap().is_some());
+/// // listing names doesn't access the data
+/// assert_eq!(archive.names().count(), 3);
+/// # assert!(format!("{archive:?}").contains("\"hello.txt\": 13"));
+/// # assert!(std::panic::catch_unwind(|| archive.get("hello.txt")).is_err());
+/// # assert!(NamedArchive::try_load(compressed).is_err());
+/// # assert_eq!(archive.try_get("missing.txt").unwrap(), None);
+/// # let archive = NamedArchive::try_load_lazy(include_dir!("assets", compression = "uncompressed", checksum_size = 8));
+/// # assert_eq!(archive.unwrap().checksum("hello.txt"), NamedArchive::load(include_dir!("assets")).checksum("hello.txt"));
+/// # let mut archive = NamedArchive::try_load_lazy(include_dir!("assets")).unwrap();
+/// # assert_eq!(archive.take("hello.txt").as_deref(), Some(b"Hello, world!".as_slice()));
+/// # assert_eq!(archive.into_copied_assets().count(), 2);
 /// ```
-/// use include_assets::EnumArchive;
 ///
-/// #[derive(include_assets::AssetEnum)]
-/// #[archive(base_path = "assets")]
-/// enum Asset {
-///     #[asset(path = "hello.txt")]
-///     Hello,
-///     #[asset(path = "unused.txt")]
-///     _Unused,
+/// Shipped applications can check that their assets are intact, e.g. when started with `--self-check`:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// let archive = NamedArchive::load_static(include_dir!("assets", compression = "uncompressed"));
+/// let checks = archive.verify_all();
+/// for check in checks.iter().filter(|check| !check.is_ok()) {
+///     eprintln!("{check}");
 /// }
+/// assert!(checks.iter().all(|check| check.is_ok()));
+/// # assert_eq!(checks.iter().map(|check| check.name).collect::<Vec<_>>(), [".gitkeep", "hello.txt", "unused.txt"]);
+/// # assert_eq!(checks[1].to_string(), "hello.txt: ok");
+/// # let mut compressed = include_dir!("assets", compression = "uncompressed");
+/// # let mut data = compressed.data.to_vec();
+/// # let start = data.windows(5).position(|window| window == b"Hello").unwrap();
+/// # data[start] = b'J';
+/// # compressed.data = &data;
+/// # let archive = NamedArchive::try_load_lazy(compressed).unwrap();
+/// # let checks = archive.verify_all();
+/// # assert!(!checks[1].is_ok() && checks[0].is_ok());
+/// # assert!(checks[1].to_string().starts_with("hello.txt: checksum mismatch: expected "));
+/// # assert_eq!(checks[1].expected, NamedArchive::load(include_dir!("assets")).checksum("hello.txt").unwrap());
+/// ```
+///
+/// Archives with many small assets can be made smaller by storing truncated checksums (see the `checksum_size` option):
 ///
-/// let archive = EnumArchive::<Asset>::load();
-/// assert_eq!(&archive[Asset::Hello], b"Hello, world!");
 /// ```
+/// use include_assets::{NamedArchive, include_dir};
 ///
-/// Include data and apply some transformation (here we convert the `&[u8]` data to `String` since all assets are plain text).
-/// The transformed data can be looked up by enum variant:
+/// let compressed = include_dir!("assets", checksum_size = 16);
+/// assert_eq!(compressed.checksum_size, 16);
+/// let archive = NamedArchive::load(compressed);
 ///
+/// // full checksums are computed while loading
+/// assert_eq!(archive.checksum("hello.txt"), NamedArchive::load(include_dir!("assets")).checksum("hello.txt"));
 /// ```
-/// use include_assets::EnumArchive;
 ///
-/// #[derive(include_assets::AssetEnum)]
-/// #[archive(base_path = "assets")]
-/// enum Asset {
-///     #[asset(path = "hello.txt")]
-///     Hello,
-///     #[asset(path = "unused.txt")]
-///     _Unused,
+/// Uncompressed archives can be loaded without copying the assets to the heap, so that the operating system pages them in on demand:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// let compressed = include_dir!("assets", compression = "uncompressed");
+/// let archive = NamedArchive::load_static(compressed);
+/// assert_eq!(&archive["hello.txt"], b"Hello, world!");
+/// assert!(compressed.data.as_ptr_range().contains(&archive["hello.txt"].as_ptr()));
+/// # assert_eq!(archive.checksum("hello.txt"), NamedArchive::load(compressed).checksum("hello.txt"));
+/// # assert!(archive.memory_usage() < NamedArchive::load(compressed).memory_usage());
+/// # let mut archive = NamedArchive::load_static(include_dir!("assets", compression = "uncompressed", single_stream = true, checksum_size = 8, shard_size = 4));
+/// # assert_eq!(archive.checksum("hello.txt"), NamedArchive::load(compressed).checksum("hello.txt"));
+/// # assert_eq!(archive.take(".gitkeep").map(|data| data.len()), Some(25));
+/// # assert_eq!(&archive["hello.txt"], b"Hello, world!");
+/// # let archive = NamedArchive::load_static(include_dir!("assets", compression = "uncompressed", align = 16));
+/// # assert!(archive.assets().all(|(_, data)| data.as_ptr() as usize % 16 == 0));
+/// ```
+///
+/// Small archives can be stored as a single compressed stream, including the names, sizes, and checksums (see the `single_stream` option):
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// let compressed = include_dir!("assets", single_stream = true);
+/// assert!(compressed.compressed_names.is_empty() && compressed.checksums.is_empty());
+/// let archive = NamedArchive::load(compressed);
+/// assert_eq!(&archive["hello.txt"], b"Hello, world!");
+/// # assert_eq!(archive.number_of_assets(), 3);
+/// # assert_eq!(archive.checksum("hello.txt"), NamedArchive::load(include_dir!("assets")).checksum("hello.txt"));
+/// # let compressed = include_dir!("assets", single_stream = true, shard_size = 5, align = 16, checksum_size = 8);
+/// # let archive = NamedArchive::load(compressed);
+/// # assert_eq!(archive.memory_usage(), NamedArchive::load(include_dir!("assets", align = 16)).memory_usage());
+/// # assert!(archive.assets().all(|(_, data)| data.as_ptr() as usize % 16 == 0));
+/// # let mut text = String::new();
+/// # std::io::Read::read_to_string(&mut compressed.reader("hello.txt").unwrap().unwrap(), &mut text).unwrap();
+/// # assert_eq!(text, "Hello, world!");
+/// # let mut file = vec![];
+/// # compressed.write_to(&mut file).unwrap();
+/// # assert_eq!(&NamedArchive::from_reader(file.as_slice()).unwrap()["hello.txt"], b"Hello, world!");
+/// # #[cfg(feature = "signature")]
+/// # {
+/// #     const PUBLIC_KEY: [u8; 32] = [
+/// #         121, 181, 86, 46, 143, 230, 84, 249, 64, 120, 177, 18, 232, 169, 139, 167, 144, 31, 133, 58, 230, 149, 190, 215, 224, 227, 145, 11, 173, 4, 150, 100,
+/// #     ];
+/// #     let signed = include_dir!("assets", signing_key = "keys/insecure_test_only_signing_key.hex", single_stream = true);
+/// #     assert!(NamedArchive::load_verified(signed, &PUBLIC_KEY).is_ok());
+/// # }
+/// ```
+///
+/// With the `align` option, every asset is aligned in memory, so that it can be reinterpreted as typed data (e.g. `&[f32]`) without copying:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// let compressed = include_dir!("assets", align = 16);
+/// assert_eq!(compressed.data.as_ptr() as usize % 16, 0);
+/// let archive = NamedArchive::load(compressed);
+/// for (_name, data) in archive.assets() {
+///     assert_eq!(data.as_ptr() as usize % 16, 0);
 /// }
+/// assert_eq!(&archive["hello.txt"], b"Hello, world!");
+/// # let mut compressed = include_dir!("assets", align = 64, shard_size = 7);
+/// # assert_eq!(compressed.align, 64);
+/// # let archive = NamedArchive::try_load_into(compressed, Vec::with_capacity(1000)).unwrap();
+/// # assert!(archive.assets().all(|(_, data)| data.as_ptr() as usize % 64 == 0));
+/// # assert_eq!(&archive["hello.txt"], b"Hello, world!");
+/// # let mut text = String::new();
+/// # std::io::Read::read_to_string(&mut compressed.reader("hello.txt").unwrap().unwrap(), &mut text).unwrap();
+/// # assert_eq!(text, "Hello, world!");
+/// # let mut file = vec![];
+/// # compressed.write_to(&mut file).unwrap();
+/// # let archive = NamedArchive::from_reader(file.as_slice()).unwrap();
+/// # assert!(archive.assets().all(|(_, data)| data.as_ptr() as usize % 64 == 0));
+/// # compressed.align = 3;
+/// # assert!(NamedArchive::try_load(compressed).is_err());
+/// ```
+///
+/// Archives with many small assets can use less memory by looking up assets with binary search instead of hashing:
 ///
-/// let archive = EnumArchive::<Asset>::load().map(|data| std::str::from_utf8(data).unwrap().to_owned());
-/// assert_eq!(archive[Asset::Hello].as_str(), "Hello, world!");
 /// ```
-pub use include_assets_decode::enums::EnumArchive;
-
-pub use include_assets_decode::enums::EnumMap;
-
-#[doc(hidden)]
-pub use include_assets_decode::named::CompressedNamedArchive;
+/// use include_assets::{Lookup, NamedArchive, include_dir};
+///
+/// let archive = NamedArchive::try_load_with(include_dir!("assets"), Lookup::Sorted).unwrap();
+/// assert_eq!(archive.get("hello.txt"), Some(b"Hello, world!".as_slice()));
+/// ```
+///
+/// The data structure for lookups is built on the first lookup, so programs that only iterate over the assets don't pay for it:
+///
+/// ```
+/// use include_assets::{Lookup, NamedArchive, include_dir};
+///
+/// let archive = NamedArchive::try_load_with(include_dir!("assets"), Lookup::CaseInsensitive).unwrap();
+/// let before = archive.memory_usage();
+/// assert!(archive.names().any(|name| name == "hello.txt"));
+/// assert_eq!(archive.memory_usage(), before);
+/// assert!(archive.get("hello.txt").is_some());
+/// assert!(archive.memory_usage() > before);
+/// ```
+///
+/// Names from user-authored content can be looked up ignoring case:
+///
+/// ```
+/// use include_assets::{Lookup, NamedArchive, include_dir};
+///
+/// let archive = NamedArchive::try_load_with(include_dir!("assets"), Lookup::CaseInsensitive).unwrap();
+/// assert_eq!(archive.get("Hello.TXT"), Some(b"Hello, world!".as_slice()));
+/// ```
+///
+/// With feature `mime`, MIME types are guessed from file extensions:
+///
+/// ```
+/// # #[cfg(feature = "mime")] {
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// let archive = NamedArchive::load(include_dir!("assets", mime_types = "txt=text/x-greeting"));
+/// assert_eq!(archive.mime("hello.txt"), Some("text/x-greeting"));
+/// assert_eq!(archive.mime("missing.png"), None);
+/// # }
+/// ```
+///
+/// With feature `bytes`, asset data can be shared as [`bytes::Bytes`](https://docs.rs/bytes) without copying, e.g. for HTTP responses:
+///
+/// ```
+/// # #[cfg(feature = "bytes")] {
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// let hello = archive.get_bytes("hello.txt").unwrap();
+/// assert_eq!(hello.clone(), &b"Hello, world!"[..]);
+/// # }
+/// ```
+///
+/// Leak the archive to get assets that live for the rest of the program, as many APIs require for fonts or shaders:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// static GREETING: std::sync::OnceLock<&'static [u8]> = std::sync::OnceLock::new();
+///
+/// let archive: &'static NamedArchive = NamedArchive::load(include_dir!("assets")).leak();
+/// GREETING.set(&archive["hello.txt"]).unwrap();
+/// assert_eq!(GREETING.get().unwrap(), b"Hello, world!");
+/// ```
+///
+/// Discover assets by file extension, e.g. shaders or locales:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// let mut names: Vec<_> = archive.with_extension("txt").map(|(name, _)| name).collect();
+/// names.sort();
+/// assert_eq!(names, ["hello.txt", "unused.txt"]);
+/// assert_eq!(archive.with_extension("wgsl").count(), 0);
+/// ```
+///
+/// List virtual directories, or view all assets as a tree:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// for entry in archive.list_dir("") {
+///     println!("{}", entry.file_name());
+/// }
+/// assert_eq!(archive.tree().size(), archive.assets().map(|(_, data)| data.len()).sum::<usize>());
+/// ```
+///
+/// Files on disk can shadow included assets at runtime, e.g. for modding:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// let mods = std::env::temp_dir().join("include_assets_doctest_overlay");
+/// std::fs::create_dir_all(&mods).unwrap();
+/// std::fs::write(mods.join("hello.txt"), "Hello, modded world!").unwrap();
+///
+/// let mut archive = NamedArchive::load(include_dir!("assets"));
+/// archive.overlay_dir(&mods).unwrap();
+/// assert_eq!(&archive["hello.txt"], b"Hello, modded world!");
+/// assert!(archive.contains("unused.txt"));
+/// # #[cfg(unix)]
+/// # {
+/// #     std::fs::create_dir_all(mods.join("a")).unwrap();
+/// #     let _ = std::os::unix::fs::symlink("..", mods.join("a/loop"));
+/// #     assert!(archive.overlay_dir(&mods).is_err());
+/// # }
+/// # std::fs::remove_dir_all(&mods).unwrap();
+/// ```
+///
+/// Take ownership of asset data:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// let mut archive = NamedArchive::load(include_dir!("assets"));
+/// let number_of_assets = archive.number_of_assets();
+///
+/// // take a single asset out of the archive, releasing its memory
+/// assert_eq!(archive.take("hello.txt").unwrap(), b"Hello, world!");
+/// assert!(!archive.contains("hello.txt"));
+///
+/// // copy all remaining assets into owned buffers
+/// let owned: std::collections::HashMap<String, Vec<u8>> = archive.into_copied_assets().collect();
+/// assert_eq!(owned.len(), number_of_assets - 1);
+/// # #[cfg(feature = "bytes")] {
+/// # let archive = NamedArchive::load(include_dir!("assets", align = 16));
+/// # let buffer = archive.get("hello.txt").unwrap().as_ptr_range();
+/// # let shared: Vec<_> = archive.into_asset_bytes().collect();
+/// # assert_eq!(shared.len(), number_of_assets);
+/// # assert!(shared.iter().any(|(name, data)| name == "hello.txt" && data.as_ptr_range() == buffer));
+/// # assert!(shared.iter().all(|(name, data)| owned.get(name).is_none_or(|copy| copy == data)));
+/// # }
+/// ```
+///
+/// Keep only the assets that are needed, and release everything else including the asset names:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// const HELLO: usize = 0;
+/// const MISSING: usize = 1;
+///
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// let resolved = archive.into_resolved(&["hello.txt", "missing.txt"]);
+/// assert_eq!(&resolved[HELLO], b"Hello, world!");
+/// assert_eq!(resolved.get(MISSING), None);
+/// ```
+///
+/// Archives can also be built from an iterator, e.g. for tests, and iterated over:
+///
+/// ```
+/// use include_assets::NamedArchive;
+///
+/// let archive: NamedArchive = [("a.txt", "first"), ("dir/b.txt", "second")].into_iter().collect();
+/// assert_eq!(archive.len(), 2);
+/// assert_eq!(format!("{archive:?}"), r#"NamedArchive { assets: {"a.txt": 5, "dir/b.txt": 6} }"#);
+///
+/// for (name, data) in &archive {
+///     println!("{name}: {} bytes", data.len());
+/// }
+/// ```
+///
+/// With feature `serde`, archives can be serialized as a map from names to contents, e.g. for debugging:
+///
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// use include_assets::NamedArchive;
+///
+/// let archive: NamedArchive = [("a.txt", "hi")].into_iter().collect();
+/// assert_eq!(serde_json::to_string(&archive).unwrap(), r#"{"a.txt":[104,105]}"#);
+/// # }
+/// ```
+pub use include_assets_decode::named::NamedArchive;
+
+#[cfg(feature = "bytes")]
+pub use include_assets_decode::named::AssetBytes;
+#[cfg(feature = "std")]
+pub use include_assets_decode::named::{ArchiveHandle, AssetReader};
+pub use include_assets_decode::named::{AssetCheck, AssetEntry, Assets, CopiedAssets, DirEntry, ResolvedAssets, TreeDir, TreeFile};
+
+pub use include_assets_decode::lookup::Lookup;
+
+/// Read-only virtual file system, so that engines and middleware can be written against one abstraction with swappable backends.
+///
+/// Asset sources can [`read`](AssetSource::read), [`open`](AssetSource::open) and [`list`](AssetSource::list) assets by name, and check whether they [`exist`](AssetSource::exists).
+/// Names are relative paths with `/` as separator, like the names in a [`NamedArchive`].
+///
+/// The trait is implemented by
+/// - [`NamedArchive`],
+/// - memory-mapped archive files (`file::MappedArchive`, requires feature `mmap`), e.g. sidecar archives shipped next to the executable,
+/// - [`Overlay`], which combines two sources, and
+/// - references and boxes of asset sources, so `Box<dyn AssetSource>` can be used to choose a backend at runtime.
+///
+/// Implement [`AssetSource::read`] and [`AssetSource::list`] for your own backends, e.g. a directory or a network connection.
+/// Requires feature `std`.
+///
+/// # Examples
+///
+/// ```
+/// use include_assets::{include_dir, AssetSource, NamedArchive, Overlay, SourceEntry};
+///
+/// fn greeting(assets: &dyn AssetSource) -> String {
+///     String::from_utf8(assets.read("hello.txt").unwrap().into_owned()).unwrap()
+/// }
+///
+/// let included = NamedArchive::load(include_dir!("assets"));
+/// assert_eq!(greeting(&included), "Hello, world!");
+///
+/// // e.g. a mod which overrides some assets
+/// let modded = NamedArchive::from_iter([("hello.txt", "Hello, mod!"), ("mod/info.txt", "v1")]);
+/// let assets = Overlay::new(&modded, &included);
+/// assert_eq!(greeting(&assets), "Hello, mod!");
+/// assert!(assets.exists("unused.txt"));
+/// assert!(assets.list("").contains(&SourceEntry::Dir("mod".into())));
+///
+/// let mut content = String::new();
+/// std::io::Read::read_to_string(&mut assets.open("mod/info.txt").unwrap(), &mut content).unwrap();
+/// assert_eq!(content, "v1");
+/// ```
+#[cfg(feature = "std")]
+pub use include_assets_decode::source::AssetSource;
+
+/// Entry of a virtual directory, see [`AssetSource::list`]
+#[cfg(feature = "std")]
+pub use include_assets_decode::source::SourceEntry;
+
+/// Combination of two [asset sources](AssetSource), where the assets of `top` shadow those of `base` with the same name.
+///
+/// Directory listings contain the entries of both sources.
+/// Overlays can be nested to combine more sources.
+#[cfg(feature = "std")]
+pub use include_assets_decode::source::Overlay;
+
+/// Include all files in a directory in compressed form.
+/// At runtime, the files can be decompressed and their contents looked up by relative path name.
+///
+/// # Usage
+///
+/// The first argument must be a string literal specifying the path of the directory to be included.
+/// This can be an absolute path or a path relative to the [`CARGO_MANIFEST_DIR`](https://doc.rust-lang.org/cargo/reference/environment-variables.html#environment-variables-cargo-sets-for-crates).
+/// This path can be absolute (though this should be avoided) or relative to `cargo`'s working directory.
+///
+/// In addition, any of the options described in the [`crate`] level documentation may be used to specify compression options.
+///
+/// Large compressed data is not expanded into a byte string literal, which would take rustc a lot of memory and time.
+/// Instead, it is written to a file named after its checksum and included with [`include_bytes!`].
+/// The files are in `OUT_DIR/include_assets` if the crate has a build script, otherwise in `include_assets` in the target directory
+/// (`CARGO_TARGET_DIR` if set, otherwise `target` in the crate's directory).
+/// Existing files and cached shards are only reused if their contents match.
+///
+/// # Examples
+///
+/// Include the directory "assets":
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// println!("{} assets were included", archive.number_of_assets());
+/// ```
+///
+/// Include the directory "assets".
+/// Assets will be compressed using zstd at level 5.
+/// Symbolic links will be treated as if they were the file/directory pointed to:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+/// let archive = NamedArchive::load(include_dir!("assets", compression = "zstd", level = 5, links = "follow"));
+/// println!("{} assets were included", archive.number_of_assets());
+/// ```
+///
+/// Include the directory "assets".
+/// The compressed data will be placed in the linker section ".assets", aligned to a page boundary:
+///
+/// ```
+/// # #[cfg(target_os = "linux")] {
+/// use include_assets::{NamedArchive, include_dir};
+/// let archive = NamedArchive::load(include_dir!("assets", link_section = ".assets", align = 4096));
+/// println!("{} assets were included", archive.number_of_assets());
+/// # }
+/// ```
+///
+/// Archives in a link section can be found in the executable, e.g. to audit a release build:
+///
+/// ```
+/// # #[cfg(target_os = "linux")] {
+/// use include_assets::{file, include_dir, NamedArchive};
+///
+/// let archive = NamedArchive::load(include_dir!("assets", link_section = ".assets"));
+///
+/// let executable = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+/// let found = file::find(&executable);
+/// assert_eq!(found.len(), 1);
+/// let (_offset, compressed) = found.into_iter().next().unwrap();
+/// assert_eq!(NamedArchive::load(compressed).get("hello.txt"), archive.get("hello.txt"));
+/// # }
+/// ```
+///
+/// The same is possible from the command line with `cargo include-assets` (install `include_assets` with feature `cli`):
+///
+/// ```text
+/// $ cargo include-assets list target/release/my-game --section .assets
+/// $ cargo include-assets extract target/release/my-game extracted-assets
+/// ```
+///
+/// Include the directory "assets" in release builds, but read it at runtime in debug builds:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+/// let archive = NamedArchive::load(include_dir!("assets", dev = true));
+/// assert_eq!(&archive["hello.txt"], b"Hello, world!");
+/// ```
+///
+/// With feature `hot-reload`, changes to the directory can be watched in debug builds:
+///
+/// ```
+/// # #[cfg(feature = "hot-reload")] {
+/// use include_assets::{NamedArchive, include_dir};
+/// let mut archive = NamedArchive::load(include_dir!("assets", dev = true));
+/// if let Some(watcher) = archive.watch() {
+///     let watcher = watcher.unwrap();
+///     // e.g. once per frame:
+///     if watcher.changes().count() > 0 {
+///         archive.reload().unwrap();
+///     }
+/// }
+/// # }
+/// ```
+///
+/// Include the directory "assets" with a module of asset name constants:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+///
+/// include_dir!("assets", names_module = "assets");
+///
+/// let archive = NamedArchive::load(assets::archive());
+/// assert_eq!(assets::HELLO_TXT, "hello.txt");
+/// assert_eq!(&archive[assets::HELLO_TXT], b"Hello, world!");
+/// ```
+///
+/// Include the directory "assets", where "unused.txt" can be looked up but is not listed:
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+/// let archive = NamedArchive::load(include_dir!("assets", private = "unused.txt"));
+/// assert!(archive.get("unused.txt").is_some());
+/// assert!(archive.names().all(|name| name != "unused.txt"));
+/// assert_eq!(archive.names().len(), archive.number_of_assets());
+/// ```
+///
+/// Private names must match an asset, also in dev mode:
+///
+/// ```compile_fail
+/// use include_assets::{NamedArchive, include_dir};
+/// let archive = NamedArchive::load(include_dir!("assets", dev = true, private = "missing.txt"));
+/// ```
+///
+/// Include the two directories "assets" (compressed with zstd level 22) and "other_assets" (lz4 compressed):
+///
+/// ```
+/// use include_assets::{NamedArchive, include_dir};
+/// let archive1 = NamedArchive::load(include_dir!("assets", compression = "zstd", level = 22));
+/// let archive2 = NamedArchive::load(include_dir!("other_assets", compression = "lz4"));
+/// println!("{} assets were included", archive1.number_of_assets() + archive2.number_of_assets());
+/// ```
+///
+/// # Limitations
+///
+/// - The directory may only contains files, directories, or symbolic links which point (directly or indirectly) to a file or directory.
+///   Special files are not allowed.
+/// - Paths must be UTF-8
+/// - Paths must not contain null bytes (U+0000)
+pub use include_assets_encode::include_dir;
+
+/// Include per-locale subdirectories of a directory, e.g. for translations, as a [`LocaleArchive`].
+///
+/// Each subdirectory is a locale named by its tag, e.g. `locales/de-DE/` for the locale `de-DE`, and is included as if with [`include_dir!`] with the same options.
+/// Files directly in the directory are ignored.
+/// Since each locale is compressed independently, only the locales which are actually used are decompressed, when they are first used.
+/// The `names_module` option is not supported.
+///
+/// Requires feature `std`.
+///
+/// # Examples
+///
+/// With the directories `locales/en`, `locales/de`, and `locales/de-AT`:
+///
+/// ```
+/// use include_assets::{include_locales, LocaleArchive};
+///
+/// let locales = include_locales!("locales", compression = "lz4").fallback("en");
+///
+/// let austrian = locales.select("de-AT").unwrap();
+/// assert_eq!(austrian.chain().collect::<Vec<_>>(), ["de-AT", "de", "en"]);
+/// assert_eq!(austrian.get_str("greeting.txt").unwrap().unwrap(), "Servus");
+/// // missing in `de-AT`, so it is taken from `de`
+/// assert_eq!(austrian.get_str("farewell.txt").unwrap().unwrap(), "Auf Wiedersehen");
+///
+/// // unknown locales fall back to `en`
+/// assert_eq!(locales.select("fr_FR").unwrap().locale(), "en");
+/// // the first included locale of an `Accept-Language` header
+/// assert_eq!(locales.select_any(["fr-CH", "de-CH", "en"]).unwrap().locale(), "de");
+/// ```
+///
+/// The archive can be put in a static:
+///
+/// ```
+/// use include_assets::{include_locales, LocaleArchive};
+/// use std::sync::LazyLock;
+///
+/// static LOCALES: LazyLock<LocaleArchive> = LazyLock::new(|| include_locales!("locales", compression = "zstd").fallback("en"));
+///
+/// assert_eq!(LOCALES.select("de").unwrap().get_str("greeting.txt").unwrap().unwrap(), "Hallo");
+/// ```
+#[cfg(feature = "std")]
+pub use include_assets_encode::include_locales;
+
+/// Assets of several locales, each decompressed when it is first used (see [`include_locales!`]).
+///
+/// [`LocaleArchive::select`] returns a [`LocaleBundle`] which looks up assets in the requested locale,
+/// then in less specific locales (e.g. `de` for `de-AT`), and finally in the fallback locales configured with [`LocaleArchive::fallback`].
+/// Locale tags are compared case-insensitively, and `_` is the same as `-`.
+#[cfg(feature = "std")]
+pub use include_assets_decode::locale::LocaleArchive;
+
+/// Assets of a selected locale and its fallbacks, see [`LocaleArchive::select`]
+#[cfg(feature = "std")]
+pub use include_assets_decode::locale::LocaleBundle;
+
+/// Declare a static [`NamedArchive`] holding a directory included with [`include_dir!`], which is loaded on first access.
+///
+/// The arguments after `=` are the same as for [`include_dir!`].
+/// The archive is loaded with [`NamedArchive::load`], which panics if loading fails.
+///
+/// # Examples
+///
+/// ```
+/// use include_assets::include_dir_lazy;
+///
+/// include_dir_lazy!(static ASSETS = "assets", compression = "lz4");
+///
+/// fn greeting() -> &'static [u8] {
+///     &ASSETS["hello.txt"]
+/// }
+///
+/// assert_eq!(greeting(), b"Hello, world!");
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! include_dir_lazy {
+    ($(#[$attr:meta])* $vis:vis static $name:ident = $($args:tt)+) => {
+        $(#[$attr])*
+        $vis static $name: ::std::sync::LazyLock<$crate::NamedArchive> = ::std::sync::LazyLock::new(|| $crate::NamedArchive::load($crate::include_dir!($($args)+)));
+    };
+}
+
+/// Declare an exported C function returning a directory included with [`include_dir!`], for use with the [`ffi`] module (requires feature `ffi`).
+///
+/// The arguments after `=` are the same as for [`include_dir!`].
+/// The function has the given name (unmangled, so it must be unique in the program), takes no arguments and returns a pointer to the [`NamedArchive`].
+/// The archive is loaded on the first call and lives for the rest of the program.
+/// If loading fails, the program aborts, since panics cannot unwind into C code.
+///
+/// ```c
+/// const IncludeAssetsArchive *game_assets(void);
+/// ```
+///
+/// See the [`ffi`] module for an example.
+#[cfg(feature = "ffi")]
+#[macro_export]
+macro_rules! include_dir_ffi {
+    ($(#[$attr:meta])* fn $name:ident = $($args:tt)+) => {
+        $(#[$attr])*
+        #[unsafe(no_mangle)]
+        pub extern "C" fn $name() -> *const $crate::NamedArchive {
+            $crate::include_dir_lazy!(static ARCHIVE = $($args)+);
+            &*ARCHIVE
+        }
+    };
+}
+
+/// Include an archive compressed by the build script with the [`include_assets_build`](https://docs.rs/include_assets_build) crate.
+///
+/// The argument is the name passed to `Archive::write` in the build script.
+/// The result is a [`CompressedNamedArchive`], just like the result of [`include_dir!`], but the assets are compressed
+/// (only when they change) by the build script rather than the proc macro, which needs much less memory for large asset directories.
+///
+/// The features of this crate must enable the codec used by the build script.
+///
+/// # Examples
+///
+/// In `build.rs`:
+///
+/// ```ignore
+/// use include_assets_build::{Archive, Compression};
+///
+/// fn main() {
+///     Archive::new("assets").compression(Compression::Zstd { level: 19 }).write("assets").unwrap();
+/// }
+/// ```
+///
+/// In the crate:
+///
+/// ```ignore
+/// use include_assets::{include_archive, NamedArchive};
+///
+/// let archive = NamedArchive::load(include_archive!("assets"));
+/// ```
+///
+/// See `examples/build_script` in the repository for a complete example.
+#[macro_export]
+macro_rules! include_archive {
+    ($name:literal) => {
+        ::core::include!(::core::concat!(::core::env!("OUT_DIR"), "/", $name, ".rs"))
+    };
+}
+
+/// Declare a type implementing [`rust_embed::RustEmbed`](https://docs.rs/rust-embed) for a directory included with [`include_dir!`] (requires feature `rust-embed`).
+///
+/// Many web frameworks and crates support rust-embed, e.g. `axum-embed` or `salvo`.
+/// With this macro, they can serve solid-compressed assets unchanged.
+/// The arguments after `=` are the same as for [`include_dir!`].
+///
+/// The archive is loaded (decompressed) on first access, and can also be accessed directly with the generated `archive()` function.
+/// Unlike with rust-embed, assets are always included, also in debug builds.
+/// The hash in the metadata of an `EmbeddedFile` is the beginning of the asset's checksum instead of its SHA-256 hash,
+/// which serves the same purpose, e.g. for `ETag` headers.
+///
+/// This feature enables the `debug-embed` feature of rust-embed.
+/// Its `compression` feature is not supported, since it adds a method to the trait.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "rust-embed")] {
+/// use include_assets::include_dir_rust_embed;
+/// use rust_embed::RustEmbed;
+///
+/// include_dir_rust_embed!(struct Assets = "assets", compression = "zstd");
+///
+/// let file = Assets::get("hello.txt").unwrap();
+/// assert_eq!(file.data.as_ref(), b"Hello, world!");
+/// assert!(Assets::iter().any(|name| name == "unused.txt"));
+/// # assert!(Assets::get("missing.txt").is_none());
+/// # assert_eq!(Assets::iter().count(), Assets::archive().number_of_assets());
+/// # assert_eq!(file.metadata.sha256_hash()[..], Assets::archive().checksum("hello.txt").unwrap()[..32]);
+/// # }
+/// ```
+#[cfg(feature = "rust-embed")]
+#[macro_export]
+macro_rules! include_dir_rust_embed {
+    ($(#[$attr:meta])* $vis:vis struct $name:ident = $($args:tt)+) => {
+        $(#[$attr])*
+        $vis struct $name;
+
+        impl $name {
+            /// Archive holding the assets, loaded on first access
+            #[allow(dead_code)]
+            $vis fn archive() -> &'static $crate::NamedArchive {
+                static ARCHIVE: ::std::sync::LazyLock<$crate::NamedArchive> = ::std::sync::LazyLock::new(|| $crate::NamedArchive::load($crate::include_dir!($($args)+)));
+                &ARCHIVE
+            }
+        }
+
+        impl $crate::__rust_embed::RustEmbed for $name {
+            fn get(file_path: &str) -> ::core::option::Option<$crate::__rust_embed::EmbeddedFile> {
+                $crate::__rust_embed::get(Self::archive(), file_path)
+            }
+
+            fn iter() -> $crate::__rust_embed::Filenames {
+                static NAMES: ::std::sync::OnceLock<&'static [&'static str]> = ::std::sync::OnceLock::new();
+                $crate::__rust_embed::Filenames::Embedded(NAMES.get_or_init(|| $crate::__rust_embed::names(Self::archive())).iter())
+            }
+        }
+    };
+}
+
+#[cfg(feature = "rust-embed")]
+#[doc(hidden)]
+pub use include_assets_decode::rust_embed as __rust_embed;
+
+/// Derive the AssetEnum trait.
+///
+/// The trait should _never_ be implemented or used manually, _only_ with this derive macro.
+/// Details (methods, associated types/constants) for the trait are not bound by semver!
+///
+/// This macro only works for enums.
+/// Every enum variant must be unit (i.e. have no fields).
+/// Explicit discriminants (e.g. for serialization) are allowed and don't affect the archive.
+/// There needs to be an outer attribute `#[archive(base_path = "path")]` on the enum specifying the base path of all assets.
+/// This can be an absolute path or a path relative to the [`CARGO_MANIFEST_DIR`](https://doc.rust-lang.org/cargo/reference/environment-variables.html#environment-variables-cargo-sets-for-crates).
+/// Every variant needs to have an attribute `#[asset(path = "relative path")]` specifying the (compile time) path of the asset relative to the base path.
+/// Exactly one `asset` attribute must remain after `cfg_attr` attributes are evaluated, so the path can depend on the compilation target.
+/// A variant can take its file from another directory with `#[asset(path = "relative path", base = "other base path")]`, e.g. for assets shared between crates.
+/// The file must be inside its base path, unless the enum is declared with `allow_outside_base = true`.
+/// With `optional = true` in the `asset` attribute, the file may be missing, see [`EnumArchive::get`].
+/// With `kind = "str"`, the file is checked to be valid UTF-8 at compile time, and can be accessed as a string slice with [`EnumArchive::get_str`].
+/// With `deserialize = "toml"` (requires feature `toml`) or `deserialize = "json"` (requires feature `json`) and `type = "Type"`, the asset is deserialized into `Type` with serde by [`EnumArchive::load_typed`].
+/// The values are stored in a struct named after the enum with suffix `Typed`, with a field named after each such variant in snake case (e.g. `html_page` for `HTMLPage`).
+///
+/// Additionally, options described in the [`crate`] level documentation may be added to the outer enum attribute to specify compression options.
+/// A variant can be stored without compression with `#[asset(path = "relative path", compression = "uncompressed")]`, which is useful for files that are already compressed (e.g. PNG or Ogg).
+///
+/// # Examples
+///
+/// Basic use.
+/// Include `"assets/hello.txt"` and `"assets/unused.txt"` allowing lookup by `Asset::Hello` and `Asset::Unused`, respectively.
+/// If the variant `Unused` is never used (as in this example), this will cause a compile-time warning.
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     Unused,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// assert_eq!(&archive[Asset::Hello], b"Hello, world!");
+/// ```
+///
+/// For more information on how to use the decompressed assets, see [`EnumArchive`].
+///
+/// A more specific `derive` with the same assets.
+/// Assets will be compressed using zstd at level 5.
+/// The enum representation is explicitely chosen as `u8`:
+///
+/// ```
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets", compression = "zstd", level = 5)]
+/// #[repr(u8)]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     Unused,
+/// }
+/// ```
+///
+/// Store an asset uncompressed within an otherwise compressed archive:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets", compression = "lz4")]
+/// enum Asset {
+///     #[asset(path = "hello.txt", compression = "uncompressed")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     Unused,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// assert_eq!(&archive[Asset::Hello], b"Hello, world!");
+/// assert_eq!(&archive[Asset::Unused], std::fs::read("assets/unused.txt").unwrap());
+/// ```
+///
+/// Include a different file depending on the compilation target, by gating the `asset` attributes with `cfg_attr`:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[cfg_attr(windows, asset(path = "unused.txt"))]
+///     #[cfg_attr(not(windows), asset(path = "hello.txt"))]
+///     Greeting,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// let path = if cfg!(windows) { "assets/unused.txt" } else { "assets/hello.txt" };
+/// assert_eq!(&archive[Asset::Greeting], std::fs::read(path).unwrap());
+/// ```
+///
+/// Text assets can be declared as strings, so invalid UTF-8 is a compile-time error:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt", kind = "str")]
+///     Hello,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// assert_eq!(archive.get_str(Asset::Hello), "Hello, world!");
+/// ```
+///
+/// With features `toml` and `json`, configuration files can be deserialized with serde:
+///
+/// ```
+/// # #[cfg(all(feature = "toml", feature = "json"))] {
+/// use include_assets::EnumArchive;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     greeting: String,
+///     volume: u8,
+/// }
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "other_assets")]
+/// enum Asset {
+///     #[asset(path = "config.toml", deserialize = "toml", type = "Config")]
+///     DefaultConfig,
+///     #[asset(path = "config.json", deserialize = "json", type = "Config")]
+///     UserConfig,
+/// }
+///
+/// let typed: AssetTyped = EnumArchive::<Asset>::load_typed().unwrap();
+/// assert_eq!(typed.default_config.greeting, "Hello, world!");
+/// assert_eq!(typed.user_config.volume, 7);
+/// # #[derive(include_assets::AssetEnum)]
+/// # #[archive(base_path = "other_assets")]
+/// # enum Acronyms {
+/// #     #[asset(path = "config.json", deserialize = "json", type = "Config")]
+/// #     JSONConfig,
+/// #     #[asset(path = "config.toml", deserialize = "toml", type = "Config")]
+/// #     Toml2Config,
+/// #     #[asset(path = "config.toml", deserialize = "toml", type = "Config")]
+/// #     HTMLPageV2,
+/// # }
+/// # let typed = EnumArchive::<Acronyms>::load_typed().unwrap();
+/// # assert_eq!(typed.json_config.volume, 7);
+/// # assert_eq!(typed.toml2_config.greeting, "Hello, world!");
+/// # assert_eq!(typed.html_page_v2.volume, typed.toml2_config.volume);
+/// # }
+/// ```
+///
+/// Optional assets, e.g. branding that is not present in every build, may be missing at compile time:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "branding.png", optional = true)]
+///     Branding,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// assert_eq!(archive.get(Asset::Hello), Some(b"Hello, world!".as_slice()));
+/// assert_eq!(archive.get(Asset::Branding), None);
+/// assert_eq!(&archive[Asset::Branding], b"");
+/// ```
+///
+/// Assets from several directories can be combined in one archive:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "config.toml", base = "other_assets")]
+///     Config,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// assert_eq!(&archive[Asset::Hello], b"Hello, world!");
+/// assert!(archive[Asset::Config].starts_with(b"greeting"));
+/// ```
+///
+/// Variants referring to the same file share its data, which is only included once (unless the enum is declared with `dedup = false`):
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "hello.txt")]
+///     Greeting,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// assert_eq!(&archive[Asset::Greeting], &archive[Asset::Hello]);
+/// # use include_assets::EmbeddedAssets;
+/// assert_eq!(archive.as_bytes().len(), Asset::Hello.asset_size());
+/// ```
+///
+/// Archives can also be indexed by reference, and the getters and the generated `path` and `size` methods accept borrowed variants too,
+/// so asset enums don't need to be `Copy`:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets", methods = true)]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "hello.txt", kind = "str")]
+///     Text,
+/// }
+///
+/// fn show(archive: &EnumArchive<Asset>, asset: &Asset) -> usize {
+///     assert_eq!(archive.get(asset).map(<[u8]>::len), Some(asset.size()));
+///     assert_eq!(archive.name_of(asset), asset.path());
+///     archive[asset].len()
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// assert_eq!(show(&archive, &Asset::Hello), 13);
+/// assert_eq!(archive.get_str(&Asset::Text), "Hello, world!");
+/// # let leaked = archive.leak();
+/// # assert_eq!(leaked.get(&Asset::Hello), leaked.get(Asset::Hello));
+/// # assert_eq!(leaked.get_str(&Asset::Text).len(), Asset::Text.size());
+/// ```
+///
+/// Variants may have explicit discriminants, with gaps:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum, Clone, Copy)]
+/// #[archive(base_path = "assets")]
+/// #[repr(u8)]
+/// enum Asset {
+///     #[asset(path = "unused.txt")]
+///     Unused = 7,
+///     #[asset(path = "hello.txt")]
+///     Hello = 42,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// assert_eq!(&archive[Asset::Hello], b"Hello, world!");
+/// assert_eq!(Asset::Unused as u8, 7);
+/// ```
+///
+/// Assets may not have fields:
+///
+/// ```compile_fail
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello(String), // field is not allowed
+///     #[asset(path = "hello.txt")]
+///     Hello2 { who: String }, // struct-like variant is not allowed
+/// }
+/// ```
+///
+/// Paths may not escape the base path:
+///
+/// ```compile_fail
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "../Cargo.toml")] // outside of "assets"
+///     Manifest,
+/// }
+/// ```
+///
+/// unless this is explicitly allowed:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets", allow_outside_base = true)]
+/// enum Asset {
+///     #[asset(path = "../Cargo.toml")]
+///     Manifest,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// assert!(archive[Asset::Manifest].starts_with(b"[package]"));
+/// ```
+pub use include_assets_encode::AssetEnum;
+
+/// Declare several [`AssetEnum`](derive@AssetEnum)s whose assets are compressed together as a single stream.
+///
+/// Derived asset enums are compressed independently, since each derive only sees its own enum.
+/// Enums declared in an asset group keep their separate types and archives, but share the compressed data, which usually compresses better, e.g. for assets with similar content.
+/// The enums are declared as usual, with the same `archive` and `asset` attributes, but without deriving `AssetEnum`.
+/// All enums in a group must have the same `compression`, `level`, `link_section` and `align` options, and `per_asset` is not supported.
+///
+/// Loading the archive of any enum in the group decompresses the data of the whole group.
+/// The decompressed group is kept (with feature `std`), so loading the other enums doesn't decompress it again, but copies their part of it.
+///
+/// # Examples
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// include_assets::asset_group! {
+///     #[derive(Clone, Copy)]
+///     #[archive(base_path = "assets", compression = "zstd")]
+///     pub enum Text {
+///         #[asset(path = "hello.txt")]
+///         Hello,
+///     }
+///
+///     #[archive(base_path = "other_assets", compression = "zstd")]
+///     pub enum Config {
+///         #[asset(path = "config.toml")]
+///         Toml,
+///     }
+/// }
+///
+/// let texts = EnumArchive::<Text>::load();
+/// let configs = EnumArchive::<Config>::load();
+/// assert_eq!(&texts[Text::Hello], b"Hello, world!");
+/// assert!(configs[Config::Toml].starts_with(b"greeting"));
+/// # use include_assets::AssetEnum;
+/// # let group = Text::GROUP.unwrap();
+/// # assert!(core::ptr::eq(group, Config::GROUP.unwrap()));
+/// # assert_eq!(group.memory_usage(), Text::GROUP_SIZE as usize);
+/// # assert_eq!(&EnumArchive::<Config>::load()[Config::Toml], &configs[Config::Toml]);
+/// # assert_eq!(group.memory_usage(), Text::GROUP_SIZE as usize);
+/// ```
+pub use include_assets_encode::asset_group;
+
+/// Derive macro for an enum whose variants are assets in a [`NamedArchive`], which are looked up at runtime.
+///
+/// Each variant is annotated with the name of its asset, as for an [`AssetEnum`](derive@AssetEnum).
+/// The assets are not included by the derive, but looked up in an archive (e.g. from [`include_dir!`]) with [`EnumView::bind`], so they are not included twice.
+/// With `#[archive(base_path = "...")]`, the derive checks at compile time that each file exists in the directory.
+///
+/// # Examples
+///
+/// ```
+/// use include_assets::{include_dir, EnumView, NamedArchive};
+///
+/// #[derive(include_assets::AssetView)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+/// }
+///
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// let view = EnumView::<Asset>::bind(&archive).unwrap();
+/// assert_eq!(&view[Asset::Hello], b"Hello, world!");
+///
+/// #[derive(include_assets::AssetView)]
+/// enum Other {
+///     #[asset(path = "config.toml")]
+///     Config,
+/// }
+///
+/// # let _ = Other::Config;
+/// let err = EnumView::<Other>::bind(&archive).err().unwrap();
+/// assert_eq!(err.to_string(), "asset config.toml is missing from the archive");
+/// ```
+pub use include_assets_encode::AssetView;
+
+#[doc(hidden)]
+pub use include_assets_decode::views::AssetView;
+
+/// Assets of a [`NamedArchive`] for each variant of an [`AssetView`](derive@AssetView) enum.
+///
+/// Created by [`EnumView::bind`], and indexed by enum variant like an [`EnumArchive`].
+pub use include_assets_decode::views::EnumView;
+
+/// Derive macro for a struct whose fields are assets.
+///
+/// Each field must be of type `&'static [u8]` or `&'static str` (validated as UTF-8 at compile time), and is annotated with the path of its asset.
+/// All assets are compressed together, as for an [`AssetEnum`](derive@AssetEnum), with the same options for the struct and its fields, except that fields can't be `optional` or deserialized.
+///
+/// The derive generates a function `load`, which loads (decompresses) the assets on its first call and returns the same assets on later calls.
+/// The asset data lives for the rest of the program, see [`StaticEnumArchive`].
+///
+/// # Examples
+///
+/// ```
+/// #[derive(include_assets::AssetStruct)]
+/// #[archive(base_path = "assets", compression = "lz4")]
+/// struct Assets {
+///     #[asset(path = "hello.txt")]
+///     greeting: &'static str,
+///     #[asset(path = "unused.txt")]
+///     unused: &'static [u8],
+/// }
+///
+/// let assets = Assets::load();
+/// assert_eq!(assets.greeting, "Hello, world!");
+/// # assert_eq!(assets.unused, b"");
+/// ```
+pub use include_assets_encode::AssetStruct;
+
+#[doc(hidden)]
+pub use include_assets_decode::enums::AssetEnum;
+
+#[doc(hidden)]
+pub use include_assets_decode::enums::IterableAssetEnum;
+
+/// Stable interface of all enums deriving [`AssetEnum`](derive@AssetEnum), for generic code over "anything that provides embedded assets", e.g. in library crates.
+///
+/// It is implemented for every asset enum, and can't be implemented otherwise.
+/// Archives of any asset enum can be loaded and used through this trait alone.
+///
+/// # Examples
+///
+/// ```
+/// use include_assets::EmbeddedAssets;
+///
+/// /// Total size of all assets
+/// fn total_size<A: EmbeddedAssets>() -> usize {
+///     A::load_archive().as_bytes().len()
+/// }
+///
+/// fn describe<A: EmbeddedAssets>(asset: &A) -> String {
+///     format!("asset {} of {}: {}", asset.asset_index() + 1, A::ASSET_COUNT, asset.asset_path())
+/// }
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+/// }
+///
+/// assert_eq!(total_size::<Asset>(), 13);
+/// assert_eq!(describe(&Asset::Hello), "asset 1 of 1: hello.txt");
+/// ```
+pub use include_assets_decode::enums::EmbeddedAssets;
+
+/// Archive holding uncompressed data for an [`AssetEnum`](derive@`AssetEnum`).
+///
+/// An `AssetEnum` is an `enum` type with unit variants.
+/// Each variant corresponds to an asset.
+/// An `EnumArchive` for a given `AssetEnum` allows looking up the enum data via indexing.
+///
+/// With option `iterable = true` on the `AssetEnum`, all variants can be iterated over with the generated function `variants()`, and all assets with [`EnumArchive::iter`].
+///
+/// Loading only fails in case of data corruption or internal bugs, in which case [`EnumArchive::load`] panics.
+/// [`EnumArchive::try_load`] returns a [`LoadError`](crate::error::LoadError) instead, which names the asset whose decompression or checksum failed.
+///
+/// # Examples
+///
+/// Include assets and look up data by name:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     _Unused,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// assert_eq!(&archive[Asset::Hello], b"Hello, world!");
+/// ```
+///
+/// Handle errors while loading:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets", per_asset = true)]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+/// }
+///
+/// match EnumArchive::<Asset>::try_load() {
+///     Ok(archive) => assert_eq!(&archive[Asset::Hello], b"Hello, world!"),
+///     Err(err) => eprintln!("couldn't load assets: {err}"),
+/// }
+/// ```
+///
+/// Include data and apply some transformation (here we convert the `&[u8]` data to `String` since all assets are plain text).
+/// The transformed data can be looked up by enum variant:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     _Unused,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load().map(|data| std::str::from_utf8(data).unwrap().to_owned());
+/// assert_eq!(archive[Asset::Hello].as_str(), "Hello, world!");
+/// ```
+///
+/// With `methods = true`, the derive also generates a function `path`, returning the path of the asset file relative to the base path,
+/// and a `const` function `size`, returning the size of the asset in bytes:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum, Clone, Copy)]
+/// #[archive(base_path = "assets", methods = true)]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+/// }
+///
+/// const _: () = assert!(Asset::Hello.size() <= 1024, "greeting exceeds the size budget");
+/// let mut buffer = Vec::with_capacity(Asset::Hello.size());
+/// assert_eq!(Asset::Hello.path(), "hello.txt");
+/// let archive = EnumArchive::<Asset>::load();
+/// assert_eq!(archive.name_of(Asset::Hello), "hello.txt");
+/// buffer.extend_from_slice(&archive[Asset::Hello]);
+/// assert_eq!(buffer.len(), Asset::Hello.size());
+/// # mod own_methods {
+/// #     // without `methods = true`, enums can have their own `path` and `size` methods
+/// #     #[derive(include_assets::AssetEnum)]
+/// #     #[archive(base_path = "assets")]
+/// #     pub enum Asset {
+/// #         #[asset(path = "hello.txt")]
+/// #         Hello,
+/// #     }
+/// #     impl Asset {
+/// #         pub fn path(&self) -> std::path::PathBuf {
+/// #             std::path::Path::new("assets").join(include_assets::EmbeddedAssets::asset_path(self))
+/// #         }
+/// #         pub fn size(&self) -> u64 {
+/// #             <Self as include_assets::AssetEnum>::SIZES[0] as u64
+/// #         }
+/// #     }
+/// # }
+/// # assert_eq!(own_methods::Asset::Hello.path(), std::path::Path::new("assets/hello.txt"));
+/// # assert_eq!(own_methods::Asset::Hello.size(), 13);
+/// ```
+///
+/// To convert the assets into owned types, `into_map` and `into_try_map` consume the archive and pass each asset as a `Vec<u8>`:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     Unused,
+/// }
+///
+/// let strings = EnumArchive::<Asset>::load().into_try_map(String::from_utf8).unwrap();
+/// assert_eq!(strings[Asset::Hello], "Hello, world!");
+/// # assert_eq!(strings[Asset::Unused].len(), include_assets::EmbeddedAssets::asset_size(&Asset::Unused));
+/// ```
+///
+/// The whole decompressed data can be accessed at once with `as_bytes`, e.g. to upload it as a single GPU buffer,
+/// and the position of each asset within it with `end_offsets`:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     Unused,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load();
+/// let bytes = archive.as_bytes();
+/// assert_eq!(archive.end_offsets(), [13, bytes.len() as u32]);
+/// assert_eq!(&bytes[..13], &archive[Asset::Hello]);
+/// assert_eq!(&bytes[13..], &archive[Asset::Unused]);
+/// ```
+///
+/// Iterate over all variants and assets, e.g. for preloading:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum, Debug, PartialEq)]
+/// #[archive(base_path = "assets", iterable = true)]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     Unused,
+/// }
+///
+/// assert_eq!(Asset::variants().collect::<Vec<_>>(), [Asset::Hello, Asset::Unused]);
+/// let archive = EnumArchive::<Asset>::load();
+/// for (variant, data) in archive.iter() {
+///     println!("{variant:?}: {} bytes", data.len());
+/// }
+/// ```
+pub use include_assets_decode::enums::EnumArchive;
+
+/// Asset archive for an [`AssetEnum`](derive@AssetEnum) which decompresses each asset the first time it is accessed, and keeps it afterwards.
+///
+/// Created by [`EnumArchive::load_lazy`].
+/// This reduces startup time and memory usage when only a few of the assets are used in a given run.
+/// Assets are only decompressed independently if the enum is declared with `per_asset = true`;
+/// otherwise, the first access decompresses all assets at once, like [`EnumArchive::load`].
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets", per_asset = true)]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     Unused,
+/// }
+///
+/// let archive = EnumArchive::<Asset>::load_lazy();
+/// // only the asset for `Asset::Hello` is decompressed
+/// assert_eq!(&archive[Asset::Hello], b"Hello, world!");
+/// # assert_eq!(&archive[Asset::Unused], &EnumArchive::<Asset>::load()[Asset::Unused]);
+/// ```
+#[cfg(feature = "std")]
+pub use include_assets_decode::enums::LazyEnumArchive;
+
+/// Asset archive for an [`AssetEnum`](derive@AssetEnum) whose data lives for the rest of the program.
+///
+/// Created by [`EnumArchive::leak`].
+/// Lookups with `get` and `get_str` return `&'static` references, which many APIs require, e.g. for fonts or shaders.
+/// The archive itself is `Copy`, so it can be passed around freely.
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt", kind = "str")]
+///     Hello,
+/// }
+///
+/// fn register_font(_name: &'static str, _data: &'static [u8]) {}
+///
+/// let archive = EnumArchive::<Asset>::load().leak();
+/// register_font("greeting", archive.get(Asset::Hello).unwrap());
+/// let greeting: &'static str = archive.get_str(Asset::Hello);
+/// assert_eq!(greeting, "Hello, world!");
+/// ```
+pub use include_assets_decode::enums::StaticEnumArchive;
+
+/// Conversion of asset data into a value, e.g. a font, image or configuration, for [`EnumArchive::load_as`].
+///
+/// Implemented for `Vec<u8>` and `String`.
+///
+/// # Examples
+///
+/// ```
+/// use include_assets::{EnumArchive, FromAsset};
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+/// }
+///
+/// struct WordCount(usize);
+///
+/// impl FromAsset for WordCount {
+///     type Error = std::str::Utf8Error;
+///
+///     fn from_asset(data: &[u8]) -> Result<Self, Self::Error> {
+///         Ok(WordCount(std::str::from_utf8(data)?.split_whitespace().count()))
+///     }
+/// }
+///
+/// let counts = EnumArchive::<Asset>::load_as::<WordCount>().unwrap();
+/// assert_eq!(counts[Asset::Hello].0, 2);
+///
+/// struct Number(u32);
+///
+/// impl FromAsset for Number {
+///     type Error = std::num::ParseIntError;
+///
+///     fn from_asset(data: &[u8]) -> Result<Self, Self::Error> {
+///         Ok(Number(String::from_utf8_lossy(data).trim().parse()?))
+///     }
+/// }
+///
+/// let err = EnumArchive::<Asset>::load_as::<Number>().err().unwrap();
+/// assert_eq!(err.path, "hello.txt");
+/// assert_eq!(err.to_string(), "couldn't convert asset hello.txt: invalid digit found in string");
+/// ```
+pub use include_assets_decode::enums::FromAsset;
+
+/// A structure which holds a value of some type `T` for each variant of an [`AssetEnum`](derive@`AssetEnum`).
+///
+/// Created by [`EnumArchive::map`] or [`EnumArchive::try_map`].
+///
+/// The values can be iterated over in declaration order of the enum variants with `values`, `values_mut` and `into_values`.
+/// If the enum is declared with `iterable = true`, `iter`, `iter_mut` and iterating over a reference to the map also yield the variants:
+///
+/// ```
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum, Debug, PartialEq)]
+/// #[archive(base_path = "assets", iterable = true)]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     Unused,
+/// }
+///
+/// let mut sizes = EnumArchive::<Asset>::load().map(|data| data.len());
+/// for size in sizes.values_mut() {
+///     *size *= 2;
+/// }
+/// assert_eq!(sizes.values().sum::<usize>(), 2 * <Asset as include_assets::AssetEnum>::SIZES.iter().sum::<usize>());
+/// for (variant, size) in &sizes {
+///     println!("{variant:?}: {size} bytes");
+/// }
+/// assert_eq!(sizes.iter().next(), Some((Asset::Hello, &26)));
+/// let sizes: Vec<usize> = sizes.into_values().collect();
+/// assert_eq!(sizes[0], 26);
+/// ```
+///
+/// Maps can also be created with `from_fn` (for `iterable` enums), and combined with `zip` and `map_with_variant`:
+///
+/// ```
+/// use include_assets::{EmbeddedAssets, EnumArchive, EnumMap};
+///
+/// #[derive(include_assets::AssetEnum, Clone, Copy, Debug, PartialEq)]
+/// #[archive(base_path = "assets", iterable = true)]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+///     #[asset(path = "unused.txt")]
+///     Unused,
+/// }
+///
+/// let paths = EnumMap::<Asset, _>::from_fn(|variant| variant.asset_path());
+/// let sizes = EnumArchive::<Asset>::load().map(|data| data.len());
+/// let described = paths.zip(sizes).map_with_variant(|variant, (path, size)| format!("{variant:?} ({path}): {size} bytes"));
+/// assert_eq!(described[Asset::Hello], "Hello (hello.txt): 13 bytes");
+/// ```
+///
+/// With feature `serde`, an `EnumMap` can be serialized as a sequence of its values, ordered by enum variant:
+///
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// use include_assets::EnumArchive;
+///
+/// #[derive(include_assets::AssetEnum)]
+/// #[archive(base_path = "assets")]
+/// enum Asset {
+///     #[asset(path = "hello.txt")]
+///     Hello,
+/// }
+///
+/// let sizes = EnumArchive::<Asset>::load().map(|data| data.len());
+/// assert_eq!(serde_json::to_string(&sizes).unwrap(), "[13]");
+/// # }
+/// ```
+pub use include_assets_decode::enums::EnumMap;
+
+/// HTTP service serving the assets of a [`NamedArchive`], e.g. an embedded website (requires feature `http`).
+///
+/// `ServeArchive` only depends on the `http` crate, and is the foundation of the framework integrations:
+/// [`ServeArchive::respond`] maps an `http::Request` to an `http::Response<Bytes>`, which is easy to adapt to any server.
+/// With feature `hyper`, it implements hyper's `Service` trait, and with feature `axum`, it implements [`tower::Service`](https://docs.rs/tower-service),
+/// so it can be used with axum and other tower-based frameworks.
+///
+/// Request paths are percent-decoded and normalized, and `index.html` is served for directories.
+/// Only `GET` and `HEAD` requests are allowed.
+/// Unknown paths (and paths containing `..`) result in `404 Not Found`.
+/// The `Content-Type` header is set from the MIME type of the asset (see `NamedArchive::mime`).
+///
+/// Responses carry a strong `ETag` derived from the checksum of the asset and a `Last-Modified` header (see `NamedArchive::modified`).
+/// Conditional requests whose `If-None-Match` or `If-Modified-Since` header shows that the client's copy is still valid are answered with `304 Not Modified`,
+/// without an asset body.
+///
+/// Single byte ranges (`Range` header, also with `If-Range`) are supported, so browsers can seek in embedded audio and video.
+/// Requests for multiple ranges are answered with the complete asset.
+///
+/// If the archive contains precompressed variants of an asset, e.g. `app.js.br`, `app.js.zst` or `app.js.gz` for `app.js`,
+/// the best variant accepted by the client (see the `Accept-Encoding` header) is served with a `Content-Encoding` header,
+/// falling back to the asset itself. Responses for such assets have a `Vary: accept-encoding` header.
+/// Use [`ServeArchive::precompressed`] to disable this.
+///
+/// Single-page applications with client-side routing need `index.html` for all paths which are not assets.
+/// Use [`ServeArchive::fallback`] (or [`ServeArchive::fallback_for_prefix`] to limit this to some paths) to serve an asset for unknown paths.
+///
+/// By default, responses have no `Cache-Control` header.
+/// Rules for assets by file extension or name prefix are added with [`ServeArchive::cache_control_for_extension`] and [`ServeArchive::cache_control_for_prefix`],
+/// and the first matching rule applies. [`ServeArchive::cache_control`] sets the header of all other assets.
+///
+/// Since servers usually need `'static` services, the archive needs to live for the rest of the program, e.g. by using [`include_dir_lazy!`] or [`NamedArchive::leak`].
+///
+/// # Examples
+///
+/// Responding to requests without a framework:
+///
+/// ```
+/// # #[cfg(feature = "http")] {
+/// use include_assets::{include_dir, NamedArchive, ServeArchive};
+///
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// let service = ServeArchive::new(&archive);
+///
+/// let response = service.respond(&http::Request::get("/hello.txt").body(()).unwrap());
+/// assert_eq!(response.status(), 200);
+/// assert_eq!(response.headers()["content-type"], "text/plain");
+/// assert_eq!(response.body().as_ref(), b"Hello, world!");
+/// # assert_eq!(response.headers()["content-length"], "13");
+/// # assert_eq!(service.respond(&http::Request::new(())).status(), 404);
+/// # assert_eq!(service.respond(&http::Request::get("/hello%2etxt").body(()).unwrap()).status(), 200);
+/// # assert_eq!(service.respond(&http::Request::get("/../hello.txt").body(()).unwrap()).status(), 404);
+/// # assert_eq!(service.respond(&http::Request::post("/hello.txt").body(()).unwrap()).status(), 405);
+/// # assert_eq!(service.respond(&http::Request::head("/hello.txt").body(()).unwrap()).body().as_ref(), b"");
+///
+/// // the client already has the current version
+/// let etag = response.headers()["etag"].clone();
+/// let response = service.respond(&http::Request::get("/hello.txt").header("if-none-match", &etag).body(()).unwrap());
+/// assert_eq!(response.status(), 304);
+/// # assert!(response.body().is_empty());
+/// # let request = http::Request::get("/hello.txt").header("if-none-match", "W/\"0123\", *");
+/// # assert_eq!(service.respond(&request.body(()).unwrap()).status(), 304);
+/// # let request = http::Request::get("/hello.txt").header("if-none-match", "\"0123\"");
+/// # assert_eq!(service.respond(&request.body(()).unwrap()).status(), 200);
+/// # let last_modified = service.respond(&http::Request::get("/hello.txt").body(()).unwrap()).headers()["last-modified"].clone();
+/// # let request = http::Request::get("/hello.txt").header("if-modified-since", last_modified.clone());
+/// # assert_eq!(service.respond(&request.body(()).unwrap()).status(), 304);
+/// # let request = http::Request::get("/hello.txt").header("if-modified-since", "Thu, 01 Jan 1970 00:00:00 GMT");
+/// # assert_eq!(service.respond(&request.body(()).unwrap()).status(), 200);
+/// # let request = http::Request::get("/hello.txt").header("if-modified-since", last_modified).header("if-none-match", "\"0123\"");
+/// # assert_eq!(service.respond(&request.body(()).unwrap()).status(), 200);
+///
+/// // the client only wants a part of the asset
+/// let response = service.respond(&http::Request::get("/hello.txt").header("range", "bytes=7-11").body(()).unwrap());
+/// assert_eq!(response.status(), 206);
+/// assert_eq!(response.headers()["content-range"], "bytes 7-11/13");
+/// assert_eq!(response.body().as_ref(), b"world");
+/// # assert_eq!(archive.slice("hello.txt", 7..12), Some(&b"world"[..]));
+/// # assert_eq!(archive.slice("hello.txt", 7..14), None);
+/// # let range = |range: &str| service.respond(&http::Request::get("/hello.txt").header("range", range).body(()).unwrap());
+/// # assert_eq!(range("bytes=-6").body().as_ref(), b"world!");
+/// # assert_eq!(range("bytes=7-").body().as_ref(), b"world!");
+/// # assert_eq!(range("bytes=7-100").headers()["content-range"], "bytes 7-12/13");
+/// # assert_eq!(range("bytes=0-1, 3-4").status(), 200);
+/// # assert_eq!(range("bytes=5-2").status(), 200);
+/// # assert_eq!(range("lines=1-2").status(), 200);
+/// # assert_eq!(range("bytes=13-").status(), 416);
+/// # assert_eq!(range("bytes=13-").headers()["content-range"], "bytes */13");
+/// # let request = http::Request::get("/hello.txt").header("range", "bytes=7-11").header("if-range", "\"0123\"");
+/// # assert_eq!(service.respond(&request.body(()).unwrap()).status(), 200);
+/// # let request = http::Request::get("/hello.txt").header("range", "bytes=7-11").header("if-range", etag);
+/// # assert_eq!(service.respond(&request.body(()).unwrap()).status(), 206);
+/// # }
+/// ```
+///
+/// Caching fingerprinted assets forever, but revalidating HTML pages:
+///
+/// ```
+/// # #[cfg(feature = "http")] {
+/// use include_assets::{NamedArchive, ServeArchive};
+///
+/// let archive: NamedArchive = [("index.html", "<h1>Hi</h1>"), ("static/app.3f2a.js", "alert()"), ("robots.txt", "")].into_iter().collect();
+/// let service = ServeArchive::new(&archive)
+///     .cache_control_for_extension("html", "no-cache")
+///     .cache_control_for_prefix("static/", "public, max-age=31536000, immutable")
+///     .cache_control("public, max-age=3600");
+///
+/// let response = service.respond(&http::Request::get("/static/app.3f2a.js").body(()).unwrap());
+/// assert_eq!(response.headers()["cache-control"], "public, max-age=31536000, immutable");
+/// let response = service.respond(&http::Request::get("/").body(()).unwrap());
+/// assert_eq!(response.headers()["cache-control"], "no-cache");
+/// # let response = service.respond(&http::Request::get("/robots.txt").body(()).unwrap());
+/// # assert_eq!(response.headers()["cache-control"], "public, max-age=3600");
+/// # let etag = response.headers()["etag"].clone();
+/// # let response = service.respond(&http::Request::get("/robots.txt").header("if-none-match", etag).body(()).unwrap());
+/// # assert_eq!(response.status(), 304);
+/// # assert_eq!(response.headers()["cache-control"], "public, max-age=3600");
+/// # assert!(!ServeArchive::new(&archive).respond(&http::Request::get("/").body(()).unwrap()).headers().contains_key("cache-control"));
+/// # }
+/// ```
+///
+/// Serving a single-page application:
+///
+/// ```
+/// # #[cfg(feature = "http")] {
+/// use include_assets::{NamedArchive, ServeArchive};
+///
+/// let archive: NamedArchive = [("index.html", "<h1>App</h1>"), ("admin/index.html", "<h1>Admin</h1>"), ("app.js", "route()")].into_iter().collect();
+/// let service = ServeArchive::new(&archive).fallback_for_prefix("admin", "admin/index.html").fallback("index.html");
+///
+/// let response = service.respond(&http::Request::get("/users/42").body(()).unwrap());
+/// assert_eq!(response.status(), 200);
+/// assert_eq!(response.body().as_ref(), b"<h1>App</h1>");
+/// let response = service.respond(&http::Request::get("/admin/users/42").body(()).unwrap());
+/// assert_eq!(response.body().as_ref(), b"<h1>Admin</h1>");
+/// # assert_eq!(service.respond(&http::Request::get("/admin").body(()).unwrap()).body().as_ref(), b"<h1>Admin</h1>");
+/// # assert_eq!(service.respond(&http::Request::get("/administrator").body(()).unwrap()).body().as_ref(), b"<h1>App</h1>");
+/// # assert_eq!(service.respond(&http::Request::get("/app.js").body(()).unwrap()).body().as_ref(), b"route()");
+/// # assert_eq!(service.respond(&http::Request::get("/../etc/passwd").body(()).unwrap()).status(), 404);
+/// # let service = ServeArchive::new(&archive).fallback_for_prefix("/admin/", "admin/index.html");
+/// # assert_eq!(service.respond(&http::Request::get("/admin/users/42").body(()).unwrap()).body().as_ref(), b"<h1>Admin</h1>");
+/// # assert_eq!(service.respond(&http::Request::get("/users/42").body(()).unwrap()).status(), 404);
+/// # let service = ServeArchive::new(&archive).fallback("missing.html");
+/// # assert_eq!(service.respond(&http::Request::get("/users/42").body(()).unwrap()).status(), 404);
+/// # }
+/// ```
+///
+/// Serving precompressed variants:
+///
+/// ```
+/// # #[cfg(feature = "http")] {
+/// use include_assets::{NamedArchive, ServeArchive};
+///
+/// let archive: NamedArchive = [("app.js", &b"alert()"[..]), ("app.js.gz", &b"\x1f\x8b..."[..])].into_iter().collect();
+/// let service = ServeArchive::new(&archive);
+///
+/// let response = service.respond(&http::Request::get("/app.js").header("accept-encoding", "gzip, deflate").body(()).unwrap());
+/// assert_eq!(response.headers()["content-encoding"], "gzip");
+/// assert_eq!(response.headers()["vary"], "accept-encoding");
+/// assert_eq!(response.body().as_ref(), b"\x1f\x8b...");
+///
+/// let response = service.respond(&http::Request::get("/app.js").body(()).unwrap());
+/// assert!(!response.headers().contains_key("content-encoding"));
+/// assert_eq!(response.body().as_ref(), b"alert()");
+/// # assert_eq!(response.headers()["vary"], "accept-encoding");
+/// # let encoding = |accept: &str| {
+/// #     let response = service.respond(&http::Request::get("/app.js").header("accept-encoding", accept).body(()).unwrap());
+/// #     response.headers().get("content-encoding").map(|value| value.to_str().unwrap().to_owned())
+/// # };
+/// # assert_eq!(encoding("br"), None);
+/// # assert_eq!(encoding("gzip;q=0"), None);
+/// # assert_eq!(encoding("gzip;q=0.5, identity"), None);
+/// # assert_eq!(encoding("gzip;q=0.5, identity;q=0.5"), Some("gzip".into()));
+/// # assert_eq!(encoding("*"), Some("gzip".into()));
+/// # assert_eq!(encoding("br, GZIP ; q=0.8"), Some("gzip".into()));
+/// # assert_eq!(service.clone().precompressed(false).respond(&http::Request::get("/app.js").header("accept-encoding", "gzip").body(()).unwrap()).body().as_ref(), b"alert()");
+/// # let gzip_etag = service.respond(&http::Request::get("/app.js").header("accept-encoding", "gzip").body(()).unwrap()).headers()["etag"].clone();
+/// # let response = service.respond(&http::Request::get("/app.js").header("if-none-match", gzip_etag.clone()).body(()).unwrap());
+/// # assert_eq!(response.status(), 200);
+/// # let response = service.respond(&http::Request::get("/app.js").header("if-none-match", gzip_etag).header("accept-encoding", "gzip").body(()).unwrap());
+/// # assert_eq!(response.status(), 304);
+/// # assert_eq!(response.headers()["vary"], "accept-encoding");
+/// # }
+/// ```
+///
+/// Using it with axum:
+///
+/// ```
+/// # #[cfg(feature = "axum")] {
+/// use include_assets::{include_dir, NamedArchive, ServeArchive};
+///
+/// let archive = NamedArchive::load(include_dir!("assets")).leak();
+/// let app: axum::Router = axum::Router::new().fallback_service(ServeArchive::new(archive));
+/// # let _ = app;
+/// # use tower_service::Service as _;
+/// # let response = ServeArchive::new(archive).call(http::Request::get("/hello.txt").body(()).unwrap()).into_inner().unwrap();
+/// # assert_eq!(response.status(), 200);
+/// # }
+/// ```
+///
+/// Using it with hyper, e.g. with `hyper::server::conn::http1::Builder::serve_connection`:
+///
+/// ```
+/// # #[cfg(feature = "hyper")] {
+/// use hyper::service::Service;
+/// use include_assets::{include_dir, NamedArchive, ServeArchive};
+///
+/// let archive = NamedArchive::load(include_dir!("assets")).leak();
+/// let service = ServeArchive::new(archive);
+/// # let response = service.call(http::Request::get("/hello.txt").body(()).unwrap()).into_inner().unwrap();
+/// # assert_eq!(response.status(), 200);
+/// # }
+/// ```
+#[cfg(feature = "http")]
+pub use include_assets_decode::serve::ServeArchive;
+
+/// Integration with actix-web (requires feature `actix`).
+///
+/// [`actix::scope`] serves the assets of a [`NamedArchive`] under a mount path, like `actix-files` does for a directory.
+/// Responses are the same as those of [`ServeArchive`], including `Content-Type` headers.
+/// Pass a configured `ServeArchive` instead of the archive to set `Cache-Control` rules.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "actix")] {
+/// use actix_web::{test, App};
+/// use include_assets::{include_dir, NamedArchive};
+///
+/// let archive = NamedArchive::load(include_dir!("assets")).leak();
+/// let app = App::new().service(include_assets::actix::scope("/static", archive));
+/// # actix_rt::System::new().block_on(async {
+/// #     let app = test::init_service(app).await;
+/// #     let response = test::call_service(&app, test::TestRequest::get().uri("/static/hello.txt").to_request()).await;
+/// #     assert_eq!(response.status(), 200);
+/// #     assert_eq!(response.headers().get("content-type").unwrap(), "text/plain");
+/// #     let etag = response.headers().get("etag").unwrap().clone();
+/// #     assert_eq!(test::read_body(response).await, "Hello, world!");
+/// #     let request = test::TestRequest::get().uri("/static/hello.txt").insert_header(("if-none-match", etag)).to_request();
+/// #     assert_eq!(test::call_service(&app, request).await.status(), 304);
+/// #     let response = test::call_service(&app, test::TestRequest::get().uri("/static/missing.txt").to_request()).await;
+/// #     assert_eq!(response.status(), 404);
+/// # });
+/// # }
+/// ```
+#[cfg(feature = "actix")]
+pub use include_assets_decode::actix;
+
+/// Integration with Rocket (requires feature `rocket`).
+///
+/// [`rocket::FileServer`] serves the assets of a [`NamedArchive`] wherever it is mounted, like Rocket's own `FileServer` does for a directory.
+/// Responses are the same as those of [`ServeArchive`], including `Content-Type` headers.
+/// Pass a configured `ServeArchive` instead of the archive to set `Cache-Control` rules.
+/// Requests for missing assets are forwarded with status `404 Not Found`, so other routes and catchers can handle them.
+/// Like Rocket's `FileServer`, the route has rank 10 by default, which can be changed with [`rocket::FileServer::rank`].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "rocket")] {
+/// use include_assets::{include_dir, rocket::FileServer, NamedArchive, ServeArchive};
+///
+/// let archive = NamedArchive::load(include_dir!("assets")).leak();
+/// let app = rocket::build().mount("/static", FileServer::new(ServeArchive::new(archive).cache_control("max-age=3600")));
+/// # let client = rocket::local::blocking::Client::untracked(app).unwrap();
+/// # let response = client.get("/static/hello.txt").dispatch();
+/// # assert_eq!(response.status(), rocket::http::Status::Ok);
+/// # assert_eq!(response.headers().get_one("content-type"), Some("text/plain"));
+/// # assert_eq!(response.headers().get_one("cache-control"), Some("max-age=3600"));
+/// # let etag = response.headers().get_one("etag").unwrap().to_owned();
+/// # assert_eq!(response.into_string().as_deref(), Some("Hello, world!"));
+/// # let response = client.get("/static/hello.txt").header(rocket::http::Header::new("if-none-match", etag)).dispatch();
+/// # assert_eq!(response.status(), rocket::http::Status::NotModified);
+/// # let response = client.head("/static/hello.txt").dispatch();
+/// # assert_eq!(response.status(), rocket::http::Status::Ok);
+/// # assert_eq!(response.into_string().as_deref(), Some(""));
+/// # assert_eq!(client.get("/static/missing.txt").dispatch().status(), rocket::http::Status::NotFound);
+/// # }
+/// ```
+#[cfg(feature = "rocket")]
+pub use include_assets_decode::rocket;
+
+/// Integration with the Bevy game engine (requires feature `bevy`).
+///
+/// [`bevy::ArchiveAssetReader`] implements Bevy's `AssetReader` for a [`NamedArchive`], so the `AssetServer` loads embedded assets by their usual paths.
+/// Register it as an asset source before adding the `AssetPlugin` (which is part of `DefaultPlugins`),
+/// either under a name to load assets with paths like `"embedded://textures/player.png"`, or as the default source to replace the `assets` directory.
+/// Meta files are looked up in the archive as well, e.g. `textures/player.png.meta`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "bevy")] {
+/// use bevy_app::App;
+/// use bevy_asset::AssetApp;
+/// use include_assets::{bevy::ArchiveAssetReader, include_dir_lazy};
+///
+/// include_dir_lazy!(static ASSETS = "assets");
+///
+/// let mut app = App::new();
+/// app.register_asset_source("embedded", ArchiveAssetReader::source(&ASSETS));
+/// // app.add_plugins(DefaultPlugins);
+/// # use bevy_asset::io::{AssetReader, Reader};
+/// # let reader = ArchiveAssetReader::new(&ASSETS);
+/// # futures_lite::future::block_on(async {
+/// #     let mut data = vec![];
+/// #     reader.read("hello.txt".as_ref()).await.unwrap().read_to_end(&mut data).await.unwrap();
+/// #     assert_eq!(data, b"Hello, world!");
+/// #     assert!(reader.read("missing.txt".as_ref()).await.is_err());
+/// #     assert!(reader.is_directory("".as_ref()).await.unwrap());
+/// #     assert!(!reader.is_directory("hello.txt".as_ref()).await.unwrap());
+/// #     let names: Vec<_> = futures_lite::StreamExt::collect(reader.read_directory("".as_ref()).await.unwrap()).await;
+/// #     assert!(names.contains(&"hello.txt".into()));
+/// # });
+/// # }
+/// ```
+#[cfg(feature = "bevy")]
+pub use include_assets_decode::bevy;
+
+/// Templates for the Tera template engine (requires feature `tera`).
+///
+/// [`tera::load`] creates a `Tera` instance with the assets under a prefix as templates, and [`tera::add_templates`] adds them to an existing instance,
+/// so templates included in the executable don't need to be extracted to a directory first.
+/// Templates are named by their asset name without the prefix, like Tera names templates by their path relative to the glob.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "tera")] {
+/// use include_assets::NamedArchive;
+///
+/// // usually `NamedArchive::load(include_dir!("assets"))`
+/// let archive = NamedArchive::from_iter([
+///     ("templates/base.html", "<h1>{% block title %}{% endblock %}</h1>"),
+///     ("templates/hello.html", r#"{% extends "base.html" %}{% block title %}Hello, {{ name }}!{% endblock %}"#),
+///     ("style.css", "h1 { color: red }"),
+/// ]);
+/// let tera = include_assets::tera::load(&archive, "templates/").unwrap();
+///
+/// let mut context = tera::Context::new();
+/// context.insert("name", "world");
+/// assert_eq!(tera.render("hello.html", &context).unwrap(), "<h1>Hello, world!</h1>");
+/// # }
+/// ```
+#[cfg(feature = "tera")]
+pub use include_assets_decode::tera;
+
+/// Templates for the Handlebars template engine (requires feature `handlebars`).
+///
+/// [`handlebars::register_templates`] registers the assets under a prefix as templates,
+/// so templates included in the executable don't need to be extracted to a directory first.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "handlebars")] {
+/// use include_assets::NamedArchive;
+///
+/// // usually `NamedArchive::load(include_dir!("assets"))`
+/// let archive = NamedArchive::from_iter([("templates/hello.hbs", "Hello, {{name}}!"), ("style.css", "h1 { color: red }")]);
+/// let mut registry = handlebars::Handlebars::new();
+/// include_assets::handlebars::register_templates(&mut registry, &archive, "templates/").unwrap();
+///
+/// let html = registry.render("hello.hbs", &serde_json::json!({ "name": "world" })).unwrap();
+/// assert_eq!(html, "Hello, world!");
+/// # }
+/// ```
+#[cfg(feature = "handlebars")]
+pub use include_assets_decode::handlebars;
+
+/// Helpers for passing assets to JavaScript on WebAssembly (requires feature `wasm`).
+///
+/// [`wasm::uint8_array`] copies an asset into a new `Uint8Array`.
+/// [`wasm::uint8_array_view`] creates a `Uint8Array` viewing the asset in WebAssembly memory without copying, which requires `unsafe`,
+/// since the view must neither be modified nor be used after WebAssembly memory grows.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[cfg(feature = "wasm")] {
+/// use include_assets::{include_dir, wasm, NamedArchive};
+///
+/// let archive = NamedArchive::load(include_dir!("assets")).leak();
+/// let hello = wasm::uint8_array(&archive["hello.txt"]);
+/// // SAFETY: the view is only read, and used before Rust code runs again
+/// let view = unsafe { wasm::uint8_array_view(&archive["hello.txt"]) };
+/// # let _ = (hello, view);
+/// # }
+/// ```
+#[cfg(feature = "wasm")]
+pub use include_assets_decode::wasm;
+
+/// C interface to archives, for applications mixing C and Rust (requires feature `ffi`).
+///
+/// Declare an exported function returning an archive with [`include_dir_ffi!`], then look up assets from C with the functions in this module.
+/// The declarations for C are in `include/include_assets.h` in the `include_assets` crate.
+/// The functions are exported from static and dynamic libraries (crate types `staticlib` and `cdylib`) which depend on `include_assets`.
+///
+/// All functions accept null pointers and return null (or 0) if there is no result.
+/// Asset data and names are not null-terminated, their length is written to the `len` parameter instead.
+///
+/// ```c
+/// #include "include_assets.h"
+///
+/// const IncludeAssetsArchive *game_assets(void);
+///
+/// size_t len;
+/// const uint8_t *hello = include_assets_get(game_assets(), "hello.txt", &len);
+/// if (hello) {
+///     fwrite(hello, 1, len, stdout);
+/// }
+/// ```
+///
+/// The functions can also be called from Rust, which requires `unsafe`:
+///
+/// ```
+/// # #[cfg(feature = "ffi")] {
+/// use include_assets::{ffi, include_dir_ffi};
+///
+/// include_dir_ffi!(fn doc_assets = "assets", compression = "lz4");
+///
+/// let mut len = 0;
+/// // SAFETY: the archive was returned by a function declared with `include_dir_ffi!`, the name is null-terminated
+/// let data = unsafe { ffi::include_assets_get(doc_assets(), c"hello.txt".as_ptr(), &mut len) };
+/// // SAFETY: the function returned a pointer to `len` bytes which live for the rest of the program
+/// assert_eq!(unsafe { std::slice::from_raw_parts(data, len) }, b"Hello, world!");
+/// # assert!(unsafe { ffi::include_assets_get(doc_assets(), c"missing.txt".as_ptr(), &mut len) }.is_null());
+/// # assert!(unsafe { ffi::include_assets_get(std::ptr::null(), c"hello.txt".as_ptr(), &mut len) }.is_null());
+/// # assert_eq!(unsafe { ffi::include_assets_count(doc_assets()) }, 3);
+/// # let name = unsafe { ffi::include_assets_name(doc_assets(), 0, &mut len) };
+/// # let name = unsafe { std::slice::from_raw_parts(name, len) };
+/// # assert!(!unsafe { ffi::include_assets_get_n(doc_assets(), name.as_ptr(), name.len(), std::ptr::null_mut()) }.is_null());
+/// # assert!(unsafe { ffi::include_assets_name(doc_assets(), 3, &mut len) }.is_null());
+/// # include_dir_ffi!(fn doc_private_assets = "assets", private = "unused.txt");
+/// # for archive in [doc_assets(), doc_private_assets()] {
+/// #     let names: Vec<_> = unsafe { &*archive }.names().collect();
+/// #     for (i, expected) in names.iter().enumerate() {
+/// #         let name = unsafe { ffi::include_assets_name(archive, i, &mut len) };
+/// #         assert_eq!(unsafe { std::slice::from_raw_parts(name, len) }, expected.as_bytes());
+/// #     }
+/// #     assert!(unsafe { ffi::include_assets_name(archive, names.len(), &mut len) }.is_null());
+/// # }
+/// # }
+/// ```
+#[cfg(feature = "ffi")]
+pub use include_assets_decode::ffi;
+
+/// Read-only file system for archives, on Linux (requires feature `fuse`).
+///
+/// [`NamedArchive::mount`] exposes the assets of an archive as files, e.g. for subprocesses and tools which can only read files.
+/// Assets are named by their path, with a directory for each `/` in their names.
+/// Requests are answered on a background thread until the returned [`Mount`](fuse::Mount) is unmounted or dropped.
+///
+/// Mounting requires the `CAP_SYS_ADMIN` capability, otherwise the `fusermount3` (or `fusermount`) program of libfuse is used,
+/// which must be installed.
+///
+/// ```no_run
+/// # #[cfg(all(feature = "fuse", target_os = "linux"))] {
+/// use include_assets::include_dir_lazy;
+///
+/// include_dir_lazy!(static ASSETS = "assets");
+///
+/// let mountpoint = std::env::temp_dir().join("assets");
+/// std::fs::create_dir_all(&mountpoint).unwrap();
+/// let mount = ASSETS.mount(&mountpoint).unwrap();
+///
+/// let status = std::process::Command::new("cat").arg(mountpoint.join("hello.txt")).status().unwrap();
+/// assert!(status.success());
+///
+/// mount.unmount().unwrap();
+/// # }
+/// ```
+#[cfg(all(feature = "fuse", target_os = "linux"))]
+pub use include_assets_decode::fuse;
+
+/// Compare codecs on your own assets, to choose the `compression` and `level` options (requires feature `std`).
+///
+/// [`bench::compare`] compresses and decompresses the assets with each codec and reports the compression ratio and the time taken.
+/// [`bench::default_codecs`] returns a selection of codecs and levels among those enabled via crate features.
+/// Measure in release builds, debug builds are much slower.
+///
+/// ```
+/// use include_assets::{bench, include_dir, NamedArchive};
+///
+/// let archive = NamedArchive::load(include_dir!("assets"));
+/// let reports = bench::compare(archive.assets().map(|(_name, data)| data), bench::default_codecs()).unwrap();
+/// for report in &reports {
+///     // e.g. `compression = "zstd", level = 5: 1048576 -> 262144 bytes (ratio 4.00), compressed in 6.2ms, decompressed in 1.3ms`
+///     println!("{report}");
+/// }
+/// # assert_eq!(reports[0].options(), r#"compression = "uncompressed""#);
+/// # assert_eq!(reports[0].compressed_size, reports[0].uncompressed_size);
+///
+/// // or compare specific codecs
+/// # #[cfg(feature = "zstd")] {
+/// let reports = bench::compare(archive.assets().map(|(_name, data)| data), [bench::Zstd { level: 3 }, bench::Zstd { level: 19 }]).unwrap();
+/// # assert_eq!(reports.len(), 2);
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub use include_assets_decode::bench;
+
+/// Compression codecs, and the extension point for codecs of other crates.
+///
+/// A crate can ship a codec by implementing [`Codec`](codec::Codec) and [`CustomCodec`](codec::CustomCodec) for it.
+/// Both traits are stable: methods added later have default implementations, and codec tags in archive files never change meaning.
+///
+/// The `include_dir!` macro cannot run code of other crates, so it only supports the built-in codecs.
+/// Archives are compressed with a custom codec in a build script instead, with `Compression::custom` of `include_assets_build`, and included with [`include_archive!`].
+/// Loading is generic over the codec, so such archives load like any other.
+/// Archive files record the tag of the codec, [`codec::register`] makes a custom codec known when reading them.
+///
+/// ```
+/// use include_assets::codec::{self, Codec, CustomCodec};
+/// use include_assets::{file, NamedArchive};
+/// # use include_assets::{include_dir, CompressedNamedArchive};
+///
+/// /// Toy codec which flips bits instead of compressing
+/// #[derive(Debug, Clone, Copy)]
+/// pub struct Xor {
+///     pub key: u8,
+/// }
+///
+/// impl Codec for Xor {
+///     type CompressionError = std::convert::Infallible;
+///     type DecompressionError = std::io::Error;
+///
+///     fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Self::CompressionError> {
+///         Ok(data.iter().map(|byte| byte ^ self.key).collect())
+///     }
+///
+///     fn decompress_checked(&self, src: &[u8], dst: &mut [u8]) -> Result<(), Self::DecompressionError> {
+///         if src.len() != dst.len() {
+///             return Err(std::io::Error::other("unexpected uncompressed size"));
+///         }
+///         for (dst, src) in dst.iter_mut().zip(src) {
+///             *dst = src ^ self.key;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// impl CustomCodec for Xor {
+///     const TAG: u32 = codec::FIRST_CUSTOM_TAG + 0x584f52;
+///
+///     fn level(&self) -> i32 {
+///         self.key.into()
+///     }
+///
+///     fn from_level(level: i32) -> Option<Self> {
+///         Some(Xor { key: level.try_into().ok()? })
+///     }
+///
+///     fn constructor(&self) -> String {
+///         format!("::xor_codec::Xor {{ key: {} }}", self.key)
+///     }
+/// }
+///
+/// // in build.rs: `Archive::new("assets").compression(Compression::custom(&Xor { key: 42 })).write("assets").unwrap();`
+/// // let compressed = include_archive!("assets");
+/// # let plain = include_dir!("assets", compression = "uncompressed");
+/// # let xor = Xor { key: 42 };
+/// # let (data, names, sizes) = (xor.compress(plain.data).unwrap(), xor.compress(plain.compressed_names).unwrap(), xor.compress(plain.compressed_sizes).unwrap());
+/// # let compressed = CompressedNamedArchive {
+/// #     codec: xor,
+/// #     data: &data,
+/// #     uncompressed_data_size: plain.uncompressed_data_size,
+/// #     compressed_names: &names,
+/// #     uncompressed_names_size: plain.uncompressed_names_size,
+/// #     checksums: plain.checksums,
+/// #     checksum_size: plain.checksum_size,
+/// #     compressed_sizes: &sizes,
+/// #     uncompressed_sizes_size: plain.uncompressed_sizes_size,
+/// #     align: plain.align,
+/// #     metadata_size: plain.metadata_size,
+/// #     signature: None,
+/// #     directory: None,
+/// #     mime_types: &[],
+/// #     private: &[],
+/// #     perfect_hash: plain.perfect_hash,
+/// #     shards: plain.shards,
+/// # };
+/// let archive = NamedArchive::load(compressed);
+/// assert_eq!(&archive["hello.txt"], b"Hello, world!");
+///
+/// // archive files can only be read once the codec is registered
+/// let mut bytes = vec![];
+/// compressed.write_to(&mut bytes).unwrap();
+/// assert!(file::parse(&bytes).is_err());
+/// codec::register::<Xor>();
+/// let archive = NamedArchive::load(file::parse(&bytes).unwrap());
+/// assert_eq!(&archive["hello.txt"], b"Hello, world!");
+/// # assert_eq!(file::parse(&bytes).unwrap().codec.tag(), Xor::TAG);
+/// # assert_eq!(codec::AnyCodec::from(xor).level(), 42);
+/// # assert!(codec::AnyCodec::from_tag_and_level(Xor::TAG, 256).is_none());
+/// # codec::register::<Xor>();
+/// # #[derive(Debug, Clone, Copy)]
+/// # struct Other;
+/// # impl Codec for Other {
+/// #     type CompressionError = std::convert::Infallible;
+/// #     type DecompressionError = std::io::Error;
+/// #     fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Self::CompressionError> { Ok(data.to_vec()) }
+/// #     fn decompress_checked(&self, src: &[u8], dst: &mut [u8]) -> Result<(), Self::DecompressionError> { Ok(dst.copy_from_slice(src)) }
+/// # }
+/// # impl CustomCodec for Other {
+/// #     const TAG: u32 = Xor::TAG;
+/// #     fn level(&self) -> i32 { 0 }
+/// #     fn from_level(_: i32) -> Option<Self> { Some(Other) }
+/// #     fn constructor(&self) -> String { "Other".into() }
+/// # }
+/// # assert!(std::panic::catch_unwind(codec::register::<Other>).is_err());
+/// # let reports = include_assets::bench::compare(archive.assets().map(|(_name, data)| data), [xor]).unwrap();
+/// # assert_eq!(reports[0].options(), "::xor_codec::Xor { key: 42 }");
+/// ```
+pub use include_assets_decode::codec;
+
+#[doc(hidden)]
+pub use include_assets_decode::named::CompressedNamedArchive;
+
+pub use include_assets_decode::error;
+pub use include_assets_decode::file;
+
+#[cfg(feature = "hot-reload")]
+pub use include_assets_decode::watch;
 
 #[doc(hidden)]
 pub mod do_not_use_this_directly {
     pub use include_assets_decode::checksum::Checksum;
     pub use include_assets_decode::codec;
+    #[cfg(feature = "serde")]
+    pub use include_assets_decode::deserialize;
+    pub use include_assets_decode::enums::AssetGroup;
+    pub use include_assets_decode::lookup::PerfectHash;
 }
@@ -0,0 +1,480 @@
+// Compression of asset directories, shared with the include_assets_encode crate.
+
+use anyhow::Context as _;
+
+use include_assets_decode::checksum::{Checksum, Hasher};
+use include_assets_decode::codec::{AnyCodec, Codec};
+use include_assets_decode::common::u32_to_usize;
+use include_assets_decode::named::MAX_ALIGN;
+
+pub struct NamedArchive {
+    /// Compressed data
+    ///
+    /// All assets are concatenated, each preceded by zero bytes such that it starts at a multiple of `align`.
+    /// The order of asset data must match the order of assets in `compressed_names`.
+    pub compressed_data: std::vec::Vec<u8>,
+    /// Size of the data after decompression
+    pub uncompressed_data_size: u32,
+    /// Compressed names of the assets in ascending order, with separating null bytes
+    pub compressed_names: std::vec::Vec<u8>,
+    /// Size of the uncompressed names (including separating null bytes)
+    pub uncompressed_names_size: u32,
+    /// Sizes of asset data as varints, in the same order as `compressed_names`.
+    pub compressed_sizes: std::vec::Vec<u8>,
+    /// Size of the uncompressed sizes
+    pub uncompressed_sizes_size: u32,
+    /// Concatenated asset checksums of `checksum_size` bytes each, in the same order as `compressed_names`.
+    pub checksums: std::vec::Vec<u8>,
+    /// Size of each checksum in bytes
+    pub checksum_size: u32,
+    /// Alignment of the assets within the uncompressed data
+    pub align: u32,
+    /// Size of the metadata at the end of the uncompressed data, 0 if names, sizes, and checksums are compressed separately
+    pub metadata_size: u32,
+    /// End offsets of the compressed and uncompressed shards (little endian `u32` pairs), empty if the data is not sharded
+    pub shards: std::vec::Vec<u8>,
+}
+
+/// Read and compress the assets, given as names and paths of their files.
+///
+/// The files are read in parts which are fed to the codec (see [`Codec::compress_parts`]) while computing checksums,
+/// so that with a streaming codec, the uncompressed data never needs to be in memory at once.
+///
+/// With shards, compressed shards are kept in `shard_cache` (if given) and reused by later builds if their data is unchanged,
+/// so that changing one asset only requires compressing the shards containing it.
+/// Each shard is then held in memory uncompressed.
+///
+/// Each asset starts at a multiple of `align` in the uncompressed data, which must be a power of two no greater than [`MAX_ALIGN`].
+/// Checksums are truncated to `checksum_size` bytes (between 1 and 64).
+/// With `single_stream`, the names, sizes, and checksums are appended to the data instead of being compressed separately.
+/// Fails if two assets have the same name, naming the files of both.
+pub fn prepare_named_archive<C: Codec + Copy + Into<AnyCodec>>(
+    codec: &C,
+    assets: std::vec::Vec<(smartstring::SmartString<smartstring::LazyCompact>, std::path::PathBuf)>,
+    shard_size: Option<u32>,
+    shard_cache: Option<&std::path::Path>,
+    align: u32,
+    checksum_size: u32,
+    single_stream: bool,
+) -> anyhow::Result<NamedArchive> {
+    assert!(align.is_power_of_two() && align <= MAX_ALIGN, "invalid asset alignment {align}");
+    assert!((1..=64).contains(&checksum_size), "invalid checksum size {checksum_size}");
+
+    // ensure that names are unique, reporting the files which would be merged otherwise
+    {
+        let mut names = std::collections::HashMap::new();
+        for (name, path) in assets.iter() {
+            if let Some(other) = names.insert(name, path) {
+                return Err(anyhow::Error::msg(format!(
+                    "duplicate asset name '{name}' for files {} and {}",
+                    other.display(),
+                    path.display()
+                )));
+            }
+        }
+    }
+
+    // read assets, compute checksums, and compress data (followed by the metadata if it is part of the same stream)
+    let mut parts = AssetParts::new(&assets, u32_to_usize(align), single_stream.then_some(u32_to_usize(checksum_size)));
+    let (compressed_data, shards) = match shard_size {
+        None => {
+            let compressed_data = codec.compress_parts(&mut parts);
+            parts.check()?;
+            (compressed_data.context("couldn't compress asset data")?, vec![])
+        }
+        Some(shard_size) => {
+            let (mut compressed_data, mut shards) = (vec![], vec![]);
+            for i in 0.. {
+                let start = parts.position;
+                parts.remaining = Some(u32_to_usize(shard_size));
+                let compressed_shard = match shard_cache {
+                    Some(directory) => {
+                        let shard: std::vec::Vec<_> = parts.by_ref().collect();
+                        parts.check()?;
+                        compress_shard_cached(codec, shard, directory)
+                    }
+                    None => codec.compress_parts(&mut parts).map_err(anyhow::Error::new),
+                };
+                parts.check()?;
+                if parts.position == start {
+                    break; // all data has been read
+                }
+                compressed_data.extend(compressed_shard.with_context(|| format!("couldn't compress asset data (shard {i})"))?);
+                let compressed_end = u32::try_from(compressed_data.len()).context("too much compressed data")?;
+                let uncompressed_end = u32::try_from(parts.position).context("too much data")?;
+                shards.extend_from_slice(&compressed_end.to_le_bytes());
+                shards.extend_from_slice(&uncompressed_end.to_le_bytes());
+            }
+            (compressed_data, shards)
+        }
+    };
+    let AssetParts {
+        sizes,
+        checksums,
+        position,
+        metadata_size,
+        ..
+    } = parts;
+    let metadata_size = u32::try_from(metadata_size).context("too much metadata")?;
+
+    // compress asset names and sizes, unless they are part of the data
+    let (compressed_names, uncompressed_names_size, compressed_sizes, uncompressed_sizes_size, checksums) = if single_stream {
+        (vec![], 0, vec![], 0, vec![])
+    } else {
+        let (compressed_names, uncompressed_names_size) =
+            compress_names(codec, assets.iter().map(|(name, _)| name)).context("couldn't compress asset names")?;
+        let (compressed_sizes, uncompressed_sizes_size) =
+            compress_sizes(codec, assets.iter().map(|(name, _)| name).zip(sizes)).context("couldn't compress asset sizes")?;
+        let checksums = truncate_checksums(&checksums, u32_to_usize(checksum_size));
+        (compressed_names, uncompressed_names_size, compressed_sizes, uncompressed_sizes_size, checksums)
+    };
+
+    // ensure that the uncompressed data isn't too big
+    let uncompressed_data_size: u32 = position
+        .try_into()
+        .map_err(|_| anyhow::Error::msg(format!("too much data ({position} bytes)")))?;
+
+    Ok(NamedArchive {
+        compressed_data,
+        uncompressed_data_size,
+        compressed_names,
+        uncompressed_names_size,
+        compressed_sizes,
+        uncompressed_sizes_size,
+        checksums,
+        checksum_size,
+        align,
+        metadata_size,
+        shards,
+    })
+}
+
+/// Concatenate the checksums, truncated to `checksum_size` bytes each (see the `checksum_size` option of `include_dir!`).
+fn truncate_checksums(checksums: &[Checksum], checksum_size: usize) -> std::vec::Vec<u8> {
+    checksums.iter().flat_map(|checksum| &checksum[..checksum_size]).copied().collect()
+}
+
+/// Metadata which single-stream archives store after the asset data
+///
+/// See [`CompressedNamedArchive::metadata_size`](include_assets_decode::named::CompressedNamedArchive::metadata_size) for the format.
+fn metadata<S: AsRef<str>>(names: &[S], sizes: &[usize], checksums: &[Checksum], checksum_size: usize) -> anyhow::Result<std::vec::Vec<u8>> {
+    let (names_bytes, names_size) = join_names(names.iter())?;
+    let (sizes_bytes, sizes_size) = encode_sizes(names.iter().zip(sizes.iter().copied()))?;
+    let number_of_assets = u32::try_from(names.len()).context("too many assets")?;
+    let mut metadata = vec![];
+    for field in [number_of_assets, names_size, sizes_size] {
+        metadata.extend_from_slice(&field.to_le_bytes());
+    }
+    metadata.extend_from_slice(&names_bytes);
+    metadata.extend_from_slice(&sizes_bytes);
+    metadata.extend_from_slice(&truncate_checksums(checksums, checksum_size));
+    Ok(metadata)
+}
+
+/// Compress a shard given as parts, or reuse the compressed shard from `directory` if it has been compressed with the same codec before.
+///
+/// Compressed shards are stored in files named after the checksum of the codec and the uncompressed shard.
+/// A cached shard is only used if it decompresses to exactly the uncompressed shard, otherwise it is compressed and stored again.
+fn compress_shard_cached<C: Codec + Copy + Into<AnyCodec>>(
+    codec: &C,
+    shard: std::vec::Vec<std::vec::Vec<u8>>,
+    directory: &std::path::Path,
+) -> anyhow::Result<std::vec::Vec<u8>> {
+    let any_codec: AnyCodec = (*codec).into();
+    let mut hasher = Hasher::default();
+    hasher.update(&any_codec.tag().to_le_bytes());
+    hasher.update(&any_codec.level().to_le_bytes());
+    for part in &shard {
+        hasher.update(part);
+    }
+    let name: std::string::String = hasher.finish()[..16].iter().map(|byte| format!("{byte:02x}")).collect();
+    let path = directory.join(format!("{name}.shard"));
+    if let Ok(compressed) = std::fs::read(&path) {
+        let mut decompressed = vec![0; shard.iter().map(std::vec::Vec::len).sum()];
+        if codec.decompress_checked(&compressed, &mut decompressed).is_ok() && decompressed == shard.concat() {
+            return Ok(compressed);
+        }
+    }
+    let compressed = codec.compress_parts(&mut shard.into_iter())?;
+    // write to a temporary file first, so that concurrent builds never read a partially written shard
+    std::fs::create_dir_all(directory).with_context(|| format!("couldn't create directory {}", directory.display()))?;
+    let temporary = directory.join(format!("{name}.{}.tmp", std::process::id()));
+    std::fs::write(&temporary, &compressed).with_context(|| format!("couldn't write {}", temporary.display()))?;
+    std::fs::rename(&temporary, &path).with_context(|| format!("couldn't write {}", path.display()))?;
+    Ok(compressed)
+}
+
+/// Maximum size of the parts in which asset files are read
+const PART_SIZE: usize = 1 << 20;
+
+/// The data of asset files in order, in parts of at most [`PART_SIZE`] bytes, recording the size and checksum of each file
+///
+/// Padding parts of zero bytes are inserted before files that would not start at a multiple of `align`.
+/// For single-stream archives, the metadata follows the last file.
+struct AssetParts<'a> {
+    assets: &'a [(smartstring::SmartString<smartstring::LazyCompact>, std::path::PathBuf)],
+    paths: std::slice::Iter<'a, (smartstring::SmartString<smartstring::LazyCompact>, std::path::PathBuf)>,
+    /// File which is being read, with its path, the checksum so far, and the number of bytes read so far
+    current: Option<(std::fs::File, &'a std::path::Path, Hasher, usize)>,
+    /// Number of bytes after which to stop (until this is set again), e.g. at the end of a shard
+    remaining: Option<usize>,
+    /// Total number of bytes read, including padding
+    position: usize,
+    align: usize,
+    sizes: std::vec::Vec<usize>,
+    checksums: std::vec::Vec<Checksum>,
+    /// Checksum size of the metadata following the last file, `None` without metadata or once the metadata has been built
+    metadata: Option<usize>,
+    /// Metadata which has not been returned yet
+    trailer: std::vec::Vec<u8>,
+    /// Size of the metadata, once it has been built
+    metadata_size: usize,
+    /// Error which ended reading early
+    error: Option<anyhow::Error>,
+}
+
+impl<'a> AssetParts<'a> {
+    fn new(assets: &'a [(smartstring::SmartString<smartstring::LazyCompact>, std::path::PathBuf)], align: usize, metadata: Option<usize>) -> Self {
+        Self {
+            assets,
+            paths: assets.iter(),
+            current: None,
+            remaining: None,
+            position: 0,
+            align,
+            sizes: vec![],
+            checksums: vec![],
+            metadata,
+            trailer: vec![],
+            metadata_size: 0,
+            error: None,
+        }
+    }
+
+    /// Return the next part of the metadata following the last file, building the metadata first
+    fn trailer_part(&mut self) -> Option<std::vec::Vec<u8>> {
+        if let Some(checksum_size) = self.metadata.take() {
+            let names: std::vec::Vec<_> = self.assets.iter().map(|(name, _)| name).collect();
+            match metadata(&names, &self.sizes, &self.checksums, checksum_size) {
+                Ok(metadata) => self.trailer = metadata,
+                Err(err) => self.error = Some(err),
+            }
+            self.metadata_size = self.trailer.len();
+        }
+        let len = self.trailer.len().min(self.remaining.unwrap_or(PART_SIZE)).min(PART_SIZE);
+        if len == 0 {
+            return None;
+        }
+        self.position += len;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= len;
+        }
+        Some(self.trailer.drain(..len).collect())
+    }
+
+    /// Return the error which occurred while reading, if any
+    fn check(&mut self) -> anyhow::Result<()> {
+        self.error.take().map_or(Ok(()), Err)
+    }
+}
+
+impl Iterator for AssetParts<'_> {
+    type Item = std::vec::Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use std::io::Read as _;
+
+        while self.error.is_none() && self.remaining != Some(0) {
+            let (file, path, hasher, size) = match &mut self.current {
+                Some(current) => current,
+                None if !self.position.is_multiple_of(self.align) && !self.paths.as_slice().is_empty() => {
+                    let padding = (self.position.next_multiple_of(self.align) - self.position).min(self.remaining.unwrap_or(usize::MAX));
+                    self.position += padding;
+                    if let Some(remaining) = &mut self.remaining {
+                        *remaining -= padding;
+                    }
+                    return Some(vec![0; padding]);
+                }
+                None => {
+                    let Some((_, path)) = self.paths.next() else {
+                        return self.trailer_part();
+                    };
+                    match std::fs::File::open(path) {
+                        Ok(file) => self.current.insert((file, path, Hasher::default(), 0)),
+                        Err(err) => {
+                            self.error = Some(anyhow::Error::new(err).context(format!("Couldn't read file '{}'", path.display())));
+                            return None;
+                        }
+                    }
+                }
+            };
+            let mut part = vec![0; self.remaining.unwrap_or(PART_SIZE).min(PART_SIZE)];
+            match file.read(&mut part) {
+                Ok(0) => {
+                    let (_, _, hasher, size) = self.current.take().expect("a file is being read");
+                    self.sizes.push(size);
+                    self.checksums.push(hasher.finish());
+                }
+                Ok(len) => {
+                    part.truncate(len);
+                    hasher.update(&part);
+                    *size += len;
+                    self.position += len;
+                    if let Some(remaining) = &mut self.remaining {
+                        *remaining -= len;
+                    }
+                    return Some(part);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => self.error = Some(anyhow::Error::new(err).context(format!("Couldn't read file '{}'", path.display()))),
+            }
+        }
+        None
+    }
+}
+
+/// Append `value` as an unsigned LEB128 varint.
+fn push_varint(bytes: &mut std::vec::Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        bytes.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Compress the asset sizes as varints, returning the compressed sizes and their uncompressed length.
+pub fn compress_sizes<C: Codec + ?Sized, S: AsRef<str>, I: Iterator<Item = (S, usize)>>(codec: &C, sizes: I) -> anyhow::Result<(std::vec::Vec<u8>, u32)> {
+    let (sizes_vec, uncompressed_size) = encode_sizes(sizes)?;
+    let compressed_sizes = codec.compress(sizes_vec.as_slice()).context("couldn't compress asset data sizes")?;
+    Ok((compressed_sizes, uncompressed_size))
+}
+
+/// Encode the asset sizes as varints, returning them and their length.
+fn encode_sizes<S: AsRef<str>, I: Iterator<Item = (S, usize)>>(sizes: I) -> anyhow::Result<(std::vec::Vec<u8>, u32)> {
+    let mut sizes_vec = vec![];
+    for (name, size) in sizes {
+        let size: u32 = size
+            .try_into()
+            .with_context(|| format!("asset {} is too big ({} bytes)", name.as_ref(), size))?;
+        push_varint(&mut sizes_vec, size);
+    }
+    // ensure that the uncompressed lengths aren't longer than 4 GiB (i.e. the length fits in a u32)
+    let uncompressed_size = u32::try_from(sizes_vec.len()).map_err(|_| {
+        anyhow::Error::msg(format!(
+            "too many assets: size of uncompressed asset sizes is too big ({} bytes)",
+            sizes_vec.len()
+        ))
+    })?;
+    Ok((sizes_vec, uncompressed_size))
+}
+
+pub fn compress_names<C: Codec + ?Sized, S: AsRef<str>, I: Iterator<Item = S>>(codec: &C, names: I) -> anyhow::Result<(std::vec::Vec<u8>, u32)> {
+    let (uncompressed_names, uncompressed_size) = join_names(names)?;
+    let compressed_names = codec.compress(uncompressed_names.as_slice()).context("couldn't compress asset names")?;
+    Ok((compressed_names, uncompressed_size))
+}
+
+/// Join the asset names with separating null bytes, returning them and their length.
+fn join_names<S: AsRef<str>, I: Iterator<Item = S>>(mut names: I) -> anyhow::Result<(std::vec::Vec<u8>, u32)> {
+    let mut uncompressed_names = vec![];
+    if let Some(first) = names.next() {
+        assert!(!first.as_ref().as_bytes().contains(&0));
+        uncompressed_names.extend_from_slice(first.as_ref().as_bytes());
+        for name in names {
+            assert!(!name.as_ref().as_bytes().contains(&0));
+            uncompressed_names.extend_from_slice(&[0u8]);
+            uncompressed_names.extend_from_slice(name.as_ref().as_bytes());
+        }
+    };
+    let uncompressed_size = u32::try_from(uncompressed_names.len())
+        .map_err(|_| anyhow::Error::msg(format!("uncompressed names are too long ({} bytes)", uncompressed_names.len())))?;
+    Ok((uncompressed_names, uncompressed_size))
+}
+
+/// Generate a perfect hash function over the asset names, in the order in which they are stored in the archive.
+pub fn perfect_hash_tokens<'a, I: Iterator<Item = &'a str>>(names: I) -> proc_macro2::TokenStream {
+    let names: std::vec::Vec<&str> = names.collect();
+    let phf_generator::HashState { key, disps, map } = phf_generator::generate_hash(&names);
+    let (displacements1, displacements2): (std::vec::Vec<u32>, std::vec::Vec<u32>) = disps.into_iter().unzip();
+    let indices = map.into_iter().map(|index| u32::try_from(index).expect("number of assets should fit into u32"));
+    quote::quote! {
+        ::include_assets::do_not_use_this_directly::PerfectHash {
+            key: #key,
+            displacements: &[#((#displacements1, #displacements2)),*],
+            indices: &[#(#indices),*],
+        }
+    }
+}
+
+pub fn checksums_tokens<I: Iterator<Item = Checksum>>(checksums: I) -> proc_macro2::TokenStream {
+    let checksums: std::vec::Vec<_> = checksums.map(|checksum| quote::quote! { [#(#checksum),*] }).collect();
+    quote::quote! {&[#(#checksums),*]}
+}
+
+/// How symbolic links in an asset directory are handled, see the `links` option of `include_dir!`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkRules {
+    /// Fail if the directory contains a symbolic link
+    #[default]
+    Forbid,
+    /// Skip symbolic links
+    Ignore,
+    /// Include the targets of symbolic links
+    Follow,
+}
+
+/// List the files in the directory `base` (recursively), as names relative to `base` and paths, in a deterministic order.
+///
+/// Unless `allow_outside_base`, followed symbolic links must point inside `base`.
+/// The files are not read, see [`prepare_named_archive`].
+pub fn read_dir<P: AsRef<std::path::Path>>(
+    base: P,
+    symlink_rules: SymlinkRules,
+    allow_outside_base: bool,
+) -> anyhow::Result<std::vec::Vec<(smartstring::SmartString<smartstring::LazyCompact>, std::path::PathBuf)>> {
+    let (follow_symlinks, ignore_symlinks) = match symlink_rules {
+        SymlinkRules::Forbid => (false, false),
+        SymlinkRules::Ignore => (false, true),
+        SymlinkRules::Follow => (true, false),
+    };
+    let canonical_base = if follow_symlinks && !allow_outside_base {
+        Some(std::fs::canonicalize(base.as_ref()).with_context(|| format!("couldn't resolve directory {}", base.as_ref().display()))?)
+    } else {
+        None
+    };
+    let mut assets = vec![];
+    for dirent in walkdir::WalkDir::new(base.as_ref()).sort_by_file_name().follow_links(follow_symlinks) {
+        // Note: sorting by file name is important to ensure the same compressed data independent of the creation/modification order of assets
+        let ent = dirent?;
+        if ent.file_type().is_dir() {
+            continue; // ignore
+        } else if ent.file_type().is_file() {
+            let filename = ent
+                .path()
+                .strip_prefix(base.as_ref())
+                .expect("child path should have parent as prefix")
+                .to_str()
+                .with_context(|| format!("Non-UTF-8 file name: '{}'", ent.path().display()))?;
+            if let Some(canonical_base) = &canonical_base {
+                let target = std::fs::canonicalize(ent.path()).with_context(|| format!("couldn't resolve file {}", ent.path().display()))?;
+                if !target.starts_with(canonical_base) {
+                    return Err(anyhow::Error::msg(format!(
+                        "{} links to {}, which is outside the directory (use allow_outside_base to allow this)",
+                        ent.path().display(),
+                        target.display()
+                    )));
+                }
+            }
+            assets.push((filename.into(), ent.path().to_path_buf()))
+        } else if ent.file_type().is_symlink() {
+            if ignore_symlinks {
+                continue; // ignore
+            } else {
+                return Err(anyhow::Error::msg(format!("Encountered a symbolic link: {}", ent.path().display())));
+            }
+        } else {
+            panic!("File {} is neither directory, file, nor symbolic link.", ent.path().display());
+        }
+    }
+    Ok(assets)
+}
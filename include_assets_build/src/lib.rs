@@ -0,0 +1,279 @@
+/*! # Compress `include_assets` archives in a build script
+
+The [`include_dir!`](https://docs.rs/include_assets/latest/include_assets/macro.include_dir.html) macro compresses assets while the crate using it is compiled.
+This crate does the same in a [build script](https://doc.rust-lang.org/cargo/reference/build-scripts.html) instead:
+[`Archive::write`] compresses an asset directory into `OUT_DIR` and generates the code to include it,
+which the `include_archive!` macro of the `include_assets` crate then includes.
+
+Compared to `include_dir!`, this
+- keeps compression out of the proc macro, which needs a lot of memory in the compiler for large asset directories,
+- only compresses assets again if they change (cargo reruns the build script only then), and
+- allows custom pipelines, e.g. generating or preprocessing assets before compressing them.
+
+In `build.rs` (with `include_assets_build` as build dependency):
+
+```no_run
+use include_assets_build::{Archive, Compression};
+
+fn main() {
+    Archive::new("assets").compression(Compression::Zstd { level: 19 }).write("assets").unwrap();
+}
+```
+
+In the crate itself (with `include_assets` as dependency):
+
+```ignore
+use include_assets::{include_archive, NamedArchive};
+
+let archive = NamedArchive::load(include_archive!("assets"));
+```
+
+The archive has the same type as one included with `include_dir!`, i.e. `CompressedNamedArchive<'static, C>` where `C` depends on the compression.
+The features of `include_assets` must enable the codec used for compression.
+Codecs of other crates can be used with [`Compression::custom`], see the `codec` module of `include_assets`.
+*/
+
+pub(crate) mod archive;
+
+pub use archive::SymlinkRules;
+
+use anyhow::Context as _;
+
+/// Compression codec and level of an archive, see the `compression` and `level` options of `include_dir!`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Store the assets without compression
+    Uncompressed,
+    /// lz4 compression (requires feature `lz4`)
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// zstd compression with the given level (requires feature `zstd`)
+    #[cfg(feature = "zstd")]
+    Zstd { level: i32 },
+    /// deflate compression with the given level (requires feature `deflate`)
+    #[cfg(feature = "deflate")]
+    Deflate { level: u8 },
+    /// Codec of another crate, see [`Compression::custom`]
+    Custom(include_assets_decode::codec::Custom),
+}
+
+impl Default for Compression {
+    /// The same compression as the default of `include_dir!`, i.e. the first enabled of zstd (level 5), lz4, deflate (level 2), and no compression
+    fn default() -> Self {
+        let available = [
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { level: 5 },
+            #[cfg(feature = "lz4")]
+            Compression::Lz4,
+            #[cfg(feature = "deflate")]
+            Compression::Deflate { level: 2 },
+            Compression::Uncompressed,
+        ];
+        available[0]
+    }
+}
+
+impl Compression {
+    /// Compress with a codec of another crate, which the generated code constructs with [`CustomCodec::constructor`](include_assets_decode::codec::CustomCodec::constructor).
+    ///
+    /// The crate including the archive must depend on the crate of the codec.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tag or level of the codec is invalid, see [`Custom::new`](include_assets_decode::codec::Custom::new).
+    pub fn custom<C: include_assets_decode::codec::CustomCodec>(codec: &C) -> Self {
+        Compression::Custom(include_assets_decode::codec::Custom::new(codec))
+    }
+
+    fn codec(self) -> include_assets_decode::codec::AnyCodec {
+        use include_assets_decode::codec::{self, AnyCodec};
+        match self {
+            Compression::Uncompressed => AnyCodec::Uncompressed(codec::Uncompressed {}),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => AnyCodec::Lz4(codec::Lz4 {}),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { level } => AnyCodec::Zstd(codec::Zstd { level }),
+            #[cfg(feature = "deflate")]
+            Compression::Deflate { level } => AnyCodec::Deflate(codec::Deflate { level }),
+            Compression::Custom(custom) => AnyCodec::Custom(custom),
+        }
+    }
+
+    /// Expression constructing the codec in the generated code
+    fn codec_tokens(self) -> anyhow::Result<proc_macro2::TokenStream> {
+        Ok(match self {
+            Compression::Uncompressed => quote::quote! { ::include_assets::do_not_use_this_directly::codec::Uncompressed{} },
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => quote::quote! { ::include_assets::do_not_use_this_directly::codec::Lz4{} },
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { level } => quote::quote! { ::include_assets::do_not_use_this_directly::codec::Zstd{ level: #level } },
+            #[cfg(feature = "deflate")]
+            Compression::Deflate { level } => quote::quote! { ::include_assets::do_not_use_this_directly::codec::Deflate{ level: #level } },
+            Compression::Custom(custom) => {
+                let constructor = custom.constructor();
+                constructor
+                    .parse()
+                    .map_err(|err| anyhow::Error::msg(format!("invalid constructor '{constructor}' of custom codec: {err}")))?
+            }
+        })
+    }
+}
+
+/// An asset directory to compress into an archive
+///
+/// The options correspond to those of `include_dir!`.
+#[derive(Debug, Clone)]
+pub struct Archive {
+    directory: std::path::PathBuf,
+    compression: Compression,
+    links: SymlinkRules,
+    allow_outside_base: bool,
+    shard_size: Option<u32>,
+    checksum_size: u32,
+    single_stream: bool,
+}
+
+impl Archive {
+    /// Compress all files in `directory`, which is relative to the directory of the build script's package.
+    pub fn new<P: Into<std::path::PathBuf>>(directory: P) -> Self {
+        Self {
+            directory: directory.into(),
+            compression: Compression::default(),
+            links: SymlinkRules::default(),
+            allow_outside_base: false,
+            shard_size: None,
+            checksum_size: 64,
+            single_stream: false,
+        }
+    }
+
+    /// Set the compression codec and level.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Set how symbolic links in the directory are handled (by default, they are an error).
+    pub fn links(mut self, links: SymlinkRules) -> Self {
+        self.links = links;
+        self
+    }
+
+    /// Allow followed symbolic links to point outside the directory, see the `allow_outside_base` option of `include_dir!`.
+    pub fn allow_outside_base(mut self, allow_outside_base: bool) -> Self {
+        self.allow_outside_base = allow_outside_base;
+        self
+    }
+
+    /// Compress the data in independent shards of `shard_size` uncompressed bytes, see the `shard_size` option of `include_dir!`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_size` is zero.
+    pub fn shard_size(mut self, shard_size: u32) -> Self {
+        assert!(shard_size > 0, "shard size must be positive");
+        self.shard_size = Some(shard_size);
+        self
+    }
+
+    /// Store checksums truncated to `checksum_size` bytes, see the `checksum_size` option of `include_dir!`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `checksum_size` is not between 8 and 64.
+    pub fn checksum_size(mut self, checksum_size: u32) -> Self {
+        assert!((8..=64).contains(&checksum_size), "checksum size must be between 8 and 64 bytes");
+        self.checksum_size = checksum_size;
+        self
+    }
+
+    /// Compress names, sizes, and checksums together with the asset data, see the `single_stream` option of `include_dir!`.
+    pub fn single_stream(mut self, single_stream: bool) -> Self {
+        self.single_stream = single_stream;
+        self
+    }
+
+    /// Compress the assets into `OUT_DIR` for inclusion with `include_archive!(name)`, and rerun the build script if the directory changes.
+    ///
+    /// Call this from a build script only, `OUT_DIR` is set by cargo.
+    pub fn write(&self, name: &str) -> anyhow::Result<()> {
+        let out_dir = std::env::var_os("OUT_DIR").context("OUT_DIR is not set (write must be called from a build script)")?;
+        println!("cargo:rerun-if-changed={}", self.directory.display());
+        self.write_to(out_dir, name)
+    }
+
+    /// Compress the assets into `out_dir`, writing the files `{name}.assets` (compressed data) and `{name}.rs` (code to include).
+    ///
+    /// The generated code is an expression of type `CompressedNamedArchive`, which includes the compressed data from the absolute path of `{name}.assets`.
+    /// With shards, compressed shards are kept in the directory `include_assets` in `out_dir`, and reused if their data is unchanged.
+    pub fn write_to<P: AsRef<std::path::Path>>(&self, out_dir: P, name: &str) -> anyhow::Result<()> {
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return Err(anyhow::Error::msg(format!("invalid archive name '{name}' (expected a non-empty file name)")));
+        }
+        let assets = archive::read_dir(&self.directory, self.links, self.allow_outside_base)
+            .with_context(|| format!("couldn't read directory {}", self.directory.display()))?;
+        let perfect_hash_token = archive::perfect_hash_tokens(assets.iter().map(|(name, _)| name.as_str()));
+        let archive = archive::prepare_named_archive(
+            &self.compression.codec(),
+            assets,
+            self.shard_size,
+            Some(&out_dir.as_ref().join("include_assets")),
+            1,
+            self.checksum_size,
+            self.single_stream,
+        )?;
+        let archive::NamedArchive {
+            compressed_data,
+            uncompressed_data_size,
+            compressed_names,
+            uncompressed_names_size,
+            compressed_sizes,
+            uncompressed_sizes_size,
+            checksums,
+            checksum_size,
+            align,
+            metadata_size,
+            shards,
+        } = archive;
+
+        std::fs::create_dir_all(out_dir.as_ref()).with_context(|| format!("couldn't create directory {}", out_dir.as_ref().display()))?;
+        let data_path = std::path::absolute(out_dir.as_ref().join(format!("{name}.assets"))).context("couldn't determine the absolute output path")?;
+        std::fs::write(&data_path, &compressed_data).with_context(|| format!("couldn't write {}", data_path.display()))?;
+        let data_path = data_path.to_str().context("output path is not UTF-8")?;
+
+        let codec_token = self.compression.codec_tokens()?;
+        let names_token = proc_macro2::Literal::byte_string(&compressed_names);
+        let checksums_token = proc_macro2::Literal::byte_string(&checksums);
+        let sizes_token = proc_macro2::Literal::byte_string(&compressed_sizes);
+        let shards_token = proc_macro2::Literal::byte_string(&shards);
+        let code = quote::quote! {
+            ::include_assets::CompressedNamedArchive {
+                codec: #codec_token,
+                data: ::core::include_bytes!(#data_path),
+                uncompressed_data_size: #uncompressed_data_size,
+                compressed_names: #names_token,
+                uncompressed_names_size: #uncompressed_names_size,
+                checksums: #checksums_token,
+                checksum_size: #checksum_size,
+                compressed_sizes: #sizes_token,
+                uncompressed_sizes_size: #uncompressed_sizes_size,
+                align: #align,
+                metadata_size: #metadata_size,
+                signature: ::core::option::Option::None,
+                directory: ::core::option::Option::None,
+                mime_types: &[],
+                private: &[],
+                perfect_hash: ::core::option::Option::Some(#perfect_hash_token),
+                shards: #shards_token
+            }
+        };
+        let code_path = out_dir.as_ref().join(format!("{name}.rs"));
+        std::fs::write(&code_path, code.to_string()).with_context(|| format!("couldn't write {}", code_path.display()))?;
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub mod do_not_use_this_directly {
+    pub use crate::archive::{checksums_tokens, perfect_hash_tokens, prepare_named_archive, read_dir, NamedArchive, SymlinkRules};
+}
@@ -0,0 +1,44 @@
+use crate::serve::ServeArchive;
+
+/// Create an actix-web scope serving assets under `mount_path`, from a `&'static NamedArchive` or a configured [`ServeArchive`].
+///
+/// For example, with `mount_path = "/static"`, a request for `/static/css/style.css` is answered with the asset `css/style.css`.
+pub fn scope<S: Into<ServeArchive<'static>>>(mount_path: &str, serve: S) -> actix_web::Scope {
+    let serve = serve.into();
+    let prefix = mount_path.trim_end_matches('/').to_owned();
+    actix_web::web::scope(mount_path).default_service(actix_web::web::to(move |request: actix_web::HttpRequest| {
+        // strip the mount path from the raw path, which is decoded when looking up the asset
+        let path = request.path().strip_prefix(prefix.as_str()).unwrap_or_default();
+        // actix-web omits the body of responses to HEAD requests itself, keeping the content length
+        let method = match request.method().as_str() {
+            "HEAD" => http::Method::GET,
+            method => http::Method::from_bytes(method.as_bytes()).unwrap_or_default(),
+        };
+        let headers = request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    http::HeaderName::from_bytes(name.as_str().as_bytes()).ok()?,
+                    http::HeaderValue::from_bytes(value.as_bytes()).ok()?,
+                ))
+            })
+            .collect();
+        let response = serve.respond_to(&method, path, &headers);
+        async move { into_actix_response(response) }
+    }))
+}
+
+/// Convert a response to the types of actix-web, which uses a different version of the `http` crate.
+fn into_actix_response(response: http::Response<bytes::Bytes>) -> actix_web::HttpResponse {
+    let (parts, body) = response.into_parts();
+    let status = actix_web::http::StatusCode::from_u16(parts.status.as_u16()).unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+    let mut builder = actix_web::HttpResponse::build(status);
+    for (name, value) in parts.headers.iter() {
+        // actix-web sets the content length itself
+        if name != http::header::CONTENT_LENGTH {
+            builder.insert_header((name.as_str(), value.as_bytes()));
+        }
+    }
+    builder.body(body)
+}
@@ -0,0 +1,115 @@
+// Comparison of codecs on real assets, to choose the compression options.
+// User-facing documentation is in the include_assets crate.
+
+use crate::codec::Codec;
+pub use crate::codec::{AnyCodec, AnyCodecError, Uncompressed};
+
+#[cfg(feature = "deflate")]
+pub use crate::codec::Deflate;
+#[cfg(feature = "lz4")]
+pub use crate::codec::Lz4;
+#[cfg(feature = "zstd")]
+pub use crate::codec::Zstd;
+
+/// Results of compressing and decompressing the assets with one codec
+#[derive(Debug, Clone, Copy)]
+pub struct CodecReport {
+    pub codec: AnyCodec,
+    /// Total size of the assets in bytes
+    pub uncompressed_size: usize,
+    /// Size of the compressed assets in bytes
+    pub compressed_size: usize,
+    pub compression_time: std::time::Duration,
+    pub decompression_time: std::time::Duration,
+}
+
+impl CodecReport {
+    /// Compression ratio, i.e. uncompressed size divided by compressed size (higher is better)
+    pub fn ratio(&self) -> f64 {
+        self.uncompressed_size as f64 / self.compressed_size.max(1) as f64
+    }
+
+    /// Macro options selecting the codec, e.g. `compression = "zstd", level = 5`, or the constructor of a custom codec (see [`crate::codec::CustomCodec`])
+    pub fn options(&self) -> std::string::String {
+        match self.codec {
+            AnyCodec::Uncompressed(_) => r#"compression = "uncompressed""#.into(),
+            #[cfg(feature = "lz4")]
+            AnyCodec::Lz4(_) => r#"compression = "lz4""#.into(),
+            #[cfg(feature = "zstd")]
+            AnyCodec::Zstd(Zstd { level }) => format!(r#"compression = "zstd", level = {level}"#),
+            #[cfg(feature = "deflate")]
+            AnyCodec::Deflate(Deflate { level }) => format!(r#"compression = "deflate", level = {level}"#),
+            AnyCodec::Custom(custom) => custom.constructor(),
+        }
+    }
+}
+
+impl core::fmt::Display for CodecReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {} bytes (ratio {:.2}), compressed in {:.1?}, decompressed in {:.1?}",
+            self.options(),
+            self.uncompressed_size,
+            self.compressed_size,
+            self.ratio(),
+            self.compression_time,
+            self.decompression_time
+        )
+    }
+}
+
+/// Codecs and levels worth comparing, among the codecs enabled via crate features
+pub fn default_codecs() -> std::vec::Vec<AnyCodec> {
+    #[allow(unused_mut)]
+    let mut codecs = vec![AnyCodec::Uncompressed(Uncompressed {})];
+    #[cfg(feature = "lz4")]
+    codecs.push(AnyCodec::Lz4(Lz4 {}));
+    #[cfg(feature = "deflate")]
+    codecs.extend([2, 6, 10].map(|level| AnyCodec::Deflate(Deflate { level })));
+    #[cfg(feature = "zstd")]
+    codecs.extend([1, 5, 12, 19, 22].map(|level| AnyCodec::Zstd(Zstd { level })));
+    codecs
+}
+
+/// Compress and decompress `assets` with each codec, measuring sizes and times.
+///
+/// The assets are concatenated and compressed as a whole, like the macros do.
+/// Reports are in the order of `codecs`.
+///
+/// # Errors
+///
+/// Returns an error if a codec fails, e.g. because of an invalid level.
+pub fn compare<A, I, C>(assets: I, codecs: C) -> Result<std::vec::Vec<CodecReport>, AnyCodecError>
+where
+    A: AsRef<[u8]>,
+    I: IntoIterator<Item = A>,
+    C: IntoIterator,
+    C::Item: Into<AnyCodec>,
+{
+    let mut data = vec![];
+    for asset in assets {
+        data.extend_from_slice(asset.as_ref());
+    }
+    let mut decompressed = vec![0; data.len()];
+    codecs
+        .into_iter()
+        .map(|codec| {
+            let codec = codec.into();
+            let start = std::time::Instant::now();
+            let compressed = codec.compress(&data)?;
+            let compression_time = start.elapsed();
+            let start = std::time::Instant::now();
+            codec.decompress_checked(&compressed, &mut decompressed)?;
+            let decompression_time = start.elapsed();
+            debug_assert!(decompressed == data);
+            Ok(CodecReport {
+                codec,
+                uncompressed_size: data.len(),
+                compressed_size: compressed.len(),
+                compression_time,
+                decompression_time,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,70 @@
+use crate::named::{DirEntry, NamedArchive};
+
+use bevy_asset::io::{AssetReader, AssetReaderError, AssetSourceBuilder, PathStream, Reader, SliceReader};
+
+// Bevy asset reader looking up assets in a NamedArchive.
+// User-facing documentation is in the include_assets crate.
+#[derive(Clone, Copy)]
+pub struct ArchiveAssetReader {
+    archive: &'static NamedArchive,
+}
+
+impl ArchiveAssetReader {
+    /// Create an asset reader for the archive.
+    pub fn new(archive: &'static NamedArchive) -> Self {
+        Self { archive }
+    }
+
+    /// Create an asset source for the archive, to be registered with `App::register_asset_source` before adding the `AssetPlugin`.
+    pub fn source(archive: &'static NamedArchive) -> AssetSourceBuilder {
+        AssetSourceBuilder::new(move || std::boxed::Box::new(Self::new(archive)))
+    }
+
+    fn get(&self, path: &std::path::Path) -> Result<&'static [u8], AssetReaderError> {
+        self.archive.get_path(path).ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))
+    }
+
+    /// Entries of the directory, or `None` if there is no such directory.
+    fn list_dir(&self, path: &std::path::Path) -> Option<std::vec::Vec<DirEntry<'static>>> {
+        let name = crate::named::normalize_path(path)?;
+        let entries = self.archive.list_dir(&name);
+        // the root directory exists even if the archive is empty
+        (!entries.is_empty() || name.is_empty()).then_some(entries)
+    }
+}
+
+impl From<&'static NamedArchive> for ArchiveAssetReader {
+    fn from(archive: &'static NamedArchive) -> Self {
+        Self::new(archive)
+    }
+}
+
+impl AssetReader for ArchiveAssetReader {
+    async fn read<'a>(&'a self, path: &'a std::path::Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.get(path).map(SliceReader::new)
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a std::path::Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        // Bevy stores the meta file of `image.png` as `image.png.meta`
+        let mut meta_path = path.as_os_str().to_owned();
+        meta_path.push(".meta");
+        self.get(std::path::Path::new(&meta_path)).map(SliceReader::new)
+    }
+
+    async fn read_directory<'a>(&'a self, path: &'a std::path::Path) -> Result<std::boxed::Box<PathStream>, AssetReaderError> {
+        let entries = self.list_dir(path).ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))?;
+        let paths: std::vec::Vec<std::path::PathBuf> = entries
+            .into_iter()
+            .filter_map(|entry| match entry {
+                // like Bevy's file reader, meta files are not listed
+                DirEntry::File { name, .. } if name.ends_with(".meta") => None,
+                DirEntry::File { name, .. } | DirEntry::Dir { name } => Some(name.into()),
+            })
+            .collect();
+        Ok(std::boxed::Box::new(futures_lite::stream::iter(paths)))
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a std::path::Path) -> Result<bool, AssetReaderError> {
+        Ok(self.list_dir(path).is_some())
+    }
+}
@@ -3,12 +3,14 @@ use blake2::Digest as _;
 pub type Checksum = [u8; 64];
 
 pub fn compute_checksum(data: &[u8]) -> Checksum {
-    blake2::Blake2b512::digest(data).try_into().expect("blake2b output is 64 byte long")
+    blake2::Blake2b512::digest(data).into()
 }
 
 pub struct Mismatch {
     expected: Checksum,
     actual: Checksum,
+    /// Number of bytes of the checksums which are compared, less than the full checksum for truncated checksums
+    len: usize,
 }
 
 impl core::fmt::Display for Mismatch {
@@ -16,8 +18,8 @@ impl core::fmt::Display for Mismatch {
         write!(
             f,
             "Checksum mismatch: expected {}, got {}",
-            hexhex::Hex::new(self.expected),
-            hexhex::Hex::new(self.actual)
+            hexhex::Hex::new(&self.expected[..self.len]),
+            hexhex::Hex::new(&self.actual[..self.len])
         )
     }
 }
@@ -28,14 +30,42 @@ impl core::fmt::Debug for Mismatch {
     }
 }
 
-impl std::error::Error for Mismatch {}
+impl core::error::Error for Mismatch {}
 
+/// Check `data` against the `expected` checksum, which may be truncated.
 #[allow(clippy::result_large_err)]
-pub fn check(data: &[u8], expected: &Checksum) -> Result<(), Mismatch> {
-    let actual = compute_checksum(data);
-    if &actual != expected {
-        Err(Mismatch { expected: *expected, actual })
+pub fn check(data: &[u8], expected: &[u8]) -> Result<(), Mismatch> {
+    compare(compute_checksum(data), expected).map(|_| ())
+}
+
+/// Compare a computed checksum to the `expected` one, which may be truncated, returning the full computed checksum if they match.
+#[allow(clippy::result_large_err)]
+pub(crate) fn compare(actual: Checksum, expected: &[u8]) -> Result<Checksum, Mismatch> {
+    if actual.get(..expected.len()) != Some(expected) {
+        let len = expected.len().min(actual.len());
+        let mut padded = [0; 64];
+        padded[..len].copy_from_slice(&expected[..len]);
+        Err(Mismatch { expected: padded, actual, len })
     } else {
-        Ok(())
+        Ok(actual)
+    }
+}
+
+/// Incremental checksum computation for data which is not available all at once
+#[derive(Default, Clone)]
+pub struct Hasher(blake2::Blake2b512);
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    pub fn finish(self) -> Checksum {
+        self.0.finalize().into()
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn check(self, expected: &[u8]) -> Result<(), Mismatch> {
+        compare(self.0.finalize().into(), expected).map(|_| ())
     }
 }
@@ -1,12 +1,30 @@
+// Compression codecs, including the extension point for codecs of other crates.
+// User-facing documentation is in the include_assets crate.
+
 /// Compression codec for the `include_assets` crate
+///
+/// Codecs of other crates implement this trait and [`CustomCodec`].
+/// Methods added in the future will have default implementations, so that such implementations keep compiling.
 pub trait Codec {
     /// Errors that might occur during compression
-    type CompressionError: std::error::Error + Send + Sync + 'static; // Send + Sync + 'static is for use with the anyhow crate.
+    type CompressionError: core::error::Error + Send + Sync + 'static; // Send + Sync + 'static is for use with the anyhow crate.
     /// Errors that might occur during decompression
-    type DecompressionError: std::error::Error + Send + Sync + 'static;
+    type DecompressionError: core::error::Error + Send + Sync + 'static;
 
     /// Compress data to a newly allocated vector.
-    fn compress(&self, data: &[u8]) -> Result<std::vec::Vec<u8>, Self::CompressionError>;
+    fn compress(&self, data: &[u8]) -> Result<alloc::vec::Vec<u8>, Self::CompressionError>;
+
+    /// Compress data given in consecutive parts to a newly allocated vector, like [`Codec::compress`] on the concatenated parts.
+    ///
+    /// The default implementation concatenates all parts first.
+    /// Codecs with a streaming compressor override it, so that the uncompressed data never needs to be in memory at once.
+    fn compress_parts(&self, parts: &mut dyn Iterator<Item = alloc::vec::Vec<u8>>) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
+        let mut data = vec![];
+        for part in parts {
+            data.extend_from_slice(&part);
+        }
+        self.compress(&data)
+    }
 
     /// Decompress data in `src` to `dst`.
     ///
@@ -20,11 +38,25 @@ pub trait Codec {
         self.decompress_checked(src, dst).expect("decompression should succeed")
     }
 
+    /// Decompress data in `src`, appending it to `dst`.
+    ///
+    /// Fails if the length of the uncompressed data is not exactly `len`.
+    ///
+    /// The default implementation zero-fills `len` bytes and decompresses into them with [`Codec::decompress_checked`].
+    /// Codecs which can write into the spare capacity of `dst` override it, which saves filling potentially gigabytes with zeros first.
+    ///
+    /// If decompression fails for any reason, the contents of `dst` are unspecified.
+    fn decompress_append(&self, src: &[u8], len: usize, dst: &mut alloc::vec::Vec<u8>) -> Result<(), Self::DecompressionError> {
+        let start = dst.len();
+        dst.resize(start + len, 0);
+        self.decompress_checked(src, &mut dst[start..])
+    }
+
     /// Decompresses data into a new vector with the given length.
     /// Panics on error.
-    fn decompress_with_length(&self, src: &[u8], len: usize) -> std::vec::Vec<u8> {
-        let mut dst = vec![0u8; len];
-        self.decompress(src, &mut dst);
+    fn decompress_with_length(&self, src: &[u8], len: usize) -> alloc::vec::Vec<u8> {
+        let mut dst = alloc::vec::Vec::with_capacity(len);
+        self.decompress_append(src, len, &mut dst).expect("decompression should succeed");
         dst
     }
 }
@@ -45,13 +77,13 @@ impl core::fmt::Display for UncompressedSizeMismatch {
     }
 }
 
-impl std::error::Error for UncompressedSizeMismatch {}
+impl core::error::Error for UncompressedSizeMismatch {}
 
 impl Codec for Uncompressed {
-    type CompressionError = std::convert::Infallible;
+    type CompressionError = core::convert::Infallible;
     type DecompressionError = UncompressedSizeMismatch;
 
-    fn compress(&self, data: &[u8]) -> Result<std::vec::Vec<u8>, Self::CompressionError> {
+    fn compress(&self, data: &[u8]) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
         Ok(data.to_vec())
     }
 
@@ -69,6 +101,18 @@ impl Codec for Uncompressed {
             })
         }
     }
+
+    fn decompress_append(&self, src: &[u8], len: usize, dst: &mut alloc::vec::Vec<u8>) -> Result<(), Self::DecompressionError> {
+        if len == src.len() {
+            dst.extend_from_slice(src);
+            Ok(())
+        } else {
+            Err(UncompressedSizeMismatch {
+                expected: len,
+                actual: src.len(),
+            })
+        }
+    }
 }
 
 #[cfg(feature = "lz4")]
@@ -78,26 +122,41 @@ pub struct Lz4 {}
 
 #[cfg(feature = "lz4")]
 impl Codec for Lz4 {
-    type CompressionError = std::convert::Infallible;
-    type DecompressionError = lz4_flex::block::DecompressError;
+    type CompressionError = core::convert::Infallible;
+    type DecompressionError = Lz4Error;
 
-    fn compress(&self, data: &[u8]) -> Result<std::vec::Vec<u8>, Self::CompressionError> {
+    fn compress(&self, data: &[u8]) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
         Ok(lz4_flex::block::compress(data))
     }
 
     fn decompress_checked(&self, src: &[u8], dst: &mut [u8]) -> Result<(), Self::DecompressionError> {
-        let uncompressed_size = lz4_flex::block::decompress_into(src, dst)?;
+        let uncompressed_size = lz4_flex::block::decompress_into(src, dst).map_err(Lz4Error)?;
         if uncompressed_size != dst.len() {
-            Err(lz4_flex::block::DecompressError::UncompressedSizeDiffers {
+            Err(Lz4Error(lz4_flex::block::DecompressError::UncompressedSizeDiffers {
                 expected: dst.len(),
                 actual: uncompressed_size,
-            })
+            }))
         } else {
             Ok(())
         }
     }
 }
 
+#[cfg(feature = "lz4")]
+/// lz4_flex's DecompressError only implements std::error::Error with std, so we wrap it and implement core::error::Error ourselves
+#[derive(Debug)]
+pub struct Lz4Error(lz4_flex::block::DecompressError);
+
+#[cfg(feature = "lz4")]
+impl core::fmt::Display for Lz4Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(feature = "lz4")]
+impl core::error::Error for Lz4Error {}
+
 #[cfg(feature = "zstd")]
 /// zstd compression
 #[derive(Debug, Clone, Copy)]
@@ -114,20 +173,46 @@ impl Codec for Zstd {
     type CompressionError = std::io::Error;
     type DecompressionError = std::io::Error;
 
-    fn compress(&self, data: &[u8]) -> Result<std::vec::Vec<u8>, Self::CompressionError> {
+    fn compress(&self, data: &[u8]) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
         zstd::bulk::compress(data, self.level)
     }
 
+    fn compress_parts(&self, parts: &mut dyn Iterator<Item = alloc::vec::Vec<u8>>) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
+        use std::io::Write as _;
+
+        let mut encoder = zstd::stream::write::Encoder::new(vec![], self.level)?;
+        for part in parts {
+            encoder.write_all(&part)?;
+        }
+        encoder.finish()
+    }
+
     fn decompress_checked(&self, src: &[u8], dst: &mut [u8]) -> Result<(), Self::DecompressionError> {
         let uncompressed_size = zstd::bulk::decompress_to_buffer(src, dst)?;
         if uncompressed_size != dst.len() {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                UncompressedSizeMismatch {
-                    expected: dst.len(),
-                    actual: uncompressed_size,
-                },
-            ))
+            Err(std::io::Error::other(UncompressedSizeMismatch {
+                expected: dst.len(),
+                actual: uncompressed_size,
+            }))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn decompress_append(&self, src: &[u8], len: usize, dst: &mut alloc::vec::Vec<u8>) -> Result<(), Self::DecompressionError> {
+        // zstd writes into the spare capacity after the cursor position, which is limited to `len` bytes
+        dst.reserve_exact(len);
+        let start = dst.len();
+        let mut cursor = std::io::Cursor::new(core::mem::take(dst));
+        cursor.set_position(start as u64);
+        let result = zstd::bulk::Decompressor::new().and_then(|mut decompressor| decompressor.decompress_to_buffer(src, &mut cursor));
+        *dst = cursor.into_inner();
+        let uncompressed_size = result?;
+        if uncompressed_size != len {
+            Err(std::io::Error::other(UncompressedSizeMismatch {
+                expected: len,
+                actual: uncompressed_size,
+            }))
         } else {
             Ok(())
         }
@@ -146,7 +231,7 @@ pub struct Deflate {
 }
 
 #[cfg(feature = "deflate")]
-/// yazi::Error doesn't implement std::error::Error, so we wrap it and implement it ourselves
+/// yazi::Error doesn't implement core::error::Error, so we wrap it and implement it ourselves
 pub struct YaziError(yazi::Error);
 
 #[cfg(feature = "deflate")]
@@ -170,17 +255,31 @@ impl core::fmt::Display for YaziError {
 }
 
 #[cfg(feature = "deflate")]
-impl std::error::Error for YaziError {}
+impl core::error::Error for YaziError {}
 
 #[cfg(feature = "deflate")]
 impl Codec for Deflate {
     type CompressionError = YaziError;
     type DecompressionError = YaziError;
 
-    fn compress(&self, data: &[u8]) -> Result<std::vec::Vec<u8>, Self::CompressionError> {
+    fn compress(&self, data: &[u8]) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
         yazi::compress(data, yazi::Format::Raw, yazi::CompressionLevel::Specific(self.level)).map_err(YaziError)
     }
 
+    fn compress_parts(&self, parts: &mut dyn Iterator<Item = alloc::vec::Vec<u8>>) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
+        // same as yazi::compress, but writing the parts one by one
+        let mut encoder = yazi::Encoder::boxed();
+        encoder.set_format(yazi::Format::Raw);
+        encoder.set_level(yazi::CompressionLevel::Specific(self.level));
+        let mut compressed = vec![];
+        let mut stream = encoder.stream_into_vec(&mut compressed);
+        for part in parts {
+            stream.write(&part).map_err(YaziError)?;
+        }
+        stream.finish().map_err(YaziError)?;
+        Ok(compressed)
+    }
+
     fn decompress_checked(&self, src: &[u8], dst: &mut [u8]) -> Result<(), Self::DecompressionError> {
         let mut decoder = yazi::Decoder::new();
         let mut stream = decoder.stream_into_buf(dst);
@@ -205,4 +304,373 @@ impl Codec for Deflate {
             Ok(())
         }
     }
+
+    fn decompress_append(&self, src: &[u8], len: usize, dst: &mut alloc::vec::Vec<u8>) -> Result<(), Self::DecompressionError> {
+        dst.reserve_exact(len);
+        let mut decoder = yazi::Decoder::new();
+        let mut stream = decoder.stream_into_vec(dst);
+        stream.write(src).map_err(YaziError)?;
+        let (uncompressed_size, checksum) = stream.finish().map_err(YaziError)?;
+        assert!(checksum.is_none(), "checksum should not be present for raw stream");
+        match usize::try_from(uncompressed_size) {
+            Ok(size) if size == len => Ok(()),
+            Ok(size) if size > len => Err(YaziError(yazi::Error::Overflow)),
+            _ => Err(YaziError(yazi::Error::Underflow)),
+        }
+    }
+}
+
+/// Codec tags from this value on are reserved for codecs of other crates, see [`CustomCodec::TAG`]
+pub const FIRST_CUSTOM_TAG: u32 = 0x8000_0000;
+
+/// Codec implemented in another crate
+///
+/// Any [`Codec`] can be used to load archives, since loading is generic over the codec.
+/// Implementing this trait in addition lets the codec be stored in archive files (see [`register`]) and used with `include_assets_build`.
+pub trait CustomCodec: Codec + Sized + 'static {
+    /// Tag identifying the codec in archive files, at least [`FIRST_CUSTOM_TAG`] and different from the tags of all other codecs
+    const TAG: u32;
+
+    /// Compression level as stored in archive files
+    fn level(&self) -> i32;
+
+    /// Reconstruct the codec from its level, or return `None` if the level is invalid.
+    fn from_level(level: i32) -> Option<Self>;
+
+    /// Rust expression constructing the codec in generated code, e.g. `::my_codec::Brotli { quality: 9 }`
+    fn constructor(&self) -> alloc::string::String;
+}
+
+/// Object-safe version of [`CustomCodec`], taking the level instead of the codec itself
+trait ErasedCodec: core::fmt::Debug + Send + Sync {
+    /// Used to tell registered codecs apart, see [`register`]
+    #[cfg(feature = "std")]
+    fn codec_type(&self) -> core::any::TypeId;
+    fn tag(&self) -> u32;
+    #[cfg(feature = "std")]
+    fn accepts_level(&self, level: i32) -> bool;
+    fn constructor(&self, level: i32) -> alloc::string::String;
+    fn compress(&self, level: i32, data: &[u8]) -> Result<alloc::vec::Vec<u8>, AnyCodecError>;
+    fn compress_parts(&self, level: i32, parts: &mut dyn Iterator<Item = alloc::vec::Vec<u8>>) -> Result<alloc::vec::Vec<u8>, AnyCodecError>;
+    fn decompress_checked(&self, level: i32, src: &[u8], dst: &mut [u8]) -> Result<(), AnyCodecError>;
+    fn decompress_append(&self, level: i32, src: &[u8], len: usize, dst: &mut alloc::vec::Vec<u8>) -> Result<(), AnyCodecError>;
+}
+
+/// Implements [`ErasedCodec`] for the custom codec `C`, without storing it
+struct Erased<C>(core::marker::PhantomData<fn() -> C>);
+
+impl<C: CustomCodec> Erased<C> {
+    /// Reference to this zero-sized type, which is `'static` since it is a constant
+    const ERASED: &'static dyn ErasedCodec = &Erased::<C>(core::marker::PhantomData);
+
+    /// Reconstruct the codec from a level which was checked when creating [`Custom`]
+    fn codec(level: i32) -> C {
+        C::from_level(level).expect("level of custom codec was checked")
+    }
+}
+
+impl<C> core::fmt::Debug for Erased<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(core::any::type_name::<C>())
+    }
+}
+
+impl<C: CustomCodec> ErasedCodec for Erased<C> {
+    #[cfg(feature = "std")]
+    fn codec_type(&self) -> core::any::TypeId {
+        core::any::TypeId::of::<C>()
+    }
+
+    fn tag(&self) -> u32 {
+        C::TAG
+    }
+
+    #[cfg(feature = "std")]
+    fn accepts_level(&self, level: i32) -> bool {
+        C::from_level(level).is_some()
+    }
+
+    fn constructor(&self, level: i32) -> alloc::string::String {
+        Self::codec(level).constructor()
+    }
+
+    fn compress(&self, level: i32, data: &[u8]) -> Result<alloc::vec::Vec<u8>, AnyCodecError> {
+        Self::codec(level).compress(data).map_err(AnyCodecError::new)
+    }
+
+    fn compress_parts(&self, level: i32, parts: &mut dyn Iterator<Item = alloc::vec::Vec<u8>>) -> Result<alloc::vec::Vec<u8>, AnyCodecError> {
+        Self::codec(level).compress_parts(parts).map_err(AnyCodecError::new)
+    }
+
+    fn decompress_checked(&self, level: i32, src: &[u8], dst: &mut [u8]) -> Result<(), AnyCodecError> {
+        Self::codec(level).decompress_checked(src, dst).map_err(AnyCodecError::new)
+    }
+
+    fn decompress_append(&self, level: i32, src: &[u8], len: usize, dst: &mut alloc::vec::Vec<u8>) -> Result<(), AnyCodecError> {
+        Self::codec(level).decompress_append(src, len, dst).map_err(AnyCodecError::new)
+    }
+}
+
+/// A [`CustomCodec`] with its type erased, so that it fits in [`AnyCodec`]
+#[derive(Debug, Clone, Copy)]
+pub struct Custom {
+    level: i32,
+    codec: &'static dyn ErasedCodec,
+}
+
+impl Custom {
+    /// Erase the type of `codec`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`CustomCodec::TAG`] is less than [`FIRST_CUSTOM_TAG`], or if [`CustomCodec::from_level`] does not accept the level of `codec`.
+    pub fn new<C: CustomCodec>(codec: &C) -> Self {
+        assert!(C::TAG >= FIRST_CUSTOM_TAG, "tags of custom codecs must be at least {FIRST_CUSTOM_TAG}");
+        let level = codec.level();
+        assert!(C::from_level(level).is_some(), "custom codec doesn't accept its own level {level}");
+        Custom {
+            level,
+            codec: Erased::<C>::ERASED,
+        }
+    }
+
+    /// Rust expression constructing the codec, see [`CustomCodec::constructor`]
+    pub fn constructor(&self) -> alloc::string::String {
+        self.codec.constructor(self.level)
+    }
+}
+
+impl PartialEq for Custom {
+    fn eq(&self, other: &Self) -> bool {
+        self.codec.tag() == other.codec.tag() && self.level == other.level
+    }
+}
+
+impl Eq for Custom {}
+
+/// Custom codecs which can be read from archive files, see [`register`]
+#[cfg(feature = "std")]
+static REGISTRY: std::sync::RwLock<alloc::vec::Vec<&'static dyn ErasedCodec>> = std::sync::RwLock::new(alloc::vec::Vec::new());
+
+/// Register a custom codec, so that archive files using it can be read, e.g. with [`NamedArchive::load_file`](crate::named::NamedArchive::load_file).
+///
+/// Archives included in the executable do not need this, since their codec is known at compile time.
+/// Registering a codec again has no effect.
+///
+/// # Panics
+///
+/// Panics if [`CustomCodec::TAG`] is less than [`FIRST_CUSTOM_TAG`], or if another codec with the same tag was registered.
+#[cfg(feature = "std")]
+pub fn register<C: CustomCodec>() {
+    assert!(C::TAG >= FIRST_CUSTOM_TAG, "tags of custom codecs must be at least {FIRST_CUSTOM_TAG}");
+    let mut registry = REGISTRY.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match registry.iter().find(|registered| registered.tag() == C::TAG) {
+        Some(registered) if registered.codec_type() == core::any::TypeId::of::<C>() => {}
+        Some(registered) => panic!("codec {registered:?} is registered with tag {} already", C::TAG),
+        None => registry.push(Erased::<C>::ERASED),
+    }
+}
+
+/// Look up a registered custom codec by its tag, and check its level.
+#[cfg(feature = "std")]
+fn registered(tag: u32, level: i32) -> Option<Custom> {
+    let registry = REGISTRY.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let codec = *registry.iter().find(|registered| registered.tag() == tag)?;
+    codec.accepts_level(level).then_some(Custom { level, codec })
+}
+
+impl Codec for Custom {
+    type CompressionError = AnyCodecError;
+    type DecompressionError = AnyCodecError;
+
+    fn compress(&self, data: &[u8]) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
+        self.codec.compress(self.level, data)
+    }
+
+    fn compress_parts(&self, parts: &mut dyn Iterator<Item = alloc::vec::Vec<u8>>) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
+        self.codec.compress_parts(self.level, parts)
+    }
+
+    fn decompress_checked(&self, src: &[u8], dst: &mut [u8]) -> Result<(), Self::DecompressionError> {
+        self.codec.decompress_checked(self.level, src, dst)
+    }
+
+    fn decompress_append(&self, src: &[u8], len: usize, dst: &mut alloc::vec::Vec<u8>) -> Result<(), Self::DecompressionError> {
+        self.codec.decompress_append(self.level, src, len, dst)
+    }
+}
+
+/// Compression codec which is only known at runtime, e.g. when reading an archive file
+#[derive(Debug, Clone, Copy)]
+pub enum AnyCodec {
+    Uncompressed(Uncompressed),
+    #[cfg(feature = "lz4")]
+    Lz4(Lz4),
+    #[cfg(feature = "zstd")]
+    Zstd(Zstd),
+    #[cfg(feature = "deflate")]
+    Deflate(Deflate),
+    /// Codec of another crate, see [`CustomCodec`]
+    Custom(Custom),
+}
+
+impl AnyCodec {
+    /// Tag identifying the codec in archive files
+    pub fn tag(&self) -> u32 {
+        match self {
+            AnyCodec::Uncompressed(_) => 0,
+            #[cfg(feature = "lz4")]
+            AnyCodec::Lz4(_) => 1,
+            #[cfg(feature = "zstd")]
+            AnyCodec::Zstd(_) => 2,
+            #[cfg(feature = "deflate")]
+            AnyCodec::Deflate(_) => 3,
+            AnyCodec::Custom(custom) => custom.codec.tag(),
+        }
+    }
+
+    /// Compression level as stored in archive files (0 for codecs without levels)
+    pub fn level(&self) -> i32 {
+        match self {
+            AnyCodec::Uncompressed(_) => 0,
+            #[cfg(feature = "lz4")]
+            AnyCodec::Lz4(_) => 0,
+            #[cfg(feature = "zstd")]
+            AnyCodec::Zstd(zstd) => zstd.level,
+            #[cfg(feature = "deflate")]
+            AnyCodec::Deflate(deflate) => deflate.level.into(),
+            AnyCodec::Custom(custom) => custom.level,
+        }
+    }
+
+    /// Reconstruct a codec from its tag and level.
+    ///
+    /// Returns `None` if the tag is unknown, the codec is not enabled via crate features or not registered (see [`register`]), or the level is out of range.
+    pub fn from_tag_and_level(tag: u32, level: i32) -> Option<Self> {
+        match tag {
+            0 if level == 0 => Some(AnyCodec::Uncompressed(Uncompressed {})),
+            #[cfg(feature = "lz4")]
+            1 if level == 0 => Some(AnyCodec::Lz4(Lz4 {})),
+            #[cfg(feature = "zstd")]
+            2 => Some(AnyCodec::Zstd(Zstd { level })),
+            #[cfg(feature = "deflate")]
+            3 => level.try_into().ok().map(|level| AnyCodec::Deflate(Deflate { level })),
+            #[cfg(feature = "std")]
+            FIRST_CUSTOM_TAG.. => registered(tag, level).map(AnyCodec::Custom),
+            _ => None,
+        }
+    }
+}
+
+impl From<Uncompressed> for AnyCodec {
+    fn from(codec: Uncompressed) -> Self {
+        AnyCodec::Uncompressed(codec)
+    }
+}
+
+#[cfg(feature = "lz4")]
+impl From<Lz4> for AnyCodec {
+    fn from(codec: Lz4) -> Self {
+        AnyCodec::Lz4(codec)
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl From<Zstd> for AnyCodec {
+    fn from(codec: Zstd) -> Self {
+        AnyCodec::Zstd(codec)
+    }
+}
+
+#[cfg(feature = "deflate")]
+impl From<Deflate> for AnyCodec {
+    fn from(codec: Deflate) -> Self {
+        AnyCodec::Deflate(codec)
+    }
+}
+
+impl<C: CustomCodec> From<C> for AnyCodec {
+    fn from(codec: C) -> Self {
+        AnyCodec::Custom(Custom::new(&codec))
+    }
+}
+
+/// Error of whichever codec is wrapped by [`AnyCodec`]
+pub struct AnyCodecError(alloc::boxed::Box<dyn core::error::Error + Send + Sync + 'static>);
+
+impl AnyCodecError {
+    fn new<E: core::error::Error + Send + Sync + 'static>(err: E) -> Self {
+        AnyCodecError(alloc::boxed::Box::new(err))
+    }
+}
+
+impl core::fmt::Debug for AnyCodecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        <dyn core::error::Error as core::fmt::Debug>::fmt(self.0.as_ref(), f)
+    }
+}
+
+impl core::fmt::Display for AnyCodecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        <dyn core::error::Error as core::fmt::Display>::fmt(self.0.as_ref(), f)
+    }
+}
+
+impl core::error::Error for AnyCodecError {}
+
+impl Codec for AnyCodec {
+    type CompressionError = AnyCodecError;
+    type DecompressionError = AnyCodecError;
+
+    fn compress(&self, data: &[u8]) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
+        match self {
+            AnyCodec::Uncompressed(codec) => codec.compress(data).map_err(AnyCodecError::new),
+            #[cfg(feature = "lz4")]
+            AnyCodec::Lz4(codec) => codec.compress(data).map_err(AnyCodecError::new),
+            #[cfg(feature = "zstd")]
+            AnyCodec::Zstd(codec) => codec.compress(data).map_err(AnyCodecError::new),
+            #[cfg(feature = "deflate")]
+            AnyCodec::Deflate(codec) => codec.compress(data).map_err(AnyCodecError::new),
+            AnyCodec::Custom(codec) => codec.compress(data),
+        }
+    }
+
+    fn compress_parts(&self, parts: &mut dyn Iterator<Item = alloc::vec::Vec<u8>>) -> Result<alloc::vec::Vec<u8>, Self::CompressionError> {
+        match self {
+            AnyCodec::Uncompressed(codec) => codec.compress_parts(parts).map_err(AnyCodecError::new),
+            #[cfg(feature = "lz4")]
+            AnyCodec::Lz4(codec) => codec.compress_parts(parts).map_err(AnyCodecError::new),
+            #[cfg(feature = "zstd")]
+            AnyCodec::Zstd(codec) => codec.compress_parts(parts).map_err(AnyCodecError::new),
+            #[cfg(feature = "deflate")]
+            AnyCodec::Deflate(codec) => codec.compress_parts(parts).map_err(AnyCodecError::new),
+            AnyCodec::Custom(codec) => codec.compress_parts(parts),
+        }
+    }
+
+    fn decompress_checked(&self, src: &[u8], dst: &mut [u8]) -> Result<(), Self::DecompressionError> {
+        match self {
+            AnyCodec::Uncompressed(codec) => codec.decompress_checked(src, dst).map_err(AnyCodecError::new),
+            #[cfg(feature = "lz4")]
+            AnyCodec::Lz4(codec) => codec.decompress_checked(src, dst).map_err(AnyCodecError::new),
+            #[cfg(feature = "zstd")]
+            AnyCodec::Zstd(codec) => codec.decompress_checked(src, dst).map_err(AnyCodecError::new),
+            #[cfg(feature = "deflate")]
+            AnyCodec::Deflate(codec) => codec.decompress_checked(src, dst).map_err(AnyCodecError::new),
+            AnyCodec::Custom(codec) => codec.decompress_checked(src, dst),
+        }
+    }
+
+    fn decompress_append(&self, src: &[u8], len: usize, dst: &mut alloc::vec::Vec<u8>) -> Result<(), Self::DecompressionError> {
+        match self {
+            AnyCodec::Uncompressed(codec) => codec.decompress_append(src, len, dst).map_err(AnyCodecError::new),
+            #[cfg(feature = "lz4")]
+            AnyCodec::Lz4(codec) => codec.decompress_append(src, len, dst).map_err(AnyCodecError::new),
+            #[cfg(feature = "zstd")]
+            AnyCodec::Zstd(codec) => codec.decompress_append(src, len, dst).map_err(AnyCodecError::new),
+            #[cfg(feature = "deflate")]
+            AnyCodec::Deflate(codec) => codec.decompress_append(src, len, dst).map_err(AnyCodecError::new),
+            AnyCodec::Custom(codec) => codec.decompress_append(src, len, dst),
+        }
+    }
 }
@@ -1,8 +1,9 @@
 use crate::codec::Codec;
+use crate::error::{LoadError, Section};
+use crate::lookup::Names;
 
-const _I_DONT_CARE_ABOUT_16_BIT_TARGETS: () = if std::mem::size_of::<usize>() < 4 {
+const _I_DONT_CARE_ABOUT_16_BIT_TARGETS: () = if core::mem::size_of::<usize>() < 4 {
     panic!("yeah, no, this ain't gonna fly")
-} else {
 };
 
 #[inline(always)]
@@ -15,29 +16,253 @@ pub fn u32_to_usize_range<R: core::borrow::Borrow<core::ops::Range<u32>>>(range:
     u32_to_usize(range.borrow().start)..u32_to_usize(range.borrow().end)
 }
 
-pub fn decompress_ranges<C: Codec>(codec: &C, compressed_lengths: &[u8], number_of_entries: usize) -> std::vec::Vec<core::ops::Range<u32>> {
-    let decompressed_len = number_of_entries
-        .checked_mul(4)
-        .expect("multiplication should not overflow at runtime because it would have overflowed at compile time already");
-    let decompressed_lengths = codec.decompress_with_length(compressed_lengths, decompressed_len);
-    let mut ranges = std::vec::Vec::<std::ops::Range<u32>>::with_capacity(number_of_entries);
-    for slice in decompressed_lengths.chunks(4) {
-        let len = u32::from_le_bytes(slice.try_into().expect("length is divisible by 4"));
-        let start = ranges.last().map(|range| range.end).unwrap_or(0);
-        let end = start
-            .checked_add(len)
-            .expect("overflow should have been caught during construction at compile time");
-        ranges.push(start..end);
+/// Decompress `src` into a new vector of length `len`.
+pub fn decompress_section<C: Codec>(codec: &C, src: &[u8], len: usize, section: Section) -> Result<alloc::vec::Vec<u8>, LoadError> {
+    // sections are small, so they are decompressed into a buffer of the declared size, which no codec can write past
+    let mut dst = vec![0; len];
+    codec.decompress_checked(src, &mut dst).map_err(|err| LoadError::Decompression {
+        section,
+        source: alloc::boxed::Box::new(err),
+    })?;
+    Ok(dst)
+}
+
+/// Compressed and uncompressed range of a shard of the asset data
+pub type Shard = (core::ops::Range<usize>, core::ops::Range<usize>);
+
+/// Parse the shard table of an archive (see [`CompressedNamedArchive::shards`](crate::named::CompressedNamedArchive::shards)).
+///
+/// Without a shard table, the whole data is a single shard.
+pub fn shards(table: &[u8], compressed_len: usize, uncompressed_len: usize) -> Result<alloc::vec::Vec<Shard>, LoadError> {
+    if table.is_empty() {
+        return Ok(vec![(0..compressed_len, 0..uncompressed_len)]);
+    }
+    let (entries, remainder) = table.as_chunks::<8>();
+    if !remainder.is_empty() {
+        return Err(LoadError::InvalidShardTable);
+    }
+    let mut shards = alloc::vec::Vec::<Shard>::with_capacity(entries.len());
+    for entry in entries {
+        let (compressed_end, uncompressed_end) = entry.split_at(4);
+        let compressed_end = u32_to_usize(u32::from_le_bytes(compressed_end.try_into().expect("slice has length 4")));
+        let uncompressed_end = u32_to_usize(u32::from_le_bytes(uncompressed_end.try_into().expect("slice has length 4")));
+        let (compressed_start, uncompressed_start) = shards.last().map(|(c, u)| (c.end, u.end)).unwrap_or((0, 0));
+        if compressed_end < compressed_start || uncompressed_end < uncompressed_start {
+            return Err(LoadError::InvalidShardTable);
+        }
+        shards.push((compressed_start..compressed_end, uncompressed_start..uncompressed_end));
+    }
+    if shards.last().map(|(c, u)| (c.end, u.end)) != Some((compressed_len, uncompressed_len)) {
+        return Err(LoadError::InvalidShardTable);
+    }
+    Ok(shards)
+}
+
+/// Decompress the shards of the asset data from `src` into `dst`, replacing its content.
+///
+/// `progress` is called with the number of uncompressed bytes done and the total number of bytes, initially and after each shard.
+pub fn decompress_shards<C: Codec>(
+    codec: &C,
+    src: &[u8],
+    shards: &[Shard],
+    dst: &mut alloc::vec::Vec<u8>,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<(), LoadError> {
+    let total = shards.last().map(|(_, uncompressed)| uncompressed.end).unwrap_or(0);
+    // shards are appended to the reserved capacity, without filling it with zeros first
+    dst.clear();
+    dst.reserve_exact(total);
+    progress(0, total);
+    for (compressed, uncompressed) in shards {
+        codec
+            .decompress_append(&src[compressed.clone()], uncompressed.len(), dst)
+            .map_err(|err| LoadError::Decompression {
+                section: Section::Data,
+                source: alloc::boxed::Box::new(err),
+            })?;
+        progress(uncompressed.end, total);
+    }
+    Ok(())
+}
+
+/// Decompress the shards of the asset data from `src` into `dst` like [`decompress_shards`], but into a buffer of the declared size.
+///
+/// This fills the buffer with zeros first, but guarantees that no codec writes more data than declared, even for malicious input.
+pub fn decompress_shards_bounded<C: Codec>(codec: &C, src: &[u8], shards: &[Shard], dst: &mut alloc::vec::Vec<u8>) -> Result<(), LoadError> {
+    let total = shards.last().map(|(_, uncompressed)| uncompressed.end).unwrap_or(0);
+    dst.clear();
+    dst.resize(total, 0);
+    for (compressed, uncompressed) in shards {
+        codec
+            .decompress_checked(&src[compressed.clone()], &mut dst[uncompressed.clone()])
+            .map_err(|err| LoadError::Decompression {
+                section: Section::Data,
+                source: alloc::boxed::Box::new(err),
+            })?;
+    }
+    Ok(())
+}
+
+/// Whether threads can be spawned on the target, which is not the case without std or for WebAssembly without the atomics proposal
+pub const HAS_THREADS: bool = cfg!(feature = "std") && !cfg!(all(target_family = "wasm", not(target_feature = "atomics")));
+
+/// Decompress the shards of the asset data from `src` into `dst` using up to `threads` threads, replacing the content of `dst`.
+pub fn decompress_shards_parallel<C: Codec + Sync>(
+    codec: &C,
+    src: &[u8],
+    shards: &[Shard],
+    dst: &mut alloc::vec::Vec<u8>,
+    threads: usize,
+) -> Result<(), LoadError> {
+    let threads = if HAS_THREADS { threads.min(shards.len()) } else { 1 };
+    if threads <= 1 {
+        return decompress_shards(codec, src, shards, dst, &mut |_, _| {});
+    }
+    #[cfg(not(feature = "std"))]
+    unreachable!("threads are never used without std");
+    #[cfg(feature = "std")]
+    decompress_shards_threaded(codec, src, shards, dst, threads)
+}
+
+#[cfg(feature = "std")]
+fn decompress_shards_threaded<C: Codec + Sync>(
+    codec: &C,
+    src: &[u8],
+    shards: &[Shard],
+    dst: &mut alloc::vec::Vec<u8>,
+    threads: usize,
+) -> Result<(), LoadError> {
+    let total = shards.last().map(|(_, uncompressed)| uncompressed.end).unwrap_or(0);
+    dst.clear();
+    dst.resize(total, 0);
+
+    // distribute shards round-robin, each with the part of `dst` it decompresses into
+    let mut jobs: alloc::vec::Vec<alloc::vec::Vec<(&[u8], &mut [u8])>> = (0..threads).map(|_| vec![]).collect();
+    let mut rest = dst.as_mut_slice();
+    for (i, (compressed, uncompressed)) in shards.iter().enumerate() {
+        let (shard, tail) = core::mem::take(&mut rest).split_at_mut(uncompressed.len());
+        rest = tail;
+        jobs[i % threads].push((&src[compressed.clone()], shard));
+    }
+
+    std::thread::scope(|scope| {
+        let handles: alloc::vec::Vec<_> = jobs
+            .into_iter()
+            .map(|job| {
+                scope.spawn(move || {
+                    for (src, dst) in job {
+                        codec.decompress_checked(src, dst).map_err(|err| LoadError::Decompression {
+                            section: Section::Data,
+                            source: alloc::boxed::Box::new(err),
+                        })?;
+                    }
+                    Ok(())
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+    })
+}
+
+/// Parse an unsigned LEB128 varint of at most 32 bits at the start of `bytes`, returning it and the remaining bytes.
+fn read_varint(bytes: &[u8]) -> Option<(u32, &[u8])> {
+    let mut value = 0;
+    for (i, &byte) in bytes.iter().enumerate().take(5) {
+        if i == 4 && byte > 0x0f {
+            return None; // more than 32 bits
+        }
+        value |= u32::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, &bytes[i + 1..]));
+        }
     }
-    ranges
+    None
 }
 
-pub fn decompress_names<C: Codec>(
+/// Decompress the asset sizes (see [`CompressedNamedArchive::compressed_sizes`](crate::named::CompressedNamedArchive::compressed_sizes)) and turn them into data ranges.
+///
+/// Each range starts at the next multiple of `align` (see [`CompressedNamedArchive::align`](crate::named::CompressedNamedArchive::align)).
+pub fn decompress_ranges<C: Codec>(
     codec: &C,
-    compressed_names_with_null_delimiters: &[u8],
+    compressed_lengths: &[u8],
     decompressed_len: u32,
-) -> std::vec::Vec<smartstring::SmartString<smartstring::LazyCompact>> {
-    let decompressed_data = codec.decompress_with_length(compressed_names_with_null_delimiters, u32_to_usize(decompressed_len));
-    let names = decompressed_data.split(|b| *b == 0);
-    names.map(|bytes| std::str::from_utf8(bytes).expect("names should be UTF-8").into()).collect()
+    number_of_entries: usize,
+    align: u32,
+) -> Result<alloc::vec::Vec<core::ops::Range<u32>>, LoadError> {
+    // sizes are stored as u32s if their uncompressed length is 0
+    let varints = decompressed_len != 0;
+    let decompressed_len = if varints {
+        u32_to_usize(decompressed_len)
+    } else {
+        number_of_entries.checked_mul(4).ok_or(LoadError::InvalidSizes)?
+    };
+    let decompressed_lengths = decompress_section(codec, compressed_lengths, decompressed_len, Section::Sizes)?;
+    parse_ranges(&decompressed_lengths, varints, number_of_entries, align)
+}
+
+/// Turn uncompressed asset sizes into data ranges, see [`decompress_ranges`].
+///
+/// The sizes are unsigned LEB128 varints if `varints` is set, or little endian `u32`s otherwise.
+pub fn parse_ranges(sizes: &[u8], varints: bool, number_of_entries: usize, align: u32) -> Result<alloc::vec::Vec<core::ops::Range<u32>>, LoadError> {
+    let mut lengths = alloc::vec::Vec::with_capacity(number_of_entries);
+    if !varints {
+        if number_of_entries.checked_mul(4) != Some(sizes.len()) {
+            return Err(LoadError::InvalidSizes);
+        }
+        lengths.extend(
+            sizes
+                .chunks(4)
+                .map(|slice| u32::from_le_bytes(slice.try_into().expect("length is divisible by 4"))),
+        );
+    } else {
+        let mut rest = sizes;
+        while !rest.is_empty() {
+            let (len, tail) = read_varint(rest).ok_or(LoadError::InvalidSizes)?;
+            lengths.push(len);
+            rest = tail;
+        }
+        if lengths.len() != number_of_entries {
+            return Err(LoadError::InvalidSizes);
+        }
+    }
+    let mut ranges = alloc::vec::Vec::<core::ops::Range<u32>>::with_capacity(number_of_entries);
+    for len in lengths {
+        let previous_end = ranges.last().map(|range| range.end).unwrap_or(0);
+        let range = previous_end
+            .checked_next_multiple_of(align.max(1))
+            .and_then(|start| Some(start..start.checked_add(len)?))
+            .ok_or(LoadError::SizeMismatch {
+                section: Section::Data,
+                expected: u32_to_usize(u32::MAX),
+                actual: u32_to_usize(previous_end).saturating_add(u32_to_usize(len)),
+            })?;
+        ranges.push(range);
+    }
+    Ok(ranges)
+}
+
+pub(crate) fn decompress_names<C: Codec>(codec: &C, compressed_names_with_null_delimiters: &[u8], decompressed_len: u32) -> Result<Names, LoadError> {
+    let decompressed_data = decompress_section(codec, compressed_names_with_null_delimiters, u32_to_usize(decompressed_len), Section::Names)?;
+    parse_names(&decompressed_data)
+}
+
+/// Split uncompressed names separated by null bytes, see [`decompress_names`].
+pub(crate) fn parse_names(names_with_null_delimiters: &[u8]) -> Result<Names, LoadError> {
+    let names = names_with_null_delimiters.split(|b| *b == 0);
+    names
+        .enumerate()
+        .map(|(index, bytes)| core::str::from_utf8(bytes).map_err(|_| LoadError::InvalidName { index }))
+        .collect()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    /// Also run on WebAssembly without atomics, e.g. `cargo test --target wasm32-wasip1` with a WASI runtime, where spawning fails.
+    #[test]
+    fn has_threads_matches_spawning() {
+        let spawned = std::thread::Builder::new().spawn(|| {}).map(std::thread::JoinHandle::join);
+        assert_eq!(spawned.is_ok(), super::HAS_THREADS);
+    }
 }
@@ -0,0 +1,15 @@
+//! Deserialization of assets declared with the `deserialize` option of `AssetEnum`.
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Deserialize TOML data.
+#[cfg(feature = "toml")]
+pub fn toml<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, Error> {
+    Ok(::toml::from_str(std::str::from_utf8(data)?)?)
+}
+
+/// Deserialize JSON data.
+#[cfg(feature = "json")]
+pub fn json<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, Error> {
+    Ok(serde_json::from_slice(data)?)
+}
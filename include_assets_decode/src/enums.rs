@@ -1,51 +1,297 @@
 use crate::checksum::{check, Checksum};
 use crate::codec::Codec;
 use crate::common::u32_to_usize;
+use crate::error::{DeserializeError, FromAssetError, LoadError, Section};
+use alloc::borrow::ToOwned;
+use core::borrow::Borrow;
 
 /// Trait for assets that can be lookup up by enum.
 ///
 /// This should _never_ be implemented manually, only derived.
 pub trait AssetEnum: Sized {
-    /// Compressed asset data
+    /// Compressed data of all assets which are not stored uncompressed (see [`AssetEnum::RAW`])
     const DATA: &'static [u8];
 
+    /// Data of all assets which are stored uncompressed, in order of the variants
+    const RAW_DATA: &'static [u8];
+
+    /// For each enum variant, whether its data is stored uncompressed in [`AssetEnum::RAW_DATA`] instead of compressed in [`AssetEnum::DATA`]
+    const RAW: &'static [bool];
+
+    /// For each enum variant, whether it is an optional asset whose file was missing at compile time
+    const MISSING: &'static [bool];
+
+    /// For each enum variant, whether its data was validated as UTF-8 at compile time
+    const STR: &'static [bool];
+
     /// Position of the end of the asset data for each enum within the uncompressed combined data.
     const DATA_END_OFFSETS: &'static [u32];
 
+    /// Position of the end of the compressed data for each enum within [`AssetEnum::DATA`] if assets are compressed independently (option `per_asset`).
+    ///
+    /// Empty if all assets are compressed as a single stream.
+    const COMPRESSED_END_OFFSETS: &'static [u32];
+
+    /// Position of the data of this enum within the decompressed [`AssetEnum::DATA`], which may be shared by all enums of an asset group
+    const GROUP_OFFSET: u32;
+
+    /// Size of the decompressed [`AssetEnum::DATA`] (unless assets are compressed independently)
+    const GROUP_SIZE: u32;
+
+    /// Decompressed data of the asset group this enum belongs to, which is shared by all enums of the group, or `None` if it doesn't belong to one
+    const GROUP: Option<&'static AssetGroup>;
+
+    /// For each enum variant, the index of the variant whose data it uses.
+    ///
+    /// This is the variant itself, unless its file is the same as that of an earlier variant (option `dedup`), in which case its own data is empty.
+    const ALIASES: &'static [usize];
+
     /// Checksums for all assets
     const CHECKSUMS: &'static [Checksum];
 
+    /// Path of the asset for each enum variant, relative to the base path
+    const PATHS: &'static [&'static str];
+
+    /// Size in bytes of the asset for each enum variant, known at compile time (0 for optional assets whose file was missing)
+    const SIZES: &'static [usize];
+
     /// Type of compression codec
     type C: Codec;
 
     /// Compression codec with which to decompress the asset data
     const CODEC: Self::C;
 
-    /// This method should map an enum variant to its discriminator (via `as` casting).
+    /// Values of all assets with a `deserialize` option, or `()` if there are none
+    type Typed;
+
+    /// This method maps an enum variant to its position in declaration order, which is independent of explicit discriminants.
     ///
-    /// The reason this exists is that the `Index` implementation for [`EnumArchive`] cannot perform this cast (because it doesn't know that implementers are enums)
-    fn index(self) -> usize;
+    /// The reason this exists is that the `Index` implementation for [`EnumArchive`] cannot perform this mapping (because it doesn't know that implementers are enums)
+    fn index(&self) -> usize;
+
+    /// Deserialize all assets with a `deserialize` option.
+    fn deserialize(archive: &EnumArchive<Self>) -> Result<Self::Typed, DeserializeError>;
 
     /// Load (decompress) compressed data for this enum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if loading fails, see [`AssetEnum::try_load`].
     fn load() -> EnumArchive<Self> {
-        let mut data = vec![0u8; u32_to_usize(Self::DATA_END_OFFSETS.last().copied().unwrap_or(0))];
-        Self::CODEC.decompress(Self::DATA, &mut data);
+        match Self::try_load() {
+            Ok(archive) => archive,
+            Err(err) => panic!("couldn't load assets of {}: {err}", core::any::type_name::<Self>()),
+        }
+    }
+
+    /// Load (decompress) compressed data for this enum, returning an error if decompression fails or the checksum of an asset doesn't match.
+    fn try_load() -> Result<EnumArchive<Self>, LoadError> {
+        let len = |i: usize| data_range::<Self>(i).len();
+        let total_len = u32_to_usize(Self::DATA_END_OFFSETS.last().copied().unwrap_or(0));
+        let mut data;
+        if Self::COMPRESSED_END_OFFSETS.is_empty() {
+            let compressed_len: usize = (0..Self::RAW.len()).filter(|&i| !Self::RAW[i]).map(len).sum();
+            data = alloc::vec::Vec::with_capacity(total_len);
+            if let Some(group) = Self::GROUP {
+                // The data is shared with other enums of an asset group.
+                let group = group.data::<Self>()?;
+                let start = u32_to_usize(Self::GROUP_OFFSET);
+                data.extend_from_slice(&group[start..start + compressed_len]);
+            } else {
+                Self::CODEC
+                    .decompress_append(Self::DATA, compressed_len, &mut data)
+                    .map_err(|err| LoadError::Decompression {
+                        section: Section::Data,
+                        source: alloc::boxed::Box::new(err),
+                    })?;
+            }
+            // only the space for raw assets is filled with zeros, it is overwritten below
+            data.resize(total_len, 0);
+
+            // Move the decompressed assets to their final position (back to front, so nothing is overwritten).
+            let mut compressed_end = compressed_len;
+            for i in (0..Self::RAW.len()).rev().filter(|&i| !Self::RAW[i]) {
+                let start = compressed_end - len(i);
+                data.copy_within(start..compressed_end, data_range::<Self>(i).start);
+                compressed_end = start;
+            }
+
+            // Fill in the uncompressed assets.
+            for (i, raw_start) in raw_starts::<Self>() {
+                data[data_range::<Self>(i)].copy_from_slice(&Self::RAW_DATA[raw_start..raw_start + len(i)]);
+            }
+        } else {
+            // Append the assets in order, decompressing each.
+            data = alloc::vec::Vec::with_capacity(total_len);
+            let mut raw_start = 0;
+            for i in 0..Self::RAW.len() {
+                if Self::RAW[i] {
+                    data.extend_from_slice(&Self::RAW_DATA[raw_start..raw_start + len(i)]);
+                    raw_start += len(i);
+                } else {
+                    decompress_asset::<Self>(i, &mut data)?;
+                }
+            }
+        }
+
         let result = EnumArchive {
             data,
             _spooky: core::marker::PhantomData,
         };
         for i in 0..Self::CHECKSUMS.len() {
-            check(result.lookup(i), &Self::CHECKSUMS[i]).expect("checksum should match");
+            check_asset::<Self>(i, result.lookup(i))?;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Decompressed data of an asset group, shared by all of its enums (see the `asset_group!` macro)
+///
+/// With feature `std`, the group is decompressed when the first of its enums is loaded, and kept for the lifetime of the program,
+/// so that loading the other enums only copies their part of it.
+/// Without `std`, each load decompresses the whole group.
+pub struct AssetGroup {
+    #[cfg(feature = "std")]
+    data: std::sync::OnceLock<alloc::boxed::Box<[u8]>>,
+}
+
+impl AssetGroup {
+    /// Group which is not decompressed yet
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            data: std::sync::OnceLock::new(),
         }
+    }
+
+    /// Heap memory used by the decompressed data of the group, 0 if it hasn't been decompressed (or isn't kept, without feature `std`)
+    pub fn memory_usage(&self) -> usize {
+        #[cfg(feature = "std")]
+        if let Some(data) = self.data.get() {
+            return data.len();
+        }
+        0
+    }
+
+    /// Decompressed data of the group which enum `E` belongs to, decompressing it on first use
+    fn data<E: AssetEnum>(&self) -> Result<alloc::borrow::Cow<'_, [u8]>, LoadError> {
+        #[cfg(feature = "std")]
+        if let Some(data) = self.data.get() {
+            return Ok(alloc::borrow::Cow::Borrowed(data));
+        }
+        let data = crate::common::decompress_section(&E::CODEC, E::DATA, u32_to_usize(E::GROUP_SIZE), Section::Data)?;
+        #[cfg(feature = "std")]
+        return Ok(alloc::borrow::Cow::Borrowed(self.data.get_or_init(|| data.into_boxed_slice())));
+        #[cfg(not(feature = "std"))]
+        Ok(alloc::borrow::Cow::Owned(data))
+    }
+}
+
+/// Range of the data of the `i`th variant within the uncompressed combined data
+fn data_range<E: AssetEnum>(i: usize) -> core::ops::Range<usize> {
+    let start = i.checked_sub(1).map(|j| E::DATA_END_OFFSETS[j]).map(u32_to_usize).unwrap_or(0);
+    start..u32_to_usize(E::DATA_END_OFFSETS[i])
+}
+
+/// Index and start within [`AssetEnum::RAW_DATA`] of each variant stored uncompressed
+fn raw_starts<E: AssetEnum>() -> impl Iterator<Item = (usize, usize)> {
+    (0..E::RAW.len()).filter(|&i| E::RAW[i]).scan(0, |raw_start, i| {
+        let start = *raw_start;
+        *raw_start += data_range::<E>(i).len();
+        Some((i, start))
+    })
+}
+
+/// Decompress the data of the `i`th variant, which must be compressed independently, appending it to `dst`.
+fn decompress_asset<E: AssetEnum>(i: usize, dst: &mut alloc::vec::Vec<u8>) -> Result<(), LoadError> {
+    let start = i.checked_sub(1).map(|j| E::COMPRESSED_END_OFFSETS[j]).map(u32_to_usize).unwrap_or(0);
+    E::CODEC
+        .decompress_append(&E::DATA[start..u32_to_usize(E::COMPRESSED_END_OFFSETS[i])], data_range::<E>(i).len(), dst)
+        .map_err(|err| LoadError::AssetDecompression {
+            name: E::PATHS[i].to_owned(),
+            source: alloc::boxed::Box::new(err),
+        })
+}
+
+/// Check the data of the `i`th variant against its checksum.
+fn check_asset<E: AssetEnum>(i: usize, data: &[u8]) -> Result<(), LoadError> {
+    check(data, &E::CHECKSUMS[i]).map_err(|mismatch| LoadError::ChecksumMismatch {
+        name: E::PATHS[i].to_owned(),
+        mismatch: alloc::boxed::Box::new(mismatch),
+    })
+}
+
+/// Stable interface of asset enums, for generic code over any enum deriving `AssetEnum`
+///
+/// Implemented for all asset enums; it can't be implemented otherwise.
+pub trait EmbeddedAssets: AssetEnum {
+    /// Number of enum variants
+    const ASSET_COUNT: usize = Self::PATHS.len();
+
+    /// Position of the enum variant in declaration order
+    fn asset_index(&self) -> usize {
+        self.index()
+    }
+
+    /// Path of the asset file of the enum variant, relative to its base path
+    fn asset_path(&self) -> &'static str {
+        Self::PATHS[self.index()]
+    }
+
+    /// Size of the asset of the enum variant in bytes, known at compile time
+    fn asset_size(&self) -> usize {
+        Self::SIZES[self.index()]
+    }
+
+    /// Load (decompress) the assets of all variants.
+    fn load_archive() -> EnumArchive<Self> {
+        Self::load()
+    }
+}
+
+impl<E: AssetEnum> EmbeddedAssets for E {}
+
+/// Trait for asset enums whose variants can be iterated over, derived with option `iterable = true`.
+///
+/// This is a separate trait because constructing all variants suppresses warnings about unused variants.
+pub trait IterableAssetEnum: AssetEnum {
+    /// Inverse of [`AssetEnum::index`].
+    ///
+    /// Panics if `index` is not the index of a variant.
+    fn from_index(index: usize) -> Self;
+}
+
+/// Conversion of asset data into a value, see [`EnumArchive::load_as`]
+pub trait FromAsset: Sized {
+    /// Error if the data can't be converted
+    type Error: core::error::Error + Send + Sync + 'static;
+
+    /// Convert the asset data.
+    fn from_asset(data: &[u8]) -> Result<Self, Self::Error>;
+}
+
+impl FromAsset for alloc::vec::Vec<u8> {
+    type Error = core::convert::Infallible;
+
+    fn from_asset(data: &[u8]) -> Result<Self, Self::Error> {
+        Ok(data.to_vec())
+    }
+}
 
-        result
+impl FromAsset for alloc::string::String {
+    type Error = core::str::Utf8Error;
+
+    fn from_asset(data: &[u8]) -> Result<Self, Self::Error> {
+        core::str::from_utf8(data).map(str::to_owned)
     }
 }
 
 // Archive holding uncompressed data for an AssetEnum.
 // User-facing documentation is in the include_assets crate.
 pub struct EnumArchive<E> {
-    data: std::vec::Vec<u8>,
+    data: alloc::vec::Vec<u8>,
     _spooky: core::marker::PhantomData<E>,
 }
 
@@ -54,10 +300,56 @@ impl<E: AssetEnum> EnumArchive<E> {
         E::load()
     }
 
+    /// Load the archive, returning an error instead of panicking if loading fails.
+    pub fn try_load() -> Result<Self, LoadError> {
+        E::try_load()
+    }
+
+    /// Load the archive and deserialize all assets with a `deserialize` option.
+    pub fn load_typed() -> Result<E::Typed, DeserializeError> {
+        Self::load().deserialize()
+    }
+
+    /// Deserialize all assets with a `deserialize` option.
+    pub fn deserialize(&self) -> Result<E::Typed, DeserializeError> {
+        E::deserialize(self)
+    }
+
+    /// Load the archive and convert the data of each asset into a `T`, see [`FromAsset`].
+    ///
+    /// Returns an error for the first asset (in declaration order) which can't be converted.
+    pub fn load_as<T: FromAsset>() -> Result<EnumMap<E, T>, FromAssetError> {
+        let archive = Self::load();
+        let data: Result<_, FromAssetError> = (0..E::CHECKSUMS.len())
+            .map(|i| {
+                T::from_asset(archive.lookup(i)).map_err(|err| FromAssetError {
+                    path: E::PATHS[i],
+                    source: alloc::boxed::Box::new(err),
+                })
+            })
+            .collect();
+        Ok(EnumMap {
+            data: data?,
+            _spooky: core::marker::PhantomData,
+        })
+    }
+
+    /// Prepare to decompress each asset the first time it is accessed, see [`LazyEnumArchive`].
+    #[cfg(feature = "std")]
+    pub fn load_lazy() -> LazyEnumArchive<E> {
+        let mut raw_start = vec![0; E::RAW.len()];
+        for (i, start) in raw_starts::<E>() {
+            raw_start[i] = start;
+        }
+        LazyEnumArchive {
+            slots: (0..E::RAW.len()).map(|_| std::sync::OnceLock::new()).collect(),
+            raw_start,
+            whole: std::sync::OnceLock::new(),
+        }
+    }
+
     fn lookup(&self, i: usize) -> &[u8] {
-        let end = u32_to_usize(E::DATA_END_OFFSETS[i]);
-        let start = i.checked_sub(1).map(|j| E::DATA_END_OFFSETS[j]).map(u32_to_usize).unwrap_or(0);
-        &self.data[start..end]
+        &self.data[data_range::<E>(E::ALIASES[i])]
     }
 
     /// Apply the mapping function to the asset data.
@@ -78,18 +370,266 @@ impl<E: AssetEnum> EnumArchive<E> {
     }
 }
 
+impl<E: AssetEnum> EnumArchive<E> {
+    /// Returns the asset data of all variants, concatenated in declaration order.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    /// Returns the end of the asset data of each variant within [`EnumArchive::as_bytes`], in declaration order.
+    ///
+    /// Each asset starts at the end of the previous one, or at 0 for the first.
+    /// A variant whose file is the same as that of an earlier variant (see option `dedup`) is empty here, and uses the data of the earlier variant.
+    pub fn end_offsets(&self) -> &'static [u32] {
+        E::DATA_END_OFFSETS
+    }
+}
+
+impl<E: AssetEnum> EnumArchive<E> {
+    /// Split the data into an owned buffer per asset, in declaration order.
+    ///
+    /// Assets are copied off the end of the archive one by one, so each byte except those of the first asset is copied once,
+    /// and the data is held up to twice until all assets are split off.
+    /// The first asset keeps the allocation of the whole archive, which is shrunk once at the end.
+    fn into_buffers(mut self) -> alloc::vec::Vec<alloc::vec::Vec<u8>> {
+        let mut buffers: alloc::vec::Vec<_> = (1..E::CHECKSUMS.len()).rev().map(|i| self.data.split_off(data_range::<E>(i).start)).collect();
+        if !E::CHECKSUMS.is_empty() {
+            self.data.shrink_to_fit();
+            buffers.push(self.data);
+        }
+        buffers.reverse();
+        for (i, &original) in E::ALIASES.iter().enumerate() {
+            if original != i {
+                buffers[i] = buffers[original].clone();
+            }
+        }
+        buffers
+    }
+
+    /// Consume the archive and apply the mapping function to the owned asset data.
+    ///
+    /// Each asset is copied into its own buffer once (except the first, which keeps the allocation of the archive),
+    /// which is then passed on, so converting it into an owned type such as `String` or `Vec<u8>` needs no further copy.
+    pub fn into_map<T, F: FnMut(alloc::vec::Vec<u8>) -> T>(self, f: F) -> EnumMap<E, T> {
+        EnumMap {
+            data: self.into_buffers().into_iter().map(f).collect(),
+            _spooky: core::marker::PhantomData,
+        }
+    }
+
+    /// Consume the archive and apply a fallible mapping function to the owned asset data, returning an enum map if each invocation succeeds, or an `Err` otherwise.
+    pub fn into_try_map<T, Err, F: FnMut(alloc::vec::Vec<u8>) -> Result<T, Err>>(self, f: F) -> Result<EnumMap<E, T>, Err> {
+        let data: Result<_, Err> = self.into_buffers().into_iter().map(f).collect();
+        Ok(EnumMap {
+            data: data?,
+            _spooky: core::marker::PhantomData,
+        })
+    }
+}
+
+impl<E: IterableAssetEnum> EnumArchive<E> {
+    /// Returns an iterator of all variants and their asset data in declaration order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (E, &[u8])> + '_ {
+        (0..E::CHECKSUMS.len()).map(|i| (E::from_index(i), self.lookup(i)))
+    }
+}
+
+impl<E: AssetEnum> EnumArchive<E> {
+    /// Look up the asset data corresponding to the enum variant, or `None` if it is an optional asset whose file was missing at compile time.
+    pub fn get(&self, e: impl Borrow<E>) -> Option<&[u8]> {
+        let i = e.borrow().index();
+        if E::MISSING[i] {
+            None
+        } else {
+            Some(self.lookup(i))
+        }
+    }
+
+    /// Returns the path of the asset file corresponding to the enum variant, relative to the base path, e.g. for log messages.
+    pub fn name_of(&self, e: impl Borrow<E>) -> &'static str {
+        E::PATHS[e.borrow().index()]
+    }
+
+    /// Look up the asset data corresponding to the enum variant as a string slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the variant is not declared with `kind = "str"`.
+    pub fn get_str(&self, e: impl Borrow<E>) -> &str {
+        let i = e.borrow().index();
+        assert!(E::STR[i], "asset is not declared with kind = \"str\"");
+        // Validated at compile time, and the checksum was verified when loading.
+        core::str::from_utf8(self.lookup(i)).expect("asset should be valid UTF-8")
+    }
+}
+
+impl<E> EnumArchive<E> {
+    /// Leak the data of the archive, so the assets live for the rest of the program, see [`StaticEnumArchive`].
+    pub fn leak(self) -> StaticEnumArchive<E> {
+        StaticEnumArchive {
+            data: self.data.leak(),
+            _spooky: core::marker::PhantomData,
+        }
+    }
+}
+
 impl<E: AssetEnum> core::ops::Index<E> for EnumArchive<E> {
     type Output = [u8];
 
-    /// Look up the asset data corresponding to the enum variant
+    /// Look up the asset data corresponding to the enum variant.
+    ///
+    /// The data of an optional asset whose file was missing at compile time is empty.
+    fn index(&self, e: E) -> &[u8] {
+        self.lookup(e.index())
+    }
+}
+
+impl<E: AssetEnum> core::ops::Index<&E> for EnumArchive<E> {
+    type Output = [u8];
+
+    /// Look up the asset data corresponding to the enum variant.
+    ///
+    /// The data of an optional asset whose file was missing at compile time is empty.
+    fn index(&self, e: &E) -> &[u8] {
+        self.lookup(e.index())
+    }
+}
+
+// Archive of an AssetEnum whose data lives for the rest of the program.
+// User-facing documentation is in the include_assets crate.
+pub struct StaticEnumArchive<E> {
+    data: &'static [u8],
+    _spooky: core::marker::PhantomData<E>,
+}
+
+impl<E> Clone for StaticEnumArchive<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for StaticEnumArchive<E> {}
+
+impl<E: AssetEnum> StaticEnumArchive<E> {
+    fn lookup(&self, i: usize) -> &'static [u8] {
+        &self.data[data_range::<E>(E::ALIASES[i])]
+    }
+
+    /// Look up the asset data corresponding to the enum variant, or `None` if it is an optional asset whose file was missing at compile time.
+    pub fn get(&self, e: impl Borrow<E>) -> Option<&'static [u8]> {
+        let i = e.borrow().index();
+        if E::MISSING[i] {
+            None
+        } else {
+            Some(self.lookup(i))
+        }
+    }
+
+    /// Look up the asset data corresponding to the enum variant as a string slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the variant is not declared with `kind = "str"`.
+    pub fn get_str(&self, e: impl Borrow<E>) -> &'static str {
+        let i = e.borrow().index();
+        assert!(E::STR[i], "asset is not declared with kind = \"str\"");
+        // Validated at compile time, and the checksum was verified when loading.
+        core::str::from_utf8(self.lookup(i)).expect("asset should be valid UTF-8")
+    }
+}
+
+impl<E: AssetEnum> core::ops::Index<E> for StaticEnumArchive<E> {
+    type Output = [u8];
+
+    /// Look up the asset data corresponding to the enum variant.
+    ///
+    /// The data of an optional asset whose file was missing at compile time is empty.
     fn index(&self, e: E) -> &[u8] {
         self.lookup(e.index())
     }
 }
 
-/// A structure which holds a value of some type `T` for each variant of an [`AssetEnum`]
+impl<E: AssetEnum> core::ops::Index<&E> for StaticEnumArchive<E> {
+    type Output = [u8];
+
+    /// Look up the asset data corresponding to the enum variant.
+    ///
+    /// The data of an optional asset whose file was missing at compile time is empty.
+    fn index(&self, e: &E) -> &[u8] {
+        self.lookup(e.index())
+    }
+}
+
+// Archive which decompresses each asset of an AssetEnum on first access.
+// User-facing documentation is in the include_assets crate.
+#[cfg(feature = "std")]
+pub struct LazyEnumArchive<E> {
+    /// Data of each asset, once it has been accessed (only with per-asset compression)
+    slots: alloc::vec::Vec<std::sync::OnceLock<alloc::boxed::Box<[u8]>>>,
+    /// Start within the uncompressed data of each asset stored uncompressed
+    raw_start: alloc::vec::Vec<usize>,
+    /// Whole archive, once any asset has been accessed (only without per-asset compression)
+    whole: std::sync::OnceLock<EnumArchive<E>>,
+}
+
+#[cfg(feature = "std")]
+impl<E: AssetEnum> LazyEnumArchive<E> {
+    fn lookup(&self, i: usize) -> &[u8] {
+        if E::COMPRESSED_END_OFFSETS.is_empty() {
+            return self.whole.get_or_init(E::load).lookup(i);
+        }
+        let i = E::ALIASES[i];
+        self.slots[i].get_or_init(|| {
+            let range = data_range::<E>(i);
+            let mut data = alloc::vec::Vec::with_capacity(range.len());
+            let result = if E::RAW[i] {
+                data.extend_from_slice(&E::RAW_DATA[self.raw_start[i]..self.raw_start[i] + range.len()]);
+                Ok(())
+            } else {
+                decompress_asset::<E>(i, &mut data)
+            };
+            if let Err(err) = result.and_then(|()| check_asset::<E>(i, &data)) {
+                panic!("couldn't load asset: {err}");
+            }
+            data.into_boxed_slice()
+        })
+    }
+
+    /// Look up the asset data corresponding to the enum variant, decompressing it if this is the first access, or `None` if it is an optional asset whose file was missing at compile time.
+    pub fn get(&self, e: impl Borrow<E>) -> Option<&[u8]> {
+        let i = e.borrow().index();
+        if E::MISSING[i] {
+            None
+        } else {
+            Some(self.lookup(i))
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: AssetEnum> core::ops::Index<E> for LazyEnumArchive<E> {
+    type Output = [u8];
+
+    /// Look up the asset data corresponding to the enum variant, decompressing it if this is the first access.
+    fn index(&self, e: E) -> &[u8] {
+        self.lookup(e.index())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: AssetEnum> core::ops::Index<&E> for LazyEnumArchive<E> {
+    type Output = [u8];
+
+    /// Look up the asset data corresponding to the enum variant, decompressing it if this is the first access.
+    fn index(&self, e: &E) -> &[u8] {
+        self.lookup(e.index())
+    }
+}
+
+// A structure which holds a value of some type `T` for each variant of an AssetEnum.
+// User-facing documentation is in the include_assets crate.
 pub struct EnumMap<E: AssetEnum, T> {
-    data: std::vec::Vec<T>,
+    data: alloc::vec::Vec<T>,
     _spooky: core::marker::PhantomData<E>,
 }
 
@@ -112,6 +652,83 @@ impl<E: AssetEnum, T> EnumMap<E, T> {
     }
 }
 
+impl<E: AssetEnum, T> EnumMap<E, T> {
+    /// Returns an iterator of the values in declaration order of the enum variants.
+    pub fn values(&self) -> core::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Returns an iterator of exclusive references to the values in declaration order of the enum variants.
+    pub fn values_mut(&mut self) -> core::slice::IterMut<'_, T> {
+        self.data.iter_mut()
+    }
+
+    /// Consumes the map and returns an iterator of the values in declaration order of the enum variants.
+    pub fn into_values(self) -> alloc::vec::IntoIter<T> {
+        self.data.into_iter()
+    }
+
+    /// Combine two maps into a map of pairs of their values.
+    pub fn zip<U>(self, other: EnumMap<E, U>) -> EnumMap<E, (T, U)> {
+        EnumMap {
+            data: self.data.into_iter().zip(other.data).collect(),
+            _spooky: core::marker::PhantomData,
+        }
+    }
+}
+
+/// Iterator of enum variants and references to their values in an [`EnumMap`]
+pub type Iter<'a, E, T> = core::iter::Map<core::iter::Enumerate<core::slice::Iter<'a, T>>, fn((usize, &'a T)) -> (E, &'a T)>;
+
+/// Iterator of enum variants and exclusive references to their values in an [`EnumMap`]
+pub type IterMut<'a, E, T> = core::iter::Map<core::iter::Enumerate<core::slice::IterMut<'a, T>>, fn((usize, &'a mut T)) -> (E, &'a mut T)>;
+
+impl<E: IterableAssetEnum, T> EnumMap<E, T> {
+    /// Create a map by calling `f` for each enum variant in declaration order.
+    pub fn from_fn<F: FnMut(E) -> T>(f: F) -> Self {
+        EnumMap {
+            data: (0..E::CHECKSUMS.len()).map(E::from_index).map(f).collect(),
+            _spooky: core::marker::PhantomData,
+        }
+    }
+
+    /// Apply the mapping function to each enum variant and its value.
+    pub fn map_with_variant<U, F: Fn(E, &T) -> U>(&self, f: F) -> EnumMap<E, U> {
+        EnumMap {
+            data: self.iter().map(|(e, value)| f(e, value)).collect(),
+            _spooky: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns an iterator of all variants and their values in declaration order.
+    pub fn iter(&self) -> Iter<'_, E, T> {
+        self.data.iter().enumerate().map(|(i, value)| (E::from_index(i), value))
+    }
+
+    /// Returns an iterator of all variants and exclusive references to their values in declaration order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, E, T> {
+        self.data.iter_mut().enumerate().map(|(i, value)| (E::from_index(i), value))
+    }
+}
+
+impl<'a, E: IterableAssetEnum, T> IntoIterator for &'a EnumMap<E, T> {
+    type Item = (E, &'a T);
+    type IntoIter = Iter<'a, E, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, E: IterableAssetEnum, T> IntoIterator for &'a mut EnumMap<E, T> {
+    type Item = (E, &'a mut T);
+    type IntoIter = IterMut<'a, E, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
 impl<E: AssetEnum, T> core::ops::Index<E> for EnumMap<E, T> {
     type Output = T;
 
@@ -127,3 +744,27 @@ impl<E: AssetEnum, T> core::ops::IndexMut<E> for EnumMap<E, T> {
         &mut self.data[e.index()]
     }
 }
+
+impl<E: AssetEnum, T> core::ops::Index<&E> for EnumMap<E, T> {
+    type Output = T;
+
+    /// Look up the value for the given enum variant
+    fn index(&self, e: &E) -> &T {
+        &self.data[e.index()]
+    }
+}
+
+impl<E: AssetEnum, T> core::ops::IndexMut<&E> for EnumMap<E, T> {
+    /// Provide an exclusive reference to the value for the given enum variant
+    fn index_mut(&mut self, e: &E) -> &mut T {
+        &mut self.data[e.index()]
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<E: AssetEnum, T: serde::Serialize> serde::Serialize for EnumMap<E, T> {
+    /// Serializes the values as a sequence, ordered by enum variant.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.data)
+    }
+}
@@ -0,0 +1,172 @@
+use crate::checksum::Mismatch;
+
+/// Part of a compressed archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// Asset data
+    Data,
+    /// Asset names
+    Names,
+    /// Asset sizes
+    Sizes,
+}
+
+impl core::fmt::Display for Section {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Section::Data => write!(f, "asset data"),
+            Section::Names => write!(f, "asset names"),
+            Section::Sizes => write!(f, "asset sizes"),
+        }
+    }
+}
+
+/// Errors that might occur while loading (decompressing) an archive
+#[derive(Debug)]
+pub enum LoadError {
+    /// The codec failed to decompress a section of the archive
+    Decompression {
+        section: Section,
+        source: alloc::boxed::Box<dyn core::error::Error + Send + Sync + 'static>,
+    },
+    /// The codec failed to decompress an asset which is compressed on its own (option `per_asset` of `AssetEnum`)
+    AssetDecompression {
+        name: alloc::string::String,
+        source: alloc::boxed::Box<dyn core::error::Error + Send + Sync + 'static>,
+    },
+    /// The decompressed data of an asset doesn't match its checksum
+    ChecksumMismatch {
+        name: alloc::string::String,
+        mismatch: alloc::boxed::Box<Mismatch>,
+    },
+    /// The size of a section of the archive doesn't match what was declared
+    SizeMismatch { section: Section, expected: usize, actual: usize },
+    /// An asset name is not valid UTF-8
+    InvalidName { index: usize },
+    /// The perfect hash table doesn't match the asset names
+    InvalidPerfectHash,
+    /// The shard table doesn't match the asset data
+    InvalidShardTable,
+    /// The checksum size is not between 1 and 64 bytes, or doesn't divide the size of the checksums
+    InvalidChecksums,
+    /// The asset sizes are not valid varints, or their number doesn't match the number of assets
+    InvalidSizes,
+    /// The asset alignment is not a power of two no greater than [`MAX_ALIGN`](crate::named::MAX_ALIGN)
+    InvalidAlignment,
+    /// The metadata at the end of the data of a single-stream archive is truncated or inconsistent
+    InvalidMetadata,
+    /// Loading the archive would need more memory than allowed (see `NamedArchive::try_load_untrusted`)
+    MemoryLimit { needed: usize, limit: usize },
+    /// Two assets have the same name (only checked by `NamedArchive::try_load_untrusted`)
+    DuplicateName { name: alloc::string::String },
+    /// The signature of the archive is missing or doesn't match (see `NamedArchive::load_verified`)
+    #[cfg(feature = "signature")]
+    Signature(crate::signature::VerificationError),
+    /// Assets could not be read from the file system (only in `dev` mode)
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+impl core::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            LoadError::Decompression { section, source } => write!(f, "couldn't decompress {section}: {source}"),
+            LoadError::AssetDecompression { name, source } => write!(f, "couldn't decompress asset '{name}': {source}"),
+            LoadError::ChecksumMismatch { name, mismatch } => write!(f, "asset '{name}': {mismatch}"),
+            LoadError::SizeMismatch { section, expected, actual } => write!(f, "unexpected size of {section}: expected {expected}, got {actual}"),
+            LoadError::InvalidName { index } => write!(f, "name of asset #{index} is not valid UTF-8"),
+            LoadError::InvalidPerfectHash => write!(f, "perfect hash table doesn't match the asset names"),
+            LoadError::InvalidShardTable => write!(f, "shard table doesn't match the asset data"),
+            LoadError::InvalidChecksums => write!(f, "invalid checksum size"),
+            LoadError::InvalidSizes => write!(f, "asset sizes are invalid"),
+            LoadError::InvalidAlignment => write!(f, "invalid asset alignment"),
+            LoadError::InvalidMetadata => write!(f, "invalid archive metadata"),
+            LoadError::MemoryLimit { needed, limit } => write!(f, "loading the archive needs about {needed} bytes of memory, but only {limit} are allowed"),
+            LoadError::DuplicateName { name } => write!(f, "duplicate asset name '{name}'"),
+            #[cfg(feature = "signature")]
+            LoadError::Signature(err) => write!(f, "couldn't verify archive: {err}"),
+            #[cfg(feature = "std")]
+            LoadError::Io(err) => write!(f, "couldn't read assets: {err}"),
+        }
+    }
+}
+
+/// Error while deserializing an asset declared with the `deserialize` option of `AssetEnum`
+#[derive(Debug)]
+pub struct DeserializeError {
+    /// Name of the enum variant
+    pub variant: &'static str,
+    /// Error of the deserializer
+    pub source: alloc::boxed::Box<dyn core::error::Error + Send + Sync + 'static>,
+}
+
+impl core::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "couldn't deserialize asset {}: {}", self.variant, self.source)
+    }
+}
+
+impl core::error::Error for DeserializeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Error while converting an asset with `FromAsset` (see `EnumArchive::load_as`)
+#[derive(Debug)]
+pub struct FromAssetError {
+    /// Path of the asset, relative to the base path
+    pub path: &'static str,
+    /// Error of the conversion
+    pub source: alloc::boxed::Box<dyn core::error::Error + Send + Sync + 'static>,
+}
+
+impl core::fmt::Display for FromAssetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "couldn't convert asset {}: {}", self.path, self.source)
+    }
+}
+
+impl core::error::Error for FromAssetError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Error if an asset of an `AssetView` enum is missing from the archive it is bound to
+#[derive(Debug)]
+pub struct MissingAssetError {
+    /// Name of the missing asset
+    pub path: &'static str,
+}
+
+impl core::fmt::Display for MissingAssetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "asset {} is missing from the archive", self.path)
+    }
+}
+
+impl core::error::Error for MissingAssetError {}
+
+impl core::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            LoadError::Decompression { source, .. } | LoadError::AssetDecompression { source, .. } => Some(source.as_ref()),
+            LoadError::ChecksumMismatch { mismatch, .. } => Some(mismatch.as_ref()),
+            #[cfg(feature = "std")]
+            LoadError::Io(err) => Some(err),
+            #[cfg(feature = "signature")]
+            LoadError::Signature(err) => Some(err),
+            LoadError::SizeMismatch { .. }
+            | LoadError::InvalidName { .. }
+            | LoadError::InvalidPerfectHash
+            | LoadError::InvalidShardTable
+            | LoadError::InvalidSizes
+            | LoadError::InvalidChecksums
+            | LoadError::InvalidAlignment
+            | LoadError::InvalidMetadata
+            | LoadError::MemoryLimit { .. }
+            | LoadError::DuplicateName { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+// C interface to named archives, for applications mixing C and Rust.
+// User-facing documentation is in the include_assets crate.
+
+#![allow(unsafe_code)] // a C interface deals in raw pointers
+
+use crate::named::NamedArchive;
+
+/// Write `len` to `out` unless it is null, and return the pointer to `data`.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writing a `usize`.
+unsafe fn with_len(data: &[u8], out: *mut usize) -> *const u8 {
+    if !out.is_null() {
+        // SAFETY: the caller ensures that `out` is valid for writes
+        unsafe { out.write(data.len()) };
+    }
+    data.as_ptr()
+}
+
+/// Look up the asset `name` in `archive`, where `name` is passed as pointer and length.
+///
+/// Returns a pointer to the asset data, and writes its length to `len` unless it is null.
+/// Returns null (and leaves `len` untouched) if there is no such asset, the name is not UTF-8, or `archive` or `name` are null.
+/// The data is not null-terminated.
+///
+/// # Safety
+///
+/// - `archive` must be null or a pointer returned by a function declared with `include_dir_ffi!`.
+/// - `name` must be null or valid for reading `name_len` bytes.
+/// - `len` must be null or valid for writing a `usize`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn include_assets_get_n(archive: *const NamedArchive, name: *const u8, name_len: usize, len: *mut usize) -> *const u8 {
+    if archive.is_null() || name.is_null() {
+        return core::ptr::null();
+    }
+    // SAFETY: the caller ensures that both pointers are valid, and archives returned by `include_dir_ffi!` live for the rest of the program
+    let (archive, name) = unsafe { (&*archive, core::slice::from_raw_parts(name, name_len)) };
+    match core::str::from_utf8(name).ok().and_then(|name| archive.get(name)) {
+        // SAFETY: the caller ensures that `len` is valid
+        Some(data) => unsafe { with_len(data, len) },
+        None => core::ptr::null(),
+    }
+}
+
+/// Look up the asset `name` in `archive`, where `name` is a null-terminated string.
+///
+/// See [`include_assets_get_n`].
+///
+/// # Safety
+///
+/// - `archive` must be null or a pointer returned by a function declared with `include_dir_ffi!`.
+/// - `name` must be null or a valid null-terminated string.
+/// - `len` must be null or valid for writing a `usize`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn include_assets_get(archive: *const NamedArchive, name: *const core::ffi::c_char, len: *mut usize) -> *const u8 {
+    if name.is_null() {
+        return core::ptr::null();
+    }
+    // SAFETY: the caller ensures that `name` is a valid null-terminated string
+    let name = unsafe { core::ffi::CStr::from_ptr(name) }.to_bytes();
+    // SAFETY: the caller ensures that `archive` and `len` are valid
+    unsafe { include_assets_get_n(archive, name.as_ptr(), name.len(), len) }
+}
+
+/// Returns the number of assets in `archive`, or 0 if `archive` is null.
+///
+/// # Safety
+///
+/// `archive` must be null or a pointer returned by a function declared with `include_dir_ffi!`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn include_assets_count(archive: *const NamedArchive) -> usize {
+    // SAFETY: the caller ensures that `archive` is valid
+    unsafe { archive.as_ref() }.map_or(0, NamedArchive::number_of_assets)
+}
+
+/// Get the name of the asset with the given `index` (less than [`include_assets_count`]), in unspecified order.
+///
+/// Returns a pointer to the name, and writes its length in bytes to `len` unless it is null.
+/// Returns null (and leaves `len` untouched) if `index` is out of range or `archive` is null.
+/// The name is UTF-8 and not null-terminated.
+///
+/// # Safety
+///
+/// - `archive` must be null or a pointer returned by a function declared with `include_dir_ffi!`.
+/// - `len` must be null or valid for writing a `usize`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn include_assets_name(archive: *const NamedArchive, index: usize, len: *mut usize) -> *const u8 {
+    // SAFETY: the caller ensures that `archive` is valid
+    match unsafe { archive.as_ref() }.and_then(|archive| archive.name_at(index)) {
+        // SAFETY: the caller ensures that `len` is valid
+        Some(name) => unsafe { with_len(name.as_bytes(), len) },
+        None => core::ptr::null(),
+    }
+}
@@ -0,0 +1,486 @@
+//! Archive files
+//!
+//! Named archives can be stored in a file (e.g. shipped next to the executable) instead of being embedded in the executable.
+//! Such files are written with [`CompressedNamedArchive::write_to`] and read with [`NamedArchive::load_file`](crate::named::NamedArchive::load_file).
+//!
+//! The same format is used for archives which `include_dir!` places in a link section (option `link_section`),
+//! so that tools can find them in a built executable with [`find`].
+//! Apart from [`CompressedNamedArchive::to_file_bytes`], this module requires feature `std`.
+//!
+//! # Format
+//!
+//! All integers are little endian.
+//!
+//! | field                                     | type                               |
+//! |-------------------------------------------|------------------------------------|
+//! | magic bytes                               | `b"INCASSET"`                      |
+//! | format version (currently 6)              | `u32`                              |
+//! | codec tag (see [`AnyCodec::tag`])         | `u32`                              |
+//! | codec level (see [`AnyCodec::level`])     | `i32`                              |
+//! | number of assets                          | `u32`                              |
+//! | uncompressed data size                    | `u32`                              |
+//! | uncompressed names size                   | `u32`                              |
+//! | uncompressed sizes size (since version 3) | `u32`                              |
+//! | checksum size (since version 4)           | `u32`                              |
+//! | asset alignment (since version 5)         | `u32`                              |
+//! | metadata size (since version 6)           | `u32`                              |
+//! | compressed data size                      | `u64`                              |
+//! | compressed names size                     | `u64`                              |
+//! | compressed sizes size                     | `u64`                              |
+//! | shard table size (not in version 1)       | `u64`                              |
+//! | signature present (0 or 1)                | `u32`                              |
+//! | checksums                                 | checksum size bytes for each asset |
+//! | signature (only if present)               | `[u8; 64]`                         |
+//! | compressed data                           | bytes                              |
+//! | compressed names                          | bytes                              |
+//! | compressed sizes                          | bytes                              |
+//! | shard table (not in version 1)            | bytes                              |
+//!
+//! The meaning of the fields is the same as for [`CompressedNamedArchive`].
+//! Files of version 1 can still be read, they contain no shard table.
+//! Files of version 1 and 2 store the asset sizes as `u32`s instead of varints, they are read with an uncompressed sizes size of 0.
+//! Files before version 4 always have checksums of 64 bytes, and files before version 5 have no aligned assets.
+//! Files before version 6 always store names, sizes, and checksums in separate sections, they are read with a metadata size of 0.
+//! If the metadata is stored at the end of the data instead, the number of assets in the header is 0.
+//! MIME type overrides and perfect hash tables are not stored.
+
+use crate::codec::{AnyCodec, Codec};
+use crate::named::CompressedNamedArchive;
+
+/// Magic bytes at the start of every archive file
+pub const MAGIC: &[u8; 8] = b"INCASSET";
+
+/// Current version of the archive file format
+pub const VERSION: u32 = 6;
+
+#[cfg(feature = "std")]
+fn invalid_data(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("invalid archive file: {msg}"))
+}
+
+/// Helper for reading the fields of an archive file from a byte slice
+#[cfg(feature = "std")]
+struct Fields<'a> {
+    bytes: &'a [u8],
+}
+
+#[cfg(feature = "std")]
+impl<'a> Fields<'a> {
+    fn take(&mut self, n: usize) -> std::io::Result<&'a [u8]> {
+        if self.bytes.len() < n {
+            return Err(invalid_data("unexpected end of file"));
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> std::io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("slice has length 4")))
+    }
+
+    fn i32(&mut self) -> std::io::Result<i32> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().expect("slice has length 4")))
+    }
+
+    fn len(&mut self) -> std::io::Result<usize> {
+        let len = u64::from_le_bytes(self.take(8)?.try_into().expect("slice has length 8"));
+        usize::try_from(len).map_err(|_| invalid_data("section too large"))
+    }
+}
+
+/// Parse an archive file which has been read into memory.
+///
+/// No data is decompressed, the returned archive borrows from `bytes`.
+#[cfg(feature = "std")]
+pub fn parse(bytes: &[u8]) -> std::io::Result<CompressedNamedArchive<'_, AnyCodec>> {
+    let (archive, len) = parse_prefix(bytes)?;
+    if len != bytes.len() {
+        return Err(invalid_data("trailing bytes"));
+    }
+    Ok(archive)
+}
+
+/// Find the archives embedded in a bigger file, e.g. those which `include_dir!` placed in a link section of an executable.
+///
+/// Returns the offset of each archive in `bytes` and the archive, which borrows from `bytes`.
+/// Archives are found by their magic bytes, occurrences of which are skipped if they aren't followed by a valid archive header.
+#[cfg(feature = "std")]
+pub fn find(bytes: &[u8]) -> std::vec::Vec<(usize, CompressedNamedArchive<'_, AnyCodec>)> {
+    let mut archives = vec![];
+    let mut offset = 0;
+    while let Some(position) = bytes[offset..].windows(MAGIC.len()).position(|window| window == MAGIC) {
+        let start = offset + position;
+        match parse_prefix(&bytes[start..]) {
+            Ok((archive, len)) => {
+                archives.push((start, archive));
+                offset = start + len;
+            }
+            Err(_) => offset = start + 1,
+        }
+    }
+    archives
+}
+
+/// Parse an archive file at the start of `bytes`, returning the archive and its length in bytes.
+#[cfg(feature = "std")]
+fn parse_prefix(bytes: &[u8]) -> std::io::Result<(CompressedNamedArchive<'_, AnyCodec>, usize)> {
+    let mut fields = Fields { bytes };
+    if fields.take(MAGIC.len())? != MAGIC {
+        return Err(invalid_data("wrong magic bytes"));
+    }
+    let version = fields.u32()?;
+    if !(1..=VERSION).contains(&version) {
+        return Err(invalid_data(&format!("unsupported version {version}")));
+    }
+    let tag = fields.u32()?;
+    let level = fields.i32()?;
+    let codec = AnyCodec::from_tag_and_level(tag, level).ok_or_else(|| invalid_data(&format!("unsupported codec {tag} with level {level}")))?;
+    let number_of_assets = crate::common::u32_to_usize(fields.u32()?);
+    let uncompressed_data_size = fields.u32()?;
+    let uncompressed_names_size = fields.u32()?;
+    let uncompressed_sizes_size = if version >= 3 { fields.u32()? } else { 0 };
+    let checksum_size = if version >= 4 { fields.u32()? } else { 64 };
+    let align = if version >= 5 { fields.u32()? } else { 1 };
+    let metadata_size = if version >= 6 { fields.u32()? } else { 0 };
+    let data_len = fields.len()?;
+    let names_len = fields.len()?;
+    let sizes_len = fields.len()?;
+    let shards_len = if version >= 2 { fields.len()? } else { 0 };
+    if !(1..=64).contains(&checksum_size) {
+        return Err(invalid_data(&format!("invalid checksum size {checksum_size}")));
+    }
+    let checksums_len = number_of_assets
+        .checked_mul(crate::common::u32_to_usize(checksum_size))
+        .ok_or_else(|| invalid_data("too many assets"))?;
+    let has_signature = match fields.u32()? {
+        0 => false,
+        1 => true,
+        _ => return Err(invalid_data("invalid signature flag")),
+    };
+    let checksums = fields.take(checksums_len)?;
+    let signature = if has_signature {
+        Some(fields.take(64)?.try_into().expect("slice has length 64"))
+    } else {
+        None
+    };
+    let data = fields.take(data_len)?;
+    let compressed_names = fields.take(names_len)?;
+    let compressed_sizes = fields.take(sizes_len)?;
+    let shards = fields.take(shards_len)?;
+    let len = bytes.len() - fields.bytes.len();
+    let archive = CompressedNamedArchive {
+        codec,
+        data,
+        uncompressed_data_size,
+        compressed_names,
+        uncompressed_names_size,
+        checksums,
+        checksum_size,
+        compressed_sizes,
+        uncompressed_sizes_size,
+        align,
+        metadata_size,
+        signature,
+        directory: None,
+        mime_types: &[],
+        private: &[],
+        perfect_hash: None,
+        shards,
+    };
+    Ok((archive, len))
+}
+
+impl<C: Codec + Copy + Into<AnyCodec>> CompressedNamedArchive<'_, C> {
+    /// Everything before the compressed data in the archive file, or `None` if there are too many assets or the checksum size is invalid
+    fn file_header(&self) -> Option<alloc::vec::Vec<u8>> {
+        let codec: AnyCodec = self.codec.into();
+        if !(1..=64).contains(&self.checksum_size) || !self.checksums.len().is_multiple_of(crate::common::u32_to_usize(self.checksum_size)) {
+            return None;
+        }
+        let number_of_assets = u32::try_from(self.checksums.len() / crate::common::u32_to_usize(self.checksum_size)).ok()?;
+        let mut header = vec![];
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&codec.tag().to_le_bytes());
+        header.extend_from_slice(&codec.level().to_le_bytes());
+        header.extend_from_slice(&number_of_assets.to_le_bytes());
+        header.extend_from_slice(&self.uncompressed_data_size.to_le_bytes());
+        header.extend_from_slice(&self.uncompressed_names_size.to_le_bytes());
+        header.extend_from_slice(&self.uncompressed_sizes_size.to_le_bytes());
+        header.extend_from_slice(&self.checksum_size.to_le_bytes());
+        header.extend_from_slice(&self.align.to_le_bytes());
+        header.extend_from_slice(&self.metadata_size.to_le_bytes());
+        for section in [self.data, self.compressed_names, self.compressed_sizes, self.shards] {
+            header.extend_from_slice(&(section.len() as u64).to_le_bytes());
+        }
+        header.extend_from_slice(&u32::from(self.signature.is_some()).to_le_bytes());
+        header.extend_from_slice(self.checksums);
+        if let Some(signature) = self.signature {
+            header.extend_from_slice(signature);
+        }
+        Some(header)
+    }
+
+    /// Write the compressed archive to an archive file.
+    ///
+    /// The file can later be loaded using [`NamedArchive::load_file`](crate::named::NamedArchive::load_file).
+    /// See [`crate::file`] for a description of the format.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        if self.directory.is_some() {
+            return Err(std::io::Error::other("archives in dev mode contain no assets and cannot be written to a file"));
+        }
+        let header = self.file_header().ok_or_else(|| invalid_data("too many assets or invalid checksum size"))?;
+        writer.write_all(&header)?;
+        for section in [self.data, self.compressed_names, self.compressed_sizes, self.shards] {
+            writer.write_all(section)?;
+        }
+        Ok(())
+    }
+
+    /// The compressed archive in the archive file format, as written by [`CompressedNamedArchive::write_to`].
+    ///
+    /// Returns `None` if the archive is in dev mode (and contains no assets), has more than `u32::MAX` assets, or has an invalid checksum size.
+    pub fn to_file_bytes(&self) -> Option<alloc::vec::Vec<u8>> {
+        if self.directory.is_some() {
+            return None;
+        }
+        let mut bytes = self.file_header()?;
+        for section in [self.data, self.compressed_names, self.compressed_sizes, self.shards] {
+            bytes.extend_from_slice(section);
+        }
+        Some(bytes)
+    }
+}
+
+/// Memory-mapped archive file
+///
+/// The compressed data is not read into main memory, the operating system pages it in as needed.
+/// [`MappedArchive::get`] reads single assets without decompressing the whole archive if possible:
+/// assets of uncompressed archives are served in place from the map,
+/// and of archives compressed in shards (option `shard_size` of `include_dir!`) only the shards containing the asset are decompressed, each at most once.
+/// Archives compressed as a single stream, and compressed archives with aligned assets, are decompressed as a whole on first access instead.
+/// Checksums are verified per asset, the first time each asset is accessed (see [`NamedArchive::try_load_lazy`](crate::named::NamedArchive::try_load_lazy)).
+#[cfg(feature = "mmap")]
+pub struct MappedArchive {
+    map: memmap2::Mmap,
+    index: Option<AssetIndex>,
+    archive: std::sync::OnceLock<crate::named::NamedArchive>,
+}
+
+/// Data of a mapped archive, decompressed one shard at a time
+#[cfg(feature = "mmap")]
+struct MappedData {
+    codec: AnyCodec,
+    /// Position of the (compressed) data in the map
+    position: core::ops::Range<usize>,
+    /// Shards of the data, each decompressed on first access, or empty if the data is uncompressed
+    shards: std::vec::Vec<(crate::common::Shard, std::sync::OnceLock<std::boxed::Box<[u8]>>)>,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedData {
+    /// Read the `range` of the uncompressed data, borrowing it unless it spans several shards.
+    fn read<'a>(&'a self, map: &'a [u8], range: core::ops::Range<usize>) -> Result<alloc::borrow::Cow<'a, [u8]>, crate::error::LoadError> {
+        let data = &map[self.position.clone()];
+        if range.is_empty() {
+            return Ok(alloc::borrow::Cow::Borrowed(&[]));
+        }
+        if self.shards.is_empty() {
+            return Ok(alloc::borrow::Cow::Borrowed(&data[range]));
+        }
+        let first = self.shards.partition_point(|((_, uncompressed), _)| uncompressed.end <= range.start);
+        let mut parts = self.shards[first..]
+            .iter()
+            .take_while(|((_, uncompressed), _)| uncompressed.start < range.end)
+            .map(|((compressed, uncompressed), cache)| {
+                let shard = match cache.get() {
+                    Some(shard) => shard,
+                    None => {
+                        let shard =
+                            crate::common::decompress_section(&self.codec, &data[compressed.clone()], uncompressed.len(), crate::error::Section::Data)?;
+                        cache.get_or_init(|| shard.into_boxed_slice())
+                    }
+                };
+                let start = range.start.max(uncompressed.start) - uncompressed.start;
+                let end = range.end.min(uncompressed.end) - uncompressed.start;
+                Ok::<_, crate::error::LoadError>(&shard[start..end])
+            });
+        let first = parts.next().expect("ranges of the data end where the shards end")?;
+        let Some(second) = parts.next() else {
+            return Ok(alloc::borrow::Cow::Borrowed(first));
+        };
+        let mut joined = std::vec::Vec::with_capacity(range.len());
+        joined.extend_from_slice(first);
+        joined.extend_from_slice(second?);
+        for part in parts {
+            joined.extend_from_slice(part?);
+        }
+        Ok(alloc::borrow::Cow::Owned(joined))
+    }
+}
+
+/// Positions and checksums of the assets in a mapped archive, to read them without decompressing the whole archive
+#[cfg(feature = "mmap")]
+struct AssetIndex {
+    data: MappedData,
+    /// Data range and index of each asset
+    entries: crate::named::AssetPositions,
+    checksums: std::vec::Vec<u8>,
+    checksum_size: usize,
+    verified: std::vec::Vec<core::sync::atomic::AtomicBool>,
+    /// Data of assets which span several shards, joined on first access
+    joined: std::vec::Vec<std::sync::OnceLock<std::boxed::Box<[u8]>>>,
+}
+
+#[cfg(feature = "mmap")]
+impl AssetIndex {
+    /// Index the assets of `compressed`, which starts at `map`, or `None` if only the whole archive can be decompressed
+    fn new(map: &[u8], compressed: &CompressedNamedArchive<'_, AnyCodec>) -> Result<Option<Self>, crate::error::LoadError> {
+        use crate::common::u32_to_usize;
+        use crate::error::{LoadError, Section};
+
+        let align = u32_to_usize(compressed.align).max(1);
+        let uncompressed_data_size = u32_to_usize(compressed.uncompressed_data_size);
+        let shards = if let AnyCodec::Uncompressed(_) = compressed.codec {
+            if compressed.data.as_ptr().align_offset(align) != 0 {
+                return Ok(None);
+            }
+            if compressed.data.len() != uncompressed_data_size {
+                return Err(LoadError::SizeMismatch {
+                    section: Section::Data,
+                    expected: uncompressed_data_size,
+                    actual: compressed.data.len(),
+                });
+            }
+            std::vec::Vec::new()
+        } else if compressed.shards.is_empty() || align > 1 {
+            return Ok(None);
+        } else {
+            let shards = crate::common::shards(compressed.shards, compressed.data.len(), uncompressed_data_size)?;
+            shards.into_iter().map(|shard| (shard, std::sync::OnceLock::new())).collect()
+        };
+        let start = compressed.data.as_ptr() as usize - map.as_ptr() as usize;
+        let data = MappedData {
+            codec: compressed.codec,
+            position: start..start + compressed.data.len(),
+            shards,
+        };
+
+        let assets_size = u32_to_usize(
+            compressed
+                .uncompressed_data_size
+                .checked_sub(compressed.metadata_size)
+                .ok_or(LoadError::InvalidMetadata)?,
+        );
+        let trailer = data.read(map, assets_size..uncompressed_data_size)?;
+        let (entries, checksums) = compressed.asset_index(&trailer)?;
+        let number_of_assets = entries.len();
+        Ok(Some(Self {
+            data,
+            entries,
+            checksums,
+            checksum_size: u32_to_usize(compressed.checksum_size),
+            verified: (0..number_of_assets).map(|_| core::sync::atomic::AtomicBool::new(false)).collect(),
+            joined: (0..number_of_assets).map(|_| std::sync::OnceLock::new()).collect(),
+        }))
+    }
+
+    /// Read and verify the asset with the given `name`.
+    fn get<'a>(&'a self, map: &'a [u8], name: &str) -> Result<Option<&'a [u8]>, crate::error::LoadError> {
+        let Some((range, i)) = self.entries.get(name) else {
+            return Ok(None);
+        };
+        let data = match self.joined[*i].get() {
+            Some(data) => data,
+            None => match self.data.read(map, crate::common::u32_to_usize_range(range))? {
+                alloc::borrow::Cow::Borrowed(data) => data,
+                alloc::borrow::Cow::Owned(data) => self.joined[*i].get_or_init(|| data.into_boxed_slice()),
+            },
+        };
+        // the data never changes, so verifying it twice on concurrent first accesses is harmless
+        if !self.verified[*i].load(core::sync::atomic::Ordering::Relaxed) {
+            let expected = &self.checksums[i * self.checksum_size..(i + 1) * self.checksum_size];
+            crate::checksum::check(data, expected).map_err(|mismatch| crate::error::LoadError::ChecksumMismatch {
+                name: name.into(),
+                mismatch: std::boxed::Box::new(mismatch),
+            })?;
+            self.verified[*i].store(true, core::sync::atomic::Ordering::Relaxed);
+        }
+        Ok(Some(data))
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl MappedArchive {
+    /// Map an archive file into memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be mapped or is not a valid archive file.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified (or truncated) while it is mapped, by this or any other process, since Rust assumes the mapped data to be immutable.
+    #[allow(unsafe_code)]
+    pub unsafe fn open<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the map is read-only, the caller ensures that the file isn't modified while it is mapped
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        let index = AssetIndex::new(&map, &parse(&map)?).map_err(|err| invalid_data(&err.to_string()))?;
+        Ok(Self {
+            map,
+            index,
+            archive: std::sync::OnceLock::new(),
+        })
+    }
+
+    /// The compressed archive, borrowed from the memory map
+    pub fn compressed(&self) -> CompressedNamedArchive<'_, AnyCodec> {
+        parse(&self.map).expect("archive file was validated when it was opened")
+    }
+
+    /// The decompressed archive
+    ///
+    /// Decompresses the whole archive on first call, independently of the assets read with [`MappedArchive::get`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the archive data is corrupted, use [`MappedArchive::try_archive`] to handle this error instead.
+    pub fn archive(&self) -> &crate::named::NamedArchive {
+        match self.try_archive() {
+            Ok(archive) => archive,
+            Err(err) => panic!("couldn't load asset archive: {err}"),
+        }
+    }
+
+    /// The decompressed archive, or an error if decompressing it fails
+    ///
+    /// Decompresses the archive on first successful call, see [`MappedArchive::archive`].
+    pub fn try_archive(&self) -> Result<&crate::named::NamedArchive, crate::error::LoadError> {
+        if let Some(archive) = self.archive.get() {
+            return Ok(archive);
+        }
+        let archive = crate::named::NamedArchive::try_load_lazy(self.compressed())?;
+        Ok(self.archive.get_or_init(|| archive))
+    }
+
+    /// Get the content of the asset with the given `name`, decompressing only what is necessary (see [`MappedArchive`]).
+    ///
+    /// Panics if the archive or the asset is corrupted, use [`MappedArchive::try_get`] to handle this error instead.
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        match self.try_get(name) {
+            Ok(data) => data,
+            Err(err) => panic!("couldn't load asset: {err}"),
+        }
+    }
+
+    /// Get the content of the asset with the given `name`, or an error if the archive or the asset is corrupted.
+    pub fn try_get(&self, name: &str) -> Result<Option<&[u8]>, crate::error::LoadError> {
+        match &self.index {
+            Some(index) => index.get(&self.map, name),
+            None => self.try_archive()?.try_get(name),
+        }
+    }
+}
@@ -0,0 +1,670 @@
+// Read-only FUSE file system serving the assets of a named archive, on Linux.
+// User-facing documentation is in the include_assets crate.
+//
+// This speaks the FUSE kernel protocol (see `include/uapi/linux/fuse.h` in the Linux sources) over `/dev/fuse` directly.
+// Only requests needed for reading are implemented, all others fail with `ENOSYS`.
+// Integers are in native byte order.
+
+use crate::named::NamedArchive;
+
+const ROOT: u64 = 1;
+
+// opcodes
+const LOOKUP: u32 = 1;
+const FORGET: u32 = 2;
+const GETATTR: u32 = 3;
+const OPEN: u32 = 14;
+const READ: u32 = 15;
+const STATFS: u32 = 17;
+const RELEASE: u32 = 18;
+const FLUSH: u32 = 25;
+const INIT: u32 = 26;
+const OPENDIR: u32 = 27;
+const READDIR: u32 = 28;
+const RELEASEDIR: u32 = 29;
+const ACCESS: u32 = 34;
+const INTERRUPT: u32 = 36;
+const DESTROY: u32 = 38;
+const BATCH_FORGET: u32 = 42;
+
+/// Size of `struct fuse_in_header`
+const IN_HEADER_SIZE: usize = 40;
+/// Size of `struct fuse_out_header`
+const OUT_HEADER_SIZE: usize = 16;
+/// Size of `struct fuse_attr`
+const ATTR_SIZE: usize = 88;
+/// Size of `struct fuse_entry_out`, which ends with a `struct fuse_attr`
+const ENTRY_OUT_SIZE: usize = 40 + ATTR_SIZE;
+/// Size of `struct fuse_attr_out`, which ends with a `struct fuse_attr`
+const ATTR_OUT_SIZE: usize = 16 + ATTR_SIZE;
+/// Size of `struct fuse_init_out` since protocol 7.23
+const INIT_OUT_SIZE: usize = 64;
+/// Size of `struct fuse_init_out` before protocol 7.23
+const COMPAT_22_INIT_OUT_SIZE: usize = 24;
+/// Size of `struct fuse_init_out` before protocol 7.5
+const COMPAT_INIT_OUT_SIZE: usize = 8;
+/// Size of `struct fuse_open_out`
+const OPEN_OUT_SIZE: usize = 16;
+/// Size of `struct fuse_dirent` without the name
+const DIRENT_SIZE: usize = 24;
+/// Size of `struct fuse_kstatfs`
+const KSTATFS_SIZE: usize = 80;
+/// Maximum size of read requests, which is also used for writes
+const MAX_READ: u32 = 128 * 1024;
+/// Buffer size for requests, which must exceed the maximum size of writes
+const BUFFER_SIZE: usize = MAX_READ as usize + 4096;
+/// How long the kernel may cache names and attributes; they never change
+const TTL_SECONDS: u64 = 365 * 24 * 60 * 60;
+/// Flag for open replies telling the kernel to keep cached file data
+const FOPEN_KEEP_CACHE: u32 = 1 << 1;
+
+/// File or directory in the mounted file system, whose inode number is its index plus one
+enum Node {
+    File(&'static [u8]),
+    /// Entries of the directory by name, with their inode numbers
+    Dir(std::collections::BTreeMap<&'static str, u64>),
+}
+
+/// Inode tree of an archive
+struct Tree {
+    nodes: std::vec::Vec<Node>,
+    /// Inode number of the parent directory of each node, the root is its own parent
+    parents: std::vec::Vec<u64>,
+    modified: std::time::SystemTime,
+}
+
+impl Tree {
+    fn new(archive: &'static NamedArchive) -> Self {
+        let mut nodes = vec![Node::Dir(Default::default())];
+        let mut parents = vec![ROOT];
+        let mut names: std::vec::Vec<&'static str> = archive.names().collect();
+        names.sort_unstable();
+        for name in names {
+            let data = archive.get(name).expect("name is in the archive");
+            let mut dir = ROOT;
+            let mut components = name.split('/').filter(|component| !component.is_empty()).peekable();
+            while let Some(component) = components.next() {
+                let is_file = components.peek().is_none();
+                let next = nodes.len() as u64 + 1;
+                let Node::Dir(entries) = &mut nodes[(dir - 1) as usize] else {
+                    break; // a file and a directory with the same name, the file wins
+                };
+                let ino = *entries.entry(component).or_insert(next);
+                if ino == next {
+                    nodes.push(if is_file { Node::File(data) } else { Node::Dir(Default::default()) });
+                    parents.push(dir);
+                }
+                dir = ino;
+            }
+        }
+        Self {
+            nodes,
+            parents,
+            modified: archive.modified(),
+        }
+    }
+
+    fn node(&self, ino: u64) -> Option<&Node> {
+        ino.checked_sub(1).and_then(|index| self.nodes.get(usize::try_from(index).ok()?))
+    }
+
+    /// `struct fuse_attr`
+    fn attr(&self, ino: u64, node: &Node, out: &mut std::vec::Vec<u8>) {
+        let start = out.len();
+        let (size, mode, nlink) = match node {
+            Node::File(data) => (data.len() as u64, libc::S_IFREG | 0o444, 1u32),
+            Node::Dir(entries) => (
+                0,
+                libc::S_IFDIR | 0o555,
+                2 + entries.values().filter(|&&child| matches!(self.node(child), Some(Node::Dir(_)))).count() as u32,
+            ),
+        };
+        let time = self.modified.duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+        for value in [ino, size, size.div_ceil(512), time.as_secs(), time.as_secs(), time.as_secs()] {
+            out.extend_from_slice(&value.to_ne_bytes());
+        }
+        // SAFETY: getuid and getgid cannot fail
+        #[allow(unsafe_code)]
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        for value in [time.subsec_nanos(), time.subsec_nanos(), time.subsec_nanos(), mode, nlink, uid, gid, 0, 4096, 0] {
+            out.extend_from_slice(&value.to_ne_bytes());
+        }
+        debug_assert_eq!(out.len() - start, ATTR_SIZE);
+    }
+
+    /// `struct fuse_entry_out`
+    fn entry(&self, ino: u64, node: &Node, out: &mut std::vec::Vec<u8>) {
+        let start = out.len();
+        for value in [ino, 0, TTL_SECONDS, TTL_SECONDS] {
+            out.extend_from_slice(&value.to_ne_bytes());
+        }
+        out.extend_from_slice(&[0; 8]);
+        self.attr(ino, node, out);
+        debug_assert_eq!(out.len() - start, ENTRY_OUT_SIZE);
+    }
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(bytes.get(offset..offset + 4)?.try_into().expect("slice has length 4")))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_ne_bytes(bytes.get(offset..offset + 8)?.try_into().expect("slice has length 8")))
+}
+
+/// Handle a request, returning the reply payload or an error number, or `None` if the request has no reply.
+fn handle(tree: &Tree, opcode: u32, ino: u64, arg: &[u8]) -> Option<Result<std::vec::Vec<u8>, i32>> {
+    let mut out = vec![];
+    let result = match opcode {
+        FORGET | BATCH_FORGET | INTERRUPT => return None,
+        INIT => {
+            let (major, minor) = (u32_at(arg, 0)?, u32_at(arg, 4)?);
+            if major < 7 {
+                Err(libc::EPROTO)
+            } else {
+                // struct fuse_init_out for protocol 7.31, without any optional features
+                let minor = minor.min(31);
+                for value in [7, minor, MAX_READ, 0] {
+                    out.extend_from_slice(&u32::to_ne_bytes(value));
+                }
+                out.extend_from_slice(&[0; 4]); // max_background, congestion_threshold
+                out.extend_from_slice(&MAX_READ.to_ne_bytes()); // max_write
+                out.extend_from_slice(&1u32.to_ne_bytes()); // time_gran
+                                                            // older kernels expect the reply of their protocol version, without the fields added later
+                out.resize(
+                    match minor {
+                        0..5 => COMPAT_INIT_OUT_SIZE,
+                        5..23 => COMPAT_22_INIT_OUT_SIZE,
+                        _ => INIT_OUT_SIZE,
+                    },
+                    0,
+                );
+                Ok(())
+            }
+        }
+        LOOKUP => {
+            let name = arg.split(|&byte| byte == 0).next().unwrap_or_default();
+            let child = match (tree.node(ino), std::str::from_utf8(name)) {
+                (Some(Node::Dir(entries)), Ok(name)) => entries.get(name).copied(),
+                _ => None,
+            };
+            match child.and_then(|child| Some((child, tree.node(child)?))) {
+                Some((child, node)) => {
+                    tree.entry(child, node, &mut out);
+                    Ok(())
+                }
+                None => Err(libc::ENOENT),
+            }
+        }
+        GETATTR => match tree.node(ino) {
+            Some(node) => {
+                out.extend_from_slice(&TTL_SECONDS.to_ne_bytes());
+                out.extend_from_slice(&[0; 8]);
+                tree.attr(ino, node, &mut out);
+                debug_assert_eq!(out.len(), ATTR_OUT_SIZE);
+                Ok(())
+            }
+            None => Err(libc::ENOENT),
+        },
+        OPEN | OPENDIR => {
+            let flags = u32_at(arg, 0)? as i32;
+            match tree.node(ino) {
+                None => Err(libc::ENOENT),
+                Some(_) if flags & libc::O_ACCMODE != libc::O_RDONLY => Err(libc::EROFS),
+                Some(Node::File(_)) if opcode == OPENDIR => Err(libc::ENOTDIR),
+                Some(Node::Dir(_)) if opcode == OPEN => Err(libc::EISDIR),
+                Some(_) => {
+                    out.extend_from_slice(&0u64.to_ne_bytes()); // file handle
+                    out.extend_from_slice(&FOPEN_KEEP_CACHE.to_ne_bytes());
+                    out.extend_from_slice(&[0; 4]);
+                    debug_assert_eq!(out.len(), OPEN_OUT_SIZE);
+                    Ok(())
+                }
+            }
+        }
+        READ => {
+            let (offset, size) = (u64_at(arg, 8)?, u32_at(arg, 16)?);
+            match tree.node(ino) {
+                Some(Node::File(data)) => {
+                    let start = usize::try_from(offset).unwrap_or(usize::MAX).min(data.len());
+                    let end = start.saturating_add(size as usize).min(data.len());
+                    out.extend_from_slice(&data[start..end]);
+                    Ok(())
+                }
+                Some(Node::Dir(_)) => Err(libc::EISDIR),
+                None => Err(libc::ENOENT),
+            }
+        }
+        READDIR => {
+            let (offset, size) = (u64_at(arg, 8)?, u32_at(arg, 16)? as usize);
+            match tree.node(ino) {
+                Some(Node::Dir(entries)) => {
+                    // the offset of an entry is the index of the next one, after "." and ".."
+                    let parent = tree.parents[(ino - 1) as usize];
+                    let dots = [(".", ino), ("..", parent)];
+                    let all = dots.into_iter().chain(entries.iter().map(|(name, child)| (*name, *child)));
+                    for (index, (name, child)) in all.enumerate().skip(usize::try_from(offset).unwrap_or(usize::MAX)) {
+                        // struct fuse_dirent, padded to 8 bytes
+                        let len = (DIRENT_SIZE + name.len()).next_multiple_of(8);
+                        if out.len() + len > size {
+                            break;
+                        }
+                        let kind = match tree.node(child) {
+                            Some(Node::File(_)) => libc::DT_REG,
+                            _ => libc::DT_DIR,
+                        };
+                        out.extend_from_slice(&child.to_ne_bytes());
+                        out.extend_from_slice(&(index as u64 + 1).to_ne_bytes());
+                        out.extend_from_slice(&(name.len() as u32).to_ne_bytes());
+                        out.extend_from_slice(&u32::from(kind).to_ne_bytes());
+                        out.extend_from_slice(name.as_bytes());
+                        out.resize(out.len().next_multiple_of(8), 0);
+                    }
+                    Ok(())
+                }
+                Some(Node::File(_)) => Err(libc::ENOTDIR),
+                None => Err(libc::ENOENT),
+            }
+        }
+        STATFS => {
+            // struct fuse_kstatfs
+            let blocks: u64 = tree
+                .nodes
+                .iter()
+                .map(|node| if let Node::File(data) = node { (data.len() as u64).div_ceil(512) } else { 0 })
+                .sum();
+            for value in [blocks, 0, 0, tree.nodes.len() as u64, 0] {
+                out.extend_from_slice(&value.to_ne_bytes());
+            }
+            for value in [512u32, 255, 512] {
+                out.extend_from_slice(&value.to_ne_bytes());
+            }
+            out.resize(KSTATFS_SIZE, 0);
+            Ok(())
+        }
+        ACCESS => match u32_at(arg, 0)? as i32 & libc::W_OK {
+            0 => Ok(()),
+            _ => Err(libc::EROFS),
+        },
+        RELEASE | RELEASEDIR | FLUSH | DESTROY => Ok(()),
+        _ => Err(libc::ENOSYS),
+    };
+    Some(result.map(|()| out))
+}
+
+/// Answer requests until the file system is unmounted.
+fn serve(device: std::fs::File, tree: Tree) -> std::io::Result<()> {
+    use std::io::{Read as _, Write as _};
+
+    let mut device = device;
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    loop {
+        let len = match device.read(&mut buffer) {
+            Ok(len) => len,
+            // the request was interrupted before it was read, or no request was ready
+            Err(err) if matches!(err.raw_os_error(), Some(libc::ENOENT | libc::EINTR | libc::EAGAIN)) => continue,
+            // unmounted
+            Err(err) if err.raw_os_error() == Some(libc::ENODEV) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        let request = &buffer[..len];
+        let (Some(opcode), Some(unique), Some(ino)) = (u32_at(request, 4), u64_at(request, 8), u64_at(request, 16)) else {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "truncated FUSE request"));
+        };
+        let arg = request.get(IN_HEADER_SIZE..).unwrap_or_default();
+        let (error, payload) = match handle(&tree, opcode, ino, arg) {
+            None => continue,
+            Some(Ok(payload)) => (0, payload),
+            Some(Err(errno)) => (-errno, vec![]),
+        };
+        let mut reply = std::vec::Vec::with_capacity(OUT_HEADER_SIZE + payload.len());
+        reply.extend_from_slice(&((OUT_HEADER_SIZE + payload.len()) as u32).to_ne_bytes());
+        reply.extend_from_slice(&error.to_ne_bytes());
+        reply.extend_from_slice(&unique.to_ne_bytes());
+        reply.extend_from_slice(&payload);
+        match device.write(&reply) {
+            // the request was interrupted in the meantime
+            Err(err) if err.raw_os_error() == Some(libc::ENOENT) => {}
+            Err(err) if err.raw_os_error() == Some(libc::ENODEV) => return Ok(()),
+            result => {
+                result?;
+            }
+        }
+        if opcode == DESTROY {
+            return Ok(());
+        }
+    }
+}
+
+fn cstring(path: &std::path::Path) -> std::io::Result<std::ffi::CString> {
+    use std::os::unix::ffi::OsStrExt as _;
+    std::ffi::CString::new(path.as_os_str().as_bytes()).map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "path contains a null byte"))
+}
+
+/// Mount with the mount system call, which requires the `CAP_SYS_ADMIN` capability.
+fn mount_directly(mountpoint: &std::path::Path) -> std::io::Result<std::fs::File> {
+    use std::os::fd::AsRawFd as _;
+
+    let device = std::fs::OpenOptions::new().read(true).write(true).open("/dev/fuse")?;
+    // SAFETY: getuid and getgid cannot fail
+    #[allow(unsafe_code)]
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+    let options =
+        std::ffi::CString::new(format!("fd={},rootmode=40000,user_id={uid},group_id={gid}", device.as_raw_fd())).expect("options contain no null bytes");
+    let target = cstring(mountpoint)?;
+    let flags = libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV;
+    // SAFETY: all strings are valid and null-terminated
+    #[allow(unsafe_code)]
+    let result = unsafe {
+        libc::mount(
+            c"include_assets".as_ptr(),
+            target.as_ptr(),
+            c"fuse.include_assets".as_ptr(),
+            flags,
+            options.as_ptr().cast(),
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(device)
+}
+
+/// Mount with the setuid `fusermount3` (or `fusermount`) helper, which passes the opened `/dev/fuse` back over a socket.
+fn mount_with_fusermount(mountpoint: &std::path::Path) -> std::io::Result<(std::fs::File, &'static str)> {
+    use std::os::fd::{AsRawFd as _, FromRawFd as _};
+
+    let mut fds = [0; 2];
+    // SAFETY: `fds` has room for the two file descriptors
+    #[allow(unsafe_code)]
+    if unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // SAFETY: socketpair returned two new file descriptors, which are owned here
+    #[allow(unsafe_code)]
+    let (ours, theirs) = unsafe { (std::os::fd::OwnedFd::from_raw_fd(fds[0]), std::os::fd::OwnedFd::from_raw_fd(fds[1])) };
+
+    let mut last_error = std::io::Error::new(std::io::ErrorKind::NotFound, "fusermount not found");
+    for program in ["fusermount3", "fusermount"] {
+        let status = std::process::Command::new(program)
+            .args(["-o", "ro,nosuid,nodev,fsname=include_assets,subtype=include_assets", "--"])
+            .arg(mountpoint)
+            .env("_FUSERMOUNT_COMM_FD", theirs.as_raw_fd().to_string())
+            .status();
+        match status {
+            Ok(status) if status.success() => {
+                let device = receive_fd(&ours)?;
+                return Ok((device, program));
+            }
+            Ok(status) => return Err(std::io::Error::other(format!("{program} failed ({status})"))),
+            Err(err) => last_error = err,
+        }
+    }
+    Err(last_error)
+}
+
+/// Receive a file descriptor sent with `SCM_RIGHTS`.
+fn receive_fd(socket: &std::os::fd::OwnedFd) -> std::io::Result<std::fs::File> {
+    use std::os::fd::{AsRawFd as _, FromRawFd as _};
+
+    let mut byte = 0u8;
+    let mut iov = libc::iovec {
+        iov_base: (&mut byte as *mut u8).cast(),
+        iov_len: 1,
+    };
+    // buffer for one control message with one file descriptor, aligned for `cmsghdr`
+    let mut control = [0u64; 8];
+    // SAFETY: all-zero is a valid `msghdr`
+    #[allow(unsafe_code)]
+    let mut message: libc::msghdr = unsafe { std::mem::zeroed() };
+    message.msg_iov = &mut iov;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr().cast();
+    message.msg_controllen = std::mem::size_of_val(&control) as _;
+    // SAFETY: the message points to valid buffers of the given sizes
+    #[allow(unsafe_code)]
+    if unsafe { libc::recvmsg(socket.as_raw_fd(), &mut message, libc::MSG_CMSG_CLOEXEC) } <= 0 {
+        return Err(std::io::Error::other("fusermount didn't pass a file descriptor"));
+    }
+    // SAFETY: the control buffer was filled by recvmsg
+    #[allow(unsafe_code)]
+    unsafe {
+        let header = libc::CMSG_FIRSTHDR(&message);
+        if header.is_null() || (*header).cmsg_level != libc::SOL_SOCKET || (*header).cmsg_type != libc::SCM_RIGHTS {
+            return Err(std::io::Error::other("fusermount didn't pass a file descriptor"));
+        }
+        let fd = libc::CMSG_DATA(header).cast::<libc::c_int>().read_unaligned();
+        Ok(std::fs::File::from_raw_fd(fd))
+    }
+}
+
+/// Archive mounted as a read-only file system, see [`NamedArchive::mount`]
+///
+/// Requests are answered on a background thread.
+/// The file system is unmounted when this is dropped.
+pub struct Mount {
+    mountpoint: std::path::PathBuf,
+    /// The fusermount program which mounted the file system, or `None` if it was mounted directly
+    fusermount: Option<&'static str>,
+    thread: Option<std::thread::JoinHandle<std::io::Result<()>>>,
+}
+
+impl Mount {
+    pub(crate) fn new(archive: &'static NamedArchive, mountpoint: &std::path::Path) -> std::io::Result<Self> {
+        let mountpoint = std::fs::canonicalize(mountpoint)?;
+        let (device, fusermount) = match mount_directly(&mountpoint) {
+            Ok(device) => (device, None),
+            Err(err) if matches!(err.raw_os_error(), Some(libc::EPERM | libc::EACCES)) => {
+                let (device, program) = mount_with_fusermount(&mountpoint)?;
+                (device, Some(program))
+            }
+            Err(err) => return Err(err),
+        };
+        let tree = Tree::new(archive);
+        let thread = std::thread::Builder::new()
+            .name("include_assets FUSE".into())
+            .spawn(move || serve(device, tree))?;
+        Ok(Self {
+            mountpoint,
+            fusermount,
+            thread: Some(thread),
+        })
+    }
+
+    /// Path at which the archive is mounted
+    pub fn mountpoint(&self) -> &std::path::Path {
+        &self.mountpoint
+    }
+
+    /// Unmount the file system.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if unmounting fails, e.g. because a file in the file system is still open, or if answering requests failed.
+    pub fn unmount(mut self) -> std::io::Result<()> {
+        self.unmount_with(false)?;
+        match self.thread.take().map(std::thread::JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => Ok(()),
+        }
+    }
+
+    /// Block until the file system is unmounted from outside the program, e.g. with `fusermount -u` or `umount`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if answering requests failed.
+    pub fn join(mut self) -> std::io::Result<()> {
+        match self.thread.take().map(std::thread::JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => Ok(()),
+        }
+    }
+
+    /// Unmount the file system, lazily (i.e. once it is no longer in use) if `lazy` is true.
+    fn unmount_with(&self, lazy: bool) -> std::io::Result<()> {
+        match self.fusermount {
+            Some(program) => {
+                let mut command = std::process::Command::new(program);
+                command.arg("-u");
+                if lazy {
+                    command.arg("-z");
+                }
+                let status = command.arg("--").arg(&self.mountpoint).status()?;
+                if status.success() {
+                    Ok(())
+                } else {
+                    Err(std::io::Error::other(format!("{program} -u failed ({status})")))
+                }
+            }
+            None => {
+                let target = cstring(&self.mountpoint)?;
+                let flags = if lazy { libc::MNT_DETACH } else { 0 };
+                // SAFETY: the path is valid and null-terminated
+                #[allow(unsafe_code)]
+                let result = unsafe { libc::umount2(target.as_ptr(), flags) };
+                if result == 0 {
+                    Ok(())
+                } else {
+                    Err(std::io::Error::last_os_error())
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Mount {
+    fn drop(&mut self) {
+        if self.thread.as_ref().is_some_and(|thread| !thread.is_finished()) {
+            // the thread stops once the file system is no longer in use
+            let _ = self.unmount_with(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> Tree {
+        Tree::new(NamedArchive::from_iter([("hello.txt", "Hello, world!"), ("dir/a.txt", "a")]).leak())
+    }
+
+    /// Look up `name` in the directory `dir`, returning the inode number of the entry
+    fn lookup(tree: &Tree, dir: u64, name: &str) -> u64 {
+        let reply = handle(tree, LOOKUP, dir, format!("{name}\0").as_bytes()).unwrap().unwrap();
+        assert_eq!(reply.len(), ENTRY_OUT_SIZE);
+        u64_at(&reply, 0).unwrap()
+    }
+
+    #[test]
+    fn reply_sizes() {
+        let tree = tree();
+        let init = handle(&tree, INIT, 0, &[7u32.to_ne_bytes(), 40u32.to_ne_bytes()].concat()).unwrap().unwrap();
+        assert_eq!(init.len(), INIT_OUT_SIZE);
+        assert_eq!((u32_at(&init, 0), u32_at(&init, 4)), (Some(7), Some(31)));
+        for (minor, size) in [(4, COMPAT_INIT_OUT_SIZE), (22, COMPAT_22_INIT_OUT_SIZE), (23, INIT_OUT_SIZE)] {
+            let init = handle(&tree, INIT, 0, &[7u32.to_ne_bytes(), u32::to_ne_bytes(minor)].concat())
+                .unwrap()
+                .unwrap();
+            assert_eq!((init.len(), u32_at(&init, 4)), (size, Some(minor)));
+        }
+        let attr = handle(&tree, GETATTR, ROOT, &[]).unwrap().unwrap();
+        assert_eq!(attr.len(), ATTR_OUT_SIZE);
+        let open = handle(&tree, OPEN, lookup(&tree, ROOT, "hello.txt"), &0u32.to_ne_bytes()).unwrap().unwrap();
+        assert_eq!(open.len(), OPEN_OUT_SIZE);
+        assert_eq!(handle(&tree, STATFS, ROOT, &[]).unwrap().unwrap().len(), KSTATFS_SIZE);
+    }
+
+    #[test]
+    fn lookup_and_read() {
+        let tree = tree();
+        let ino = lookup(&tree, ROOT, "hello.txt");
+        let entry = handle(&tree, LOOKUP, ROOT, b"hello.txt\0").unwrap().unwrap();
+        // the size follows the inode number in `struct fuse_attr`
+        assert_eq!(u64_at(&entry, 40 + 8), Some(13));
+        let read = |offset: u64, size: u32| {
+            let arg = [0u64.to_ne_bytes(), offset.to_ne_bytes(), u64::from(size).to_ne_bytes()].concat();
+            handle(&tree, READ, ino, &arg).unwrap()
+        };
+        assert_eq!(read(0, 5), Ok(b"Hello".to_vec()));
+        assert_eq!(read(7, 100), Ok(b"world!".to_vec()));
+        assert_eq!(read(100, 5), Ok(vec![]));
+        assert_eq!(handle(&tree, LOOKUP, ROOT, b"missing.txt\0"), Some(Err(libc::ENOENT)));
+        assert_eq!(handle(&tree, READ, ROOT, &[0; 24]), Some(Err(libc::EISDIR)));
+        assert_eq!(handle(&tree, OPEN, ino, &(libc::O_WRONLY as u32).to_ne_bytes()), Some(Err(libc::EROFS)));
+    }
+
+    /// Names, inode numbers, offsets, and types of the entries of a `READDIR` reply
+    fn dirents(reply: &[u8]) -> std::vec::Vec<(String, u64, u64, u32)> {
+        let mut entries = vec![];
+        let mut rest = reply;
+        while !rest.is_empty() {
+            let len = u32_at(rest, 16).unwrap() as usize;
+            let name = std::str::from_utf8(&rest[DIRENT_SIZE..DIRENT_SIZE + len]).unwrap().to_owned();
+            entries.push((name, u64_at(rest, 0).unwrap(), u64_at(rest, 8).unwrap(), u32_at(rest, 20).unwrap()));
+            rest = &rest[(DIRENT_SIZE + len).next_multiple_of(8)..];
+        }
+        entries
+    }
+
+    #[test]
+    fn readdir() {
+        let tree = tree();
+        let arg = [0u64.to_ne_bytes(), 0u64.to_ne_bytes(), 4096u64.to_ne_bytes()].concat();
+        let reply = handle(&tree, READDIR, ROOT, &arg).unwrap().unwrap();
+        let dir = u32::from(libc::DT_DIR);
+        let file = u32::from(libc::DT_REG);
+        let dir_ino = lookup(&tree, ROOT, "dir");
+        let hello_ino = lookup(&tree, ROOT, "hello.txt");
+        assert_eq!(
+            dirents(&reply),
+            [
+                (".".into(), ROOT, 1, dir),
+                ("..".into(), ROOT, 2, dir),
+                ("dir".into(), dir_ino, 3, dir),
+                ("hello.txt".into(), hello_ino, 4, file)
+            ]
+        );
+        // ".." of a subdirectory is its parent
+        let reply = handle(&tree, READDIR, dir_ino, &arg).unwrap().unwrap();
+        assert_eq!(
+            dirents(&reply),
+            [
+                (".".into(), dir_ino, 1, dir),
+                ("..".into(), ROOT, 2, dir),
+                ("a.txt".into(), lookup(&tree, dir_ino, "a.txt"), 3, file)
+            ]
+        );
+        // continue after the third entry, with room for only one more
+        let arg = [0u64.to_ne_bytes(), 3u64.to_ne_bytes(), 40u64.to_ne_bytes()].concat();
+        let reply = handle(&tree, READDIR, ROOT, &arg).unwrap().unwrap();
+        assert_eq!(reply.len(), (DIRENT_SIZE + "hello.txt".len()).next_multiple_of(8));
+    }
+
+    /// Needs `/dev/fuse` and either `CAP_SYS_ADMIN` or `fusermount3`, and passes without checking anything otherwise
+    #[test]
+    fn mount_round_trip() {
+        let archive = NamedArchive::from_iter([("hello.txt", "Hello, world!"), ("dir/a.txt", "a")]).leak();
+        let mountpoint = std::env::temp_dir().join(format!("include_assets_fuse_test_{}", std::process::id()));
+        std::fs::create_dir_all(&mountpoint).unwrap();
+        let mount = match archive.mount(&mountpoint) {
+            Ok(mount) => mount,
+            Err(err) => {
+                eprintln!("skipping mount_round_trip, mounting failed: {err}");
+                std::fs::remove_dir(&mountpoint).unwrap();
+                return;
+            }
+        };
+        assert_eq!(std::fs::read(mountpoint.join("hello.txt")).unwrap(), b"Hello, world!");
+        assert_eq!(std::fs::read(mountpoint.join("dir/a.txt")).unwrap(), b"a");
+        let mut names: std::vec::Vec<_> = std::fs::read_dir(&mountpoint).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        names.sort();
+        assert_eq!(names, ["dir", "hello.txt"]);
+        assert!(std::fs::write(mountpoint.join("hello.txt"), "changed").is_err());
+        mount.unmount().unwrap();
+        std::fs::remove_dir(&mountpoint).unwrap();
+    }
+}
@@ -0,0 +1,25 @@
+use crate::named::NamedArchive;
+
+/// Register the assets whose names start with `prefix` as templates, named by the rest of their name.
+///
+/// For example, with `prefix = "templates/"`, the asset `templates/page.hbs` is registered as the template `page.hbs`.
+/// Unlike `Handlebars::register_templates_directory`, the extension is kept, since assets may be templates of different kinds.
+///
+/// # Errors
+///
+/// Returns an error if a template is not valid UTF-8 or cannot be parsed.
+/// Templates before the erroneous one remain registered.
+pub fn register_templates(registry: &mut handlebars::Handlebars<'_>, archive: &NamedArchive, prefix: &str) -> Result<(), handlebars::TemplateError> {
+    let mut templates: std::vec::Vec<_> = archive.iter_prefix(prefix).collect();
+    // register in a deterministic order, so the same error is reported every time
+    templates.sort_unstable_by_key(|(name, _)| *name);
+    for (name, data) in templates {
+        let name = &name[prefix.len()..];
+        let content = core::str::from_utf8(data).map_err(|err| {
+            let err = std::io::Error::new(std::io::ErrorKind::InvalidData, err);
+            handlebars::TemplateError::of(handlebars::TemplateErrorReason::IoError(err, name.to_owned())).in_template(name.to_owned())
+        })?;
+        registry.register_template_string(name, content)?;
+    }
+    Ok(())
+}
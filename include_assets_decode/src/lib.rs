@@ -1,8 +1,49 @@
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(any(feature = "mmap", feature = "wasm", feature = "ffi", feature = "fuse")), forbid(unsafe_code))]
+#![cfg_attr(any(feature = "mmap", feature = "wasm", feature = "ffi", feature = "fuse"), deny(unsafe_code))] // memory mapping a file, viewing wasm memory from JavaScript, C interfaces, and mounting a FUSE file system are inherently unsafe
+
+#[macro_use]
+extern crate alloc;
 
 pub mod common;
 
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "std")]
+pub mod bench;
+#[cfg(feature = "bevy")]
+pub mod bevy;
 pub mod checksum;
 pub mod codec;
+#[cfg(feature = "serde")]
+pub mod deserialize;
 pub mod enums;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod file;
+#[cfg(all(feature = "fuse", target_os = "linux"))]
+pub mod fuse;
+#[cfg(feature = "handlebars")]
+pub mod handlebars;
+#[cfg(feature = "std")]
+pub mod locale;
+pub mod lookup;
 pub mod named;
+#[cfg(feature = "rocket")]
+pub mod rocket;
+#[cfg(feature = "rust-embed")]
+pub mod rust_embed;
+#[cfg(feature = "http")]
+pub mod serve;
+#[cfg(feature = "signature")]
+pub mod signature;
+#[cfg(feature = "std")]
+pub mod source;
+#[cfg(feature = "tera")]
+pub mod tera;
+pub mod views;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "hot-reload")]
+pub mod watch;
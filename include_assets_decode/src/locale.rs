@@ -0,0 +1,151 @@
+// Archives of per-locale assets, decompressed on demand.
+// User-facing documentation is in the include_assets crate.
+
+use crate::codec::{AnyCodec, Codec};
+use crate::named::{CompressedNamedArchive, NamedArchive};
+
+pub struct LocaleArchive {
+    locales: std::vec::Vec<Locale>,
+    /// Indices of the locales used when none of the requested locales are included, in order of preference
+    fallbacks: std::vec::Vec<usize>,
+}
+
+/// Compressed assets of a locale, and the decompressed assets once they have been used
+struct Locale {
+    tag: &'static str,
+    compressed: CompressedNamedArchive<'static, AnyCodec>,
+    archive: std::sync::OnceLock<NamedArchive>,
+}
+
+impl LocaleArchive {
+    /// Create an archive from the compressed assets of each locale, as generated by the `include_locales!` macro.
+    pub fn new<C: Codec + Into<AnyCodec>, I: IntoIterator<Item = (&'static str, CompressedNamedArchive<'static, C>)>>(locales: I) -> Self {
+        let locales = locales
+            .into_iter()
+            .map(|(tag, compressed)| Locale {
+                tag,
+                compressed: compressed.into_any_codec(),
+                archive: std::sync::OnceLock::new(),
+            })
+            .collect();
+        Self { locales, fallbacks: vec![] }
+    }
+
+    /// Use the given locale if none of the requested locales are included.
+    ///
+    /// Call this more than once to build a chain of fallbacks, in order of preference.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the archive does not include the locale.
+    pub fn fallback(mut self, locale: &str) -> Self {
+        let index = self.position(locale).unwrap_or_else(|| panic!("locale '{locale}' is not included"));
+        if !self.fallbacks.contains(&index) {
+            self.fallbacks.push(index);
+        }
+        self
+    }
+
+    /// Returns the tags of all included locales, i.e. the names of their directories.
+    pub fn locales(&self) -> impl ExactSizeIterator<Item = &'static str> + '_ {
+        self.locales.iter().map(|locale| locale.tag)
+    }
+
+    /// Returns true if the archive includes the locale (exactly, without fallbacks).
+    pub fn contains(&self, locale: &str) -> bool {
+        self.position(locale).is_some()
+    }
+
+    /// Get the assets of the locale (exactly, without fallbacks), decompressing them if this is the first access.
+    ///
+    /// # Panics
+    ///
+    /// Panics if loading fails, see [`NamedArchive::load`].
+    pub fn get(&self, locale: &str) -> Option<&NamedArchive> {
+        self.position(locale).map(|index| self.load(index))
+    }
+
+    /// Select the assets for the locale, falling back to less specific locales and then to the [fallbacks](LocaleArchive::fallback).
+    ///
+    /// For example, `select("de-CH")` looks up assets in `de-CH`, then `de`, then the fallbacks, using those which are included.
+    /// Returns `None` if neither the locale nor any fallback is included.
+    pub fn select(&self, locale: &str) -> Option<LocaleBundle<'_>> {
+        self.select_any([locale])
+    }
+
+    /// Select the assets for the first of the given locales which is included, e.g. from an `Accept-Language` header.
+    ///
+    /// Less specific locales are tried before the next requested locale, e.g. `de` after `de-CH`, and the fallbacks are tried last.
+    /// Returns `None` if neither any of the locales nor any fallback is included.
+    pub fn select_any<'a, I: IntoIterator<Item = &'a str>>(&self, locales: I) -> Option<LocaleBundle<'_>> {
+        let mut chain = vec![];
+        let mut push = |index: usize| {
+            if !chain.contains(&index) {
+                chain.push(index);
+            }
+        };
+        for locale in locales {
+            let mut tag = locale;
+            loop {
+                if let Some(index) = self.position(tag) {
+                    push(index);
+                }
+                match tag.rsplit_once(['-', '_']) {
+                    Some((prefix, _)) => tag = prefix,
+                    None => break,
+                }
+            }
+        }
+        self.fallbacks.iter().for_each(|&index| push(index));
+        (!chain.is_empty()).then_some(LocaleBundle { archive: self, chain })
+    }
+
+    /// Index of the locale, where tags are compared case-insensitively and `_` is the same as `-`.
+    fn position(&self, locale: &str) -> Option<usize> {
+        let normalize = |c: u8| if c == b'_' { b'-' } else { c.to_ascii_lowercase() };
+        self.locales
+            .iter()
+            .position(|other| other.tag.len() == locale.len() && other.tag.bytes().map(normalize).eq(locale.bytes().map(normalize)))
+    }
+
+    fn load(&self, index: usize) -> &NamedArchive {
+        let locale = &self.locales[index];
+        locale.archive.get_or_init(|| NamedArchive::load(locale.compressed))
+    }
+}
+
+// Assets of a selected locale and its fallbacks.
+// User-facing documentation is in the include_assets crate.
+pub struct LocaleBundle<'a> {
+    archive: &'a LocaleArchive,
+    /// Indices of the locales to look up assets in, in order
+    chain: std::vec::Vec<usize>,
+}
+
+impl<'a> LocaleBundle<'a> {
+    /// Tag of the most specific included locale, which is used for all assets it contains.
+    pub fn locale(&self) -> &'static str {
+        self.archive.locales[self.chain[0]].tag
+    }
+
+    /// Tags of the locales in which assets are looked up, in order.
+    pub fn chain(&self) -> impl ExactSizeIterator<Item = &'static str> + '_ {
+        self.chain.iter().map(|&index| self.archive.locales[index].tag)
+    }
+
+    /// Get the content of the asset with the given `name` from the first locale in the chain which contains it.
+    ///
+    /// Locales are decompressed when they are first needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if loading a locale fails, see [`NamedArchive::load`].
+    pub fn get(&self, name: &str) -> Option<&'a [u8]> {
+        self.chain.iter().find_map(|&index| self.archive.load(index).get(name))
+    }
+
+    /// Get the content of the asset with the given `name` as string, see [`LocaleBundle::get`].
+    pub fn get_str(&self, name: &str) -> Option<Result<&'a str, core::str::Utf8Error>> {
+        self.get(name).map(core::str::from_utf8)
+    }
+}
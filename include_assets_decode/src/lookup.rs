@@ -0,0 +1,474 @@
+//! Lookup of assets by name, via a perfect hash function generated at compile time, a hash map, or binary search.
+
+use crate::common::u32_to_usize;
+
+type Name = smartstring::SmartString<smartstring::LazyCompact>;
+
+/// Heap memory used by a name, which is zero for short names stored inline
+pub(crate) fn heap_size(name: &Name) -> usize {
+    if name.is_inline() {
+        0
+    } else {
+        name.capacity()
+    }
+}
+
+/// Perfect hash function over the asset names of a compressed archive
+///
+/// Generated by the `include_dir!` macro (using the `phf_generator` crate), so that loading an archive doesn't have to build a hash map of all asset names.
+#[derive(Debug, Clone, Copy)]
+pub struct PerfectHash<'a> {
+    /// Key of the hash function
+    pub key: u64,
+
+    /// Displacements for each bucket of the hash table
+    pub displacements: &'a [(u32, u32)],
+
+    /// Index of the asset (in the order of the archive's names) for each slot of the hash table
+    pub indices: &'a [u32],
+}
+
+/// Data structure used to look up assets by name
+///
+/// With feature `std`, hash maps and sorted tables are built on the first lookup, so that loading an archive doesn't pay for them if assets are only iterated over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lookup {
+    /// Perfect hash table generated at compile time if available, otherwise a hash map
+    ///
+    /// Lookups take constant time.
+    /// Once assets are added or removed (e.g. with [`NamedArchive::overlay_dir`](crate::named::NamedArchive::overlay_dir)), a hash map is used.
+    #[default]
+    Hash,
+
+    /// Table of asset indices sorted by name, searched with binary search
+    ///
+    /// Lookups take logarithmic time, but no memory is allocated per asset, which matters for archives with many small assets.
+    Sorted,
+
+    /// Hash map, plus a second hash map of lowercase names, so that `"Logo.PNG"` finds the asset `"logo.png"`
+    ///
+    /// This is useful if asset references come from user-authored content created on a case-insensitive file system.
+    /// An exact match takes precedence. If several assets differ only in case, the first one in the archive is found.
+    CaseInsensitive,
+}
+
+/// Case-folded name for [`Lookup::CaseInsensitive`]
+fn fold(name: &str) -> Name {
+    name.to_lowercase().into()
+}
+
+/// Names stored in one contiguous buffer
+#[derive(Clone, Default)]
+pub(crate) struct Names {
+    buffer: alloc::string::String,
+    /// End offset of each name in the buffer
+    ends: alloc::vec::Vec<u32>,
+}
+
+impl Names {
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn get(&self, i: usize) -> &str {
+        let start = i.checked_sub(1).map(|j| u32_to_usize(self.ends[j])).unwrap_or(0);
+        &self.buffer[start..u32_to_usize(self.ends[i])]
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
+        (0..self.len()).map(|i| self.get(i))
+    }
+
+    /// Append a name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the names would exceed 4 GiB.
+    pub fn push(&mut self, name: &str) {
+        self.buffer.push_str(name);
+        self.ends.push(u32::try_from(self.buffer.len()).expect("asset names should not exceed 4 GiB"));
+    }
+
+    /// Remove the name at index `i`, moving all following names.
+    fn remove(&mut self, i: usize) {
+        let start = i.checked_sub(1).map(|j| self.ends[j]).unwrap_or(0);
+        let len = self.ends.remove(i) - start;
+        self.buffer.replace_range(u32_to_usize(start)..u32_to_usize(start + len), "");
+        for end in &mut self.ends[i..] {
+            *end -= len;
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn clear(&mut self) {
+        self.buffer.clear();
+        self.ends.clear();
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.buffer.shrink_to_fit();
+        self.ends.shrink_to_fit();
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.buffer.capacity() + self.ends.capacity() * core::mem::size_of::<u32>()
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Names {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(names: I) -> Self {
+        let mut result = Self::default();
+        for name in names {
+            result.push(name);
+        }
+        result.shrink_to_fit();
+        result
+    }
+}
+
+/// Owned perfect hash table
+#[derive(Clone)]
+struct Table {
+    key: u64,
+    displacements: alloc::vec::Vec<(u32, u32)>,
+    indices: alloc::vec::Vec<u32>,
+}
+
+impl Table {
+    /// Copy the hash table, checking that it maps to each of `len` assets exactly once.
+    fn new(hash: PerfectHash<'_>, len: usize) -> Option<Self> {
+        if hash.indices.len() != len || (len > 0 && hash.displacements.is_empty()) {
+            return None;
+        }
+        let mut seen = vec![false; len];
+        for &index in hash.indices {
+            let seen = seen.get_mut(u32_to_usize(index))?;
+            if core::mem::replace(seen, true) {
+                return None;
+            }
+        }
+        Some(Self {
+            key: hash.key,
+            displacements: hash.displacements.to_vec(),
+            indices: hash.indices.to_vec(),
+        })
+    }
+
+    /// Returns the only index at which an asset with the given `name` may be located.
+    fn candidate(&self, name: &str) -> Option<usize> {
+        if self.indices.is_empty() {
+            return None;
+        }
+        let hashes = phf_shared::hash(name, &self.key);
+        let slot = phf_shared::get_index(&hashes, &self.displacements, self.indices.len());
+        Some(u32_to_usize(self.indices[u32_to_usize(slot)]))
+    }
+}
+
+#[derive(Clone)]
+enum Index {
+    /// Perfect hash table, only valid until assets are added or removed
+    Perfect(Table),
+    /// Hash map from names to indices
+    Map(hashbrown::HashMap<Name, usize>),
+    /// Indices sorted by name
+    Sorted(alloc::vec::Vec<u32>),
+    /// Hash maps from names and from case-folded names to indices
+    CaseInsensitive {
+        exact: hashbrown::HashMap<Name, usize>,
+        folded: hashbrown::HashMap<Name, usize>,
+    },
+}
+
+/// Index built on the first lookup
+#[derive(Clone)]
+struct LazyIndex {
+    lookup: Lookup,
+    #[cfg(feature = "std")]
+    index: std::sync::OnceLock<Index>,
+    // without std, there is no thread-safe lazy initialization, so the index is built eagerly
+    #[cfg(not(feature = "std"))]
+    index: Index,
+}
+
+impl LazyIndex {
+    #[cfg_attr(feature = "std", allow(unused_variables))]
+    fn new(names: &Names, lookup: Lookup) -> Self {
+        Self {
+            lookup,
+            #[cfg(feature = "std")]
+            index: std::sync::OnceLock::new(),
+            #[cfg(not(feature = "std"))]
+            index: Index::build(names, lookup),
+        }
+    }
+
+    fn built(index: Index) -> Self {
+        Self {
+            lookup: index.lookup(),
+            #[cfg(feature = "std")]
+            index: index.into(),
+            #[cfg(not(feature = "std"))]
+            index,
+        }
+    }
+
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    fn get(&self, names: &Names) -> &Index {
+        #[cfg(feature = "std")]
+        return self.index.get_or_init(|| Index::build(names, self.lookup));
+        #[cfg(not(feature = "std"))]
+        return &self.index;
+    }
+
+    /// Returns the index if it has been built.
+    fn get_mut(&mut self) -> Option<&mut Index> {
+        #[cfg(feature = "std")]
+        return self.index.get_mut();
+        #[cfg(not(feature = "std"))]
+        return Some(&mut self.index);
+    }
+
+    fn memory_usage(&self) -> usize {
+        #[cfg(feature = "std")]
+        return self.index.get().map_or(0, Index::memory_usage);
+        #[cfg(not(feature = "std"))]
+        return self.index.memory_usage();
+    }
+}
+
+/// Approximate heap memory used by a hash map from names to indices
+fn map_memory_usage(map: &hashbrown::HashMap<Name, usize>) -> usize {
+    // hashbrown stores one control byte per bucket in addition to the entries
+    map.capacity() * (core::mem::size_of::<(Name, usize)>() + 1) + map.keys().map(heap_size).sum::<usize>()
+}
+
+impl Index {
+    fn build(names: &Names, lookup: Lookup) -> Self {
+        match lookup {
+            Lookup::Hash => Index::Map(names.iter().enumerate().map(|(i, name)| (name.into(), i)).collect()),
+            Lookup::Sorted => {
+                let mut order: alloc::vec::Vec<u32> = (0..names.len())
+                    .map(|i| u32::try_from(i).expect("number of assets should fit into u32"))
+                    .collect();
+                order.sort_by_key(|&i| names.get(u32_to_usize(i)));
+                Index::Sorted(order)
+            }
+            Lookup::CaseInsensitive => {
+                let mut folded = hashbrown::HashMap::with_capacity(names.len());
+                for (i, name) in names.iter().enumerate() {
+                    folded.entry(fold(name)).or_insert(i);
+                }
+                Index::CaseInsensitive {
+                    exact: names.iter().enumerate().map(|(i, name)| (name.into(), i)).collect(),
+                    folded,
+                }
+            }
+        }
+    }
+
+    fn memory_usage(&self) -> usize {
+        match self {
+            Index::Perfect(table) => {
+                table.displacements.capacity() * core::mem::size_of::<(u32, u32)>() + table.indices.capacity() * core::mem::size_of::<u32>()
+            }
+            Index::Map(map) => map_memory_usage(map),
+            Index::Sorted(order) => order.capacity() * core::mem::size_of::<u32>(),
+            Index::CaseInsensitive { exact, folded } => map_memory_usage(exact) + map_memory_usage(folded),
+        }
+    }
+
+    fn lookup(&self) -> Lookup {
+        match self {
+            Index::Perfect(_) | Index::Map(_) => Lookup::Hash,
+            Index::Sorted(_) => Lookup::Sorted,
+            Index::CaseInsensitive { .. } => Lookup::CaseInsensitive,
+        }
+    }
+}
+
+/// Values by name, looked up via a perfect hash table, a hash map, or binary search
+#[derive(Clone)]
+pub(crate) struct Entries<V> {
+    names: Names,
+    values: alloc::vec::Vec<V>,
+    index: LazyIndex,
+}
+
+impl<V> Entries<V> {
+    pub fn new(lookup: Lookup) -> Self {
+        Self::with_perfect_hash(Names::default(), vec![], None, lookup).expect("there is no perfect hash table to check")
+    }
+
+    /// Create entries with unique names.
+    ///
+    /// With [`Lookup::Hash`], the perfect hash table is used if given.
+    /// Returns `None` if it doesn't match the number of entries.
+    pub fn with_perfect_hash(names: Names, values: alloc::vec::Vec<V>, hash: Option<PerfectHash<'_>>, lookup: Lookup) -> Option<Self> {
+        assert_eq!(names.len(), values.len(), "there should be one value per name");
+        let index = match (hash, lookup) {
+            (Some(hash), Lookup::Hash) => LazyIndex::built(Index::Perfect(Table::new(hash, names.len())?)),
+            _ => LazyIndex::new(&names, lookup),
+        };
+        Some(Self { names, values, index })
+    }
+
+    pub fn lookup(&self) -> Lookup {
+        self.index.lookup
+    }
+
+    /// Switch to another data structure for lookups.
+    pub fn set_lookup(&mut self, lookup: Lookup) {
+        if lookup != self.lookup() {
+            self.index = LazyIndex::new(&self.names, lookup);
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        match self.index.get(&self.names) {
+            Index::Perfect(table) => table.candidate(name).filter(|&i| self.names.get(i) == name),
+            Index::Map(map) => map.get(name).copied(),
+            Index::Sorted(order) => order
+                .binary_search_by(|&i| self.names.get(u32_to_usize(i)).cmp(name))
+                .ok()
+                .map(|j| u32_to_usize(order[j])),
+            Index::CaseInsensitive { exact, folded } => exact.get(name).or_else(|| folded.get(&fold(name))).copied(),
+        }
+    }
+
+    /// Position of the entry with exactly the given `name`, ignoring case-insensitive matches
+    fn exact_position(&self, name: &str) -> Option<usize> {
+        self.position(name).filter(|&i| self.names.get(i) == name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&V> {
+        self.position(name).map(|i| &self.values[i])
+    }
+
+    /// Returns the stored name (which may differ in case with [`Lookup::CaseInsensitive`]) and the value.
+    pub fn get_key_value(&self, name: &str) -> Option<(&str, &V)> {
+        self.position(name).map(|i| (self.names.get(i), &self.values[i]))
+    }
+
+    /// Insert a value, replacing the value with the same name if there is one.
+    pub fn insert(&mut self, name: &str, value: V) {
+        if let Some(i) = self.exact_position(name) {
+            self.values[i] = value;
+            return;
+        }
+        let i = self.names.len();
+        self.names.push(name);
+        self.values.push(value);
+        match self.index.get_mut() {
+            None => {}
+            Some(Index::Perfect(_)) => self.index = LazyIndex::new(&self.names, Lookup::Hash),
+            Some(Index::Map(map)) => {
+                map.insert(name.into(), i);
+            }
+            Some(Index::Sorted(order)) => {
+                let j = order.partition_point(|&j| self.names.get(u32_to_usize(j)) < name);
+                order.insert(j, u32::try_from(i).expect("number of assets should fit into u32"));
+            }
+            Some(Index::CaseInsensitive { exact, folded }) => {
+                exact.insert(name.into(), i);
+                folded.entry(fold(name)).or_insert(i);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<V> {
+        let i = self.exact_position(name)?;
+        self.names.remove(i);
+        let value = self.values.remove(i);
+        match self.index.get_mut() {
+            None => {}
+            Some(Index::Perfect(_)) => self.index = LazyIndex::new(&self.names, Lookup::Hash),
+            Some(Index::Map(map)) => {
+                map.remove(name);
+                map.values_mut().filter(|j| **j > i).for_each(|j| *j -= 1);
+            }
+            Some(Index::Sorted(order)) => {
+                order.retain(|&j| u32_to_usize(j) != i);
+                order.iter_mut().filter(|j| u32_to_usize(**j) > i).for_each(|j| *j -= 1);
+            }
+            // another asset may differ from the removed one only in case
+            Some(Index::CaseInsensitive { .. }) => self.index = LazyIndex::new(&self.names, Lookup::CaseInsensitive),
+        }
+        Some(value)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn clear(&mut self) {
+        self.names.clear();
+        self.values.clear();
+        self.index = LazyIndex::new(&self.names, self.lookup());
+    }
+
+    /// Release excess capacity.
+    pub fn shrink_to_fit(&mut self) {
+        self.names.shrink_to_fit();
+        self.values.shrink_to_fit();
+        match self.index.get_mut() {
+            None | Some(Index::Perfect(_)) => {}
+            Some(Index::Map(map)) => map.shrink_to_fit(),
+            Some(Index::Sorted(order)) => order.shrink_to_fit(),
+            Some(Index::CaseInsensitive { exact, folded }) => {
+                exact.shrink_to_fit();
+                folded.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Approximate heap memory used by names, values (not including their heap memory) and the index if it has been built
+    pub fn memory_usage(&self) -> usize {
+        self.names.memory_usage() + self.values.capacity() * core::mem::size_of::<V>() + self.index.memory_usage()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Name and value of the entry at position `i` in the order of [`Entries::iter`]
+    #[cfg(feature = "ffi")]
+    pub fn get_index(&self, i: usize) -> Option<(&str, &V)> {
+        (i < self.len()).then(|| (self.names.get(i), &self.values[i]))
+    }
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            entries: self,
+            range: 0..self.len(),
+        }
+    }
+
+    pub fn values_mut(&mut self) -> core::slice::IterMut<'_, V> {
+        self.values.iter_mut()
+    }
+
+    pub fn into_vec(self) -> alloc::vec::Vec<(Name, V)> {
+        let Self { names, values, index: _ } = self;
+        names.iter().map(Name::from).zip(values).collect()
+    }
+}
+
+/// Iterator of names and values of [`Entries`]
+#[derive(Clone)]
+pub(crate) struct Iter<'a, V> {
+    entries: &'a Entries<V>,
+    range: core::ops::Range<usize>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (&'a str, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let i = self.range.next()?;
+        Some((self.entries.names.get(i), &self.entries.values[i]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<V> ExactSizeIterator for Iter<'_, V> {}
@@ -2,21 +2,31 @@ use crate::checksum;
 /// Named asset archives provide maps from path name to asset content.
 /// This crate contains functionality specific to this kind of asset archives.
 use crate::codec::Codec;
+use alloc::borrow::ToOwned;
+use alloc::string::ToString;
 
-use crate::common::{decompress_names, decompress_ranges, u32_to_usize, u32_to_usize_range};
+use crate::common::{
+    decompress_names, decompress_ranges, decompress_shards, decompress_shards_bounded, decompress_shards_parallel, parse_names, parse_ranges, u32_to_usize,
+    u32_to_usize_range, Shard, HAS_THREADS,
+};
+use crate::error::{LoadError, Section};
+use crate::lookup::{heap_size, Entries, Lookup, Names, PerfectHash};
+
+/// Largest supported alignment of assets within the uncompressed data (see [`CompressedNamedArchive::align`]), which suffices for all primitive and SIMD types
+pub const MAX_ALIGN: u32 = 64;
 
 /// Compressed named archive
 ///
 /// Contains the compressed asset data and all information required to uncompress it.
 ///
-/// Users should only create these archives via the `include_dir!` macro and only read or access them via [`NamedArchive::load`].
+/// Users should only create these archives via the `include_dir!` macro (or by reading an archive file, see [`crate::file`]) and only read or access them via [`NamedArchive::load`].
 #[derive(Clone, Copy)]
-pub struct CompressedNamedArchive<C: Codec> {
+pub struct CompressedNamedArchive<'a, C: Codec> {
     /// Compression codec with which the data was compressed
     pub codec: C,
 
     /// Raw compressed data
-    pub data: &'static [u8],
+    pub data: &'a [u8],
 
     /// Size of the data after decompression.
     /// Limited to at most 4 GiB.
@@ -31,80 +41,1383 @@ pub struct CompressedNamedArchive<C: Codec> {
     /// This is for two reasons:
     /// - It likely leads to better compression if all names with the same (path) prefix are close together, and
     /// - It makes reproducible builds easier since we don't rely on file system iteration order.
-    pub compressed_names: &'static [u8],
+    pub compressed_names: &'a [u8],
 
     /// Lengths of the uncompressed names (including separating null bytes)
     pub uncompressed_names_size: u32,
 
-    /// List of asset checksums in the same order as [`CompressedNamedArchive::compressed_names`].
-    pub checksums: &'static [checksum::Checksum],
+    /// Asset checksums in the same order as [`CompressedNamedArchive::compressed_names`], each of [`CompressedNamedArchive::checksum_size`] bytes
+    pub checksums: &'a [u8],
+
+    /// Size of each checksum in bytes, 64 for full Blake2b-512 checksums or less for truncated ones (set by the `checksum_size` option of the `include_dir!` macro)
+    pub checksum_size: u32,
 
     /// Compressed data sizes of the assets.
     ///
-    /// Once uncompressed, these will be `u32`s (little endian) in the same order as [`CompressedNamedArchive::compressed_names`].
-    pub compressed_sizes: &'static [u8],
+    /// Once uncompressed, these will be unsigned LEB128 varints in the same order as [`CompressedNamedArchive::compressed_names`],
+    /// so that archives with many small assets need only one or two bytes per size.
+    /// The offsets of the assets are the running totals of their sizes.
+    pub compressed_sizes: &'a [u8],
+
+    /// Length of the uncompressed sizes, or 0 if they are `u32`s (little endian) as in archive files of version 1 and 2.
+    pub uncompressed_sizes_size: u32,
+
+    /// Alignment of the assets within the uncompressed data in bytes, set by the `align` option of the `include_dir!` macro.
+    ///
+    /// Each asset starts at a multiple of `align`, preceded by zero bytes which are not part of any asset, and [`NamedArchive::load`] aligns its buffer accordingly.
+    /// Must be a power of two no greater than [`MAX_ALIGN`], or 0 (like 1) if assets are not aligned.
+    pub align: u32,
+
+    /// Size of the metadata at the end of the uncompressed data, set by the `single_stream` option of the `include_dir!` macro.
+    ///
+    /// The metadata consists of the number of assets, the uncompressed names size and the uncompressed sizes size (as little endian `u32`s),
+    /// followed by the uncompressed names, sizes (as varints), and checksums.
+    /// [`CompressedNamedArchive::compressed_names`], [`CompressedNamedArchive::compressed_sizes`], and [`CompressedNamedArchive::checksums`] are empty in this case.
+    /// 0 if the metadata is stored in these separate sections.
+    pub metadata_size: u32,
+
+    /// Optional ed25519 signature over the archive (see the `signature` module, requires feature `signature`)
+    pub signature: Option<&'a [u8; 64]>,
+
+    /// Absolute path of a directory from which the assets are read at load time instead of decompressing them.
+    ///
+    /// Only set by the `include_dir!` macro in debug builds with option `dev = true`, in which case the archive contains no assets.
+    pub directory: Option<&'a str>,
+
+    /// MIME types by lowercase file extension, overriding the guessed MIME types (see [`NamedArchive::mime`]).
+    pub mime_types: &'a [(&'a str, &'a str)],
+
+    /// Names of private assets, which can be looked up by name but are not listed (see [`NamedArchive::assets`]), set by the `private` option of the `include_dir!` macro.
+    ///
+    /// A name ending with `/` makes all assets in that directory and its subdirectories private.
+    pub private: &'a [&'a str],
+
+    /// Optional perfect hash function over the asset names, used instead of building a hash map at load time
+    pub perfect_hash: Option<PerfectHash<'a>>,
+
+    /// Table of independently compressed shards of [`CompressedNamedArchive::data`], set by the `shard_size` option of the `include_dir!` macro.
+    ///
+    /// For each shard, the end offset of its compressed data and the end offset of its uncompressed data (as little endian `u32`s).
+    /// Shards don't need to be aligned with assets.
+    /// Empty if the data is compressed as a single stream.
+    pub shards: &'a [u8],
+}
+
+impl<'a, C: Codec + Into<crate::codec::AnyCodec>> CompressedNamedArchive<'a, C> {
+    /// Convert to an archive whose codec is only known at runtime, e.g. to store archives with different codecs together.
+    pub fn into_any_codec(self) -> CompressedNamedArchive<'a, crate::codec::AnyCodec> {
+        CompressedNamedArchive {
+            codec: self.codec.into(),
+            data: self.data,
+            uncompressed_data_size: self.uncompressed_data_size,
+            compressed_names: self.compressed_names,
+            uncompressed_names_size: self.uncompressed_names_size,
+            checksums: self.checksums,
+            checksum_size: self.checksum_size,
+            compressed_sizes: self.compressed_sizes,
+            uncompressed_sizes_size: self.uncompressed_sizes_size,
+            align: self.align,
+            metadata_size: self.metadata_size,
+            signature: self.signature,
+            directory: self.directory,
+            mime_types: self.mime_types,
+            private: self.private,
+            perfect_hash: self.perfect_hash,
+            shards: self.shards,
+        }
+    }
+}
+
+impl<C: Codec> CompressedNamedArchive<'_, C> {
+    /// Approximate upper bound of the heap memory needed to load the archive, computed from the declared sizes without decompressing anything.
+    ///
+    /// This includes the decompressed data, names, and sizes, and the per-asset data structures, but not the compressed archive itself.
+    /// See [`NamedArchive::try_load_untrusted`].
+    pub fn memory_needed(&self) -> usize {
+        let checksum_size = u32_to_usize(self.checksum_size).max(1);
+        // each asset has a checksum, and a size of at least one byte in the metadata of single-stream archives
+        let number_of_assets = match self.metadata_size {
+            0 => self.checksums.len() / checksum_size,
+            metadata_size => u32_to_usize(metadata_size) / (checksum_size + 1),
+        };
+        let sizes_size = match self.uncompressed_sizes_size {
+            0 => number_of_assets.saturating_mul(4),
+            size => u32_to_usize(size),
+        };
+        let per_asset = core::mem::size_of::<Entry>()
+            + core::mem::size_of::<core::ops::Range<u32>>()
+            + core::mem::size_of::<checksum::Checksum>()
+            // names are indexed by a hash map on the first lookup
+            + core::mem::size_of::<(smartstring::SmartString<smartstring::LazyCompact>, usize)>()
+            + 1;
+        u32_to_usize(self.uncompressed_data_size)
+            .saturating_add(u32_to_usize(self.align.clamp(1, MAX_ALIGN)) - 1)
+            // decompressed names, the names of the archive, and the keys of the hash map
+            .saturating_add(u32_to_usize(self.uncompressed_names_size).saturating_mul(3))
+            .saturating_add(sizes_size)
+            .saturating_add(number_of_assets.saturating_mul(per_asset))
+    }
+}
+
+impl<'a, C: Codec> CompressedNamedArchive<'a, C> {
+    /// Size of the asset data, i.e. the uncompressed data without the metadata of single-stream archives
+    fn assets_size(&self) -> Result<u32, LoadError> {
+        self.uncompressed_data_size.checked_sub(self.metadata_size).ok_or(LoadError::InvalidMetadata)
+    }
+
+    /// Look up table of the data range and the index of each asset by name, with the (possibly truncated) checksums of all assets, see [`CompressedNamedArchive::metadata`]
+    ///
+    /// This is used to decompress single assets, see [`MappedArchive`](crate::file::MappedArchive).
+    #[cfg(feature = "mmap")]
+    pub(crate) fn asset_index(&self, trailer: &[u8]) -> Result<(AssetPositions, alloc::vec::Vec<u8>), LoadError> {
+        let Metadata { names, ranges, checksums } = self.metadata(trailer)?;
+        let values = ranges.into_iter().enumerate().map(|(i, range)| (range, i)).collect();
+        let entries = Entries::with_perfect_hash(names, values, None, Lookup::Hash).expect("there is no perfect hash table to check");
+        Ok((entries, checksums.to_vec()))
+    }
+
+    /// Decompress the asset names, data ranges, and checksums, or parse them from `trailer` (the end of the uncompressed data) for single-stream archives.
+    ///
+    /// There is a name and a checksum for each range, and the final range ends where the asset data ends.
+    fn metadata<'m>(&self, trailer: &'m [u8]) -> Result<Metadata<'m>, LoadError>
+    where
+        'a: 'm,
+    {
+        let metadata = if self.metadata_size == 0 {
+            let number_of_assets = split_checksums(self.checksums, self.checksum_size)?.len();
+            Metadata {
+                names: decompress_names(&self.codec, self.compressed_names, self.uncompressed_names_size)?,
+                ranges: decompress_ranges(&self.codec, self.compressed_sizes, self.uncompressed_sizes_size, number_of_assets, self.align)?,
+                checksums: self.checksums,
+            }
+        } else {
+            parse_metadata(trailer, self.checksum_size, self.align)?
+        };
+        if metadata.names.len() != metadata.ranges.len() {
+            return Err(LoadError::SizeMismatch {
+                section: Section::Names,
+                expected: metadata.ranges.len(),
+                actual: metadata.names.len(),
+            });
+        }
+
+        // Data ranges were constructed in decompress_ranges.
+        // We know that they are all non-overlapping, increasing, and don't leave any space except padding to align them.
+        // We know the first range starts at 0.
+        // The final range should end where the asset data (and the metadata) ends.
+        let assets_size = self.assets_size()?;
+        let data_end = metadata.ranges.last().map(|range| range.end).unwrap_or(0);
+        if data_end != assets_size {
+            return Err(LoadError::SizeMismatch {
+                section: Section::Data,
+                expected: u32_to_usize(assets_size),
+                actual: u32_to_usize(data_end),
+            });
+        }
+        Ok(metadata)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<C: Codec> CompressedNamedArchive<'_, C> {
+    /// Open the asset with the given `name` for reading, without decompressing the whole archive.
+    ///
+    /// Only the asset names and sizes are decompressed up front.
+    /// The asset data is decompressed incrementally while reading, one shard at a time (see the `shard_size` option of the `include_dir!` macro), so a big asset can be streamed without holding it in memory.
+    /// Without shards, the whole data is decompressed on the first read.
+    /// The checksum of the asset is verified once it has been read completely.
+    ///
+    /// Returns `None` if the archive does not contain an asset with this `name`, which is always the case in `dev` mode.
+    pub fn reader(&self, name: &str) -> Result<Option<AssetReader<'_, C>>, LoadError> {
+        let shards = crate::common::shards(self.shards, self.data.len(), u32_to_usize(self.uncompressed_data_size))?;
+        let mut trailer = vec![];
+        if self.metadata_size != 0 {
+            // decompress the shards containing the metadata
+            let start = u32_to_usize(self.assets_size()?);
+            let first = shards.partition_point(|(_, uncompressed)| uncompressed.end <= start);
+            for (compressed, uncompressed) in &shards[first..] {
+                self.codec
+                    .decompress_append(&self.data[compressed.clone()], uncompressed.len(), &mut trailer)
+                    .map_err(|err| LoadError::Decompression {
+                        section: Section::Data,
+                        source: alloc::boxed::Box::new(err),
+                    })?;
+            }
+            trailer.drain(..start - shards.get(first).map_or(start, |(_, uncompressed)| uncompressed.start));
+        }
+        let Metadata { names, ranges, checksums } = self.metadata(&trailer)?;
+        let Some(index) = names.iter().position(|other| other == name) else {
+            return Ok(None);
+        };
+        let range = &ranges[index];
+        Ok(Some(AssetReader {
+            codec: &self.codec,
+            data: self.data,
+            shards,
+            position: u32_to_usize(range.start),
+            end: u32_to_usize(range.end),
+            buffer: vec![],
+            buffer_range: 0..0,
+            hasher: Some(checksum::Hasher::default()),
+            checksum: split_checksums(checksums, self.checksum_size)?
+                .nth(index)
+                .expect("there is a checksum per asset")
+                .to_vec(),
+            name: name.to_owned(),
+        }))
+    }
+}
+
+#[cfg(feature = "std")]
+/// Reader decompressing a single asset incrementally, see [`CompressedNamedArchive::reader`]
+pub struct AssetReader<'a, C: Codec> {
+    codec: &'a C,
+    data: &'a [u8],
+    shards: alloc::vec::Vec<Shard>,
+    /// Position of the next byte to read within the uncompressed data
+    position: usize,
+    /// End of the asset within the uncompressed data
+    end: usize,
+    /// Decompressed shard
+    buffer: alloc::vec::Vec<u8>,
+    /// Range of the decompressed shard within the uncompressed data
+    buffer_range: core::ops::Range<usize>,
+    /// Checksum of the data read so far, `None` once the checksum has been verified
+    hasher: Option<checksum::Hasher>,
+    checksum: alloc::vec::Vec<u8>,
+    name: alloc::string::String,
+}
+
+#[cfg(feature = "std")]
+impl<C: Codec> AssetReader<'_, C> {
+    /// Returns the number of bytes which have not been read yet.
+    pub fn remaining(&self) -> usize {
+        self.end - self.position
+    }
+
+    /// Decompress the shard containing the current position.
+    fn load_shard(&mut self) -> std::io::Result<()> {
+        let index = self.shards.partition_point(|(_, uncompressed)| uncompressed.end <= self.position);
+        let Some((compressed, uncompressed)) = self.shards.get(index) else {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, LoadError::InvalidShardTable));
+        };
+        self.buffer.clear();
+        self.codec
+            .decompress_append(&self.data[compressed.clone()], uncompressed.len(), &mut self.buffer)
+            .map_err(|err| {
+                let err = LoadError::Decompression {
+                    section: Section::Data,
+                    source: alloc::boxed::Box::new(err),
+                };
+                std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+            })?;
+        self.buffer_range = uncompressed.clone();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<C: Codec> std::io::Read for AssetReader<'_, C> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position == self.end {
+            if let Some(hasher) = self.hasher.take() {
+                hasher.check(&self.checksum).map_err(|mismatch| {
+                    let err = LoadError::ChecksumMismatch {
+                        name: self.name.clone(),
+                        mismatch: alloc::boxed::Box::new(mismatch),
+                    };
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+                })?;
+            }
+            return Ok(0);
+        }
+        if !self.buffer_range.contains(&self.position) {
+            self.load_shard()?;
+        }
+        let start = self.position - self.buffer_range.start;
+        let len = buf.len().min(self.end.min(self.buffer_range.end) - self.position);
+        buf[..len].copy_from_slice(&self.buffer[start..start + len]);
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&buf[..len]);
+        }
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Location and checksum of an asset in a [`NamedArchive`]
+struct Entry {
+    range: core::ops::Range<u32>,
+    checksum: checksum::Checksum,
+    /// Whether the asset is left out when listing assets
+    private: bool,
+    /// Whether the checksum is yet to be verified on first access, see [`NamedArchive::try_load_lazy`]
+    unverified: core::sync::atomic::AtomicBool,
+}
+
+impl Clone for Entry {
+    fn clone(&self) -> Self {
+        Self {
+            range: self.range.clone(),
+            checksum: self.checksum,
+            private: self.private,
+            unverified: self.unverified.load(core::sync::atomic::Ordering::Relaxed).into(),
+        }
+    }
+}
+
+/// Returns the data of the asset `name` described by `entry`, verifying its checksum first if that has not happened yet.
+fn verified_data<'a>(data: &'a [u8], name: &str, entry: &Entry) -> Result<&'a [u8], LoadError> {
+    let data = &data[u32_to_usize_range(&entry.range)];
+    // the data never changes, so verifying it twice on concurrent first accesses is harmless
+    if entry.unverified.load(core::sync::atomic::Ordering::Relaxed) {
+        checksum::check(data, &entry.checksum).map_err(|mismatch| LoadError::ChecksumMismatch {
+            name: name.to_string(),
+            mismatch: alloc::boxed::Box::new(mismatch),
+        })?;
+        entry.unverified.store(false, core::sync::atomic::Ordering::Relaxed);
+    }
+    Ok(data)
+}
+
+/// Like [`verified_data`], but panics if the checksum does not match.
+fn checked_data<'a>(data: &'a [u8], name: &str, entry: &Entry) -> &'a [u8] {
+    match verified_data(data, name, entry) {
+        Ok(data) => data,
+        Err(err) => panic!("couldn't load asset: {err}"),
+    }
+}
+
+/// Data range and index of each asset by name, see [`CompressedNamedArchive::asset_index`]
+#[cfg(feature = "mmap")]
+pub(crate) type AssetPositions = Entries<(core::ops::Range<u32>, usize)>;
+
+/// Buffer holding the uncompressed data of all assets, without excess capacity, or borrowing it from an uncompressed archive
+#[cfg(not(feature = "bytes"))]
+type Buffer = alloc::borrow::Cow<'static, [u8]>;
+#[cfg(feature = "bytes")]
+type Buffer = bytes::Bytes;
+
+/// Converts a vector to the buffer, shrinking it to its length
+#[cfg(not(feature = "bytes"))]
+fn buffer_from_vec(data: alloc::vec::Vec<u8>) -> Buffer {
+    alloc::borrow::Cow::Owned(data.into_boxed_slice().into_vec())
+}
+
+#[cfg(feature = "bytes")]
+fn buffer_from_vec(data: alloc::vec::Vec<u8>) -> Buffer {
+    data.into_boxed_slice().into()
+}
+
+/// Converts static data to the buffer without copying it
+#[cfg(not(feature = "bytes"))]
+fn buffer_from_static(data: &'static [u8]) -> Buffer {
+    alloc::borrow::Cow::Borrowed(data)
+}
+
+#[cfg(feature = "bytes")]
+fn buffer_from_static(data: &'static [u8]) -> Buffer {
+    bytes::Bytes::from_static(data)
+}
+
+/// Converts a vector to the buffer like [`buffer_from_vec`], moving its first `len` bytes to an offset at which they are aligned to `align` bytes.
+///
+/// Returns the buffer and the offset, which is less than `align`.
+fn aligned_buffer_from_vec(mut data: alloc::vec::Vec<u8>, len: usize, align: usize) -> (Buffer, usize) {
+    data.resize(len + align - 1, 0);
+    // the data can only be moved to its offset once the buffer has reached its final address
+    let mut data = data.into_boxed_slice();
+    let offset = data.as_ptr().align_offset(align);
+    data.copy_within(..len, offset);
+    (buffer_from_vec(data.into_vec()), offset)
+}
+
+/// Converts the buffer to a vector, without copying unless the buffer is shared or static
+#[cfg(not(feature = "bytes"))]
+fn buffer_into_vec(data: Buffer) -> alloc::vec::Vec<u8> {
+    data.into_owned()
+}
+
+#[cfg(feature = "bytes")]
+fn buffer_into_vec(data: Buffer) -> alloc::vec::Vec<u8> {
+    data.into()
 }
 
 /// Unpacked archive of named assets
 ///
 /// Can be used to look up assets by name (i.e. path).
 pub struct NamedArchive {
-    data: std::vec::Vec<u8>,
-    ranges: std::collections::HashMap<smartstring::SmartString<smartstring::LazyCompact>, std::ops::Range<u32>>,
+    data: Buffer,
+    /// Whether `data` is borrowed from an uncompressed archive instead of using heap memory (see [`NamedArchive::try_load_static`])
+    borrowed: bool,
+    entries: Entries<Entry>,
+    /// Directory from which assets were read (only in `dev` mode)
+    #[cfg(feature = "std")]
+    directory: Option<std::path::PathBuf>,
+    /// Time when the assets were loaded or last changed
+    #[cfg(feature = "std")]
+    modified: std::time::SystemTime,
+    /// MIME types by lowercase file extension, overriding the guessed MIME types
+    #[cfg_attr(not(feature = "mime"), allow(dead_code))]
+    mime_types: alloc::vec::Vec<(
+        smartstring::SmartString<smartstring::LazyCompact>,
+        smartstring::SmartString<smartstring::LazyCompact>,
+    )>,
+    /// Names of private assets and directories, see [`CompressedNamedArchive::private`]
+    private: alloc::vec::Vec<smartstring::SmartString<smartstring::LazyCompact>>,
+    /// Number of private assets in `entries`
+    private_assets: usize,
 }
 
 impl NamedArchive {
     /// Load (decompress) compressed asset archive at runtime
     ///
+    /// The checksums of large archives are verified on several threads, up to [`std::thread::available_parallelism`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if loading fails, including if the checksum of any asset does not match.
+    /// This is only possible in the case of internal bugs or data corruption, assuming that the compressed asset were created with the `include_dir!` macro.
+    /// Use [`NamedArchive::try_load`] to handle these errors instead.
+    pub fn load<C: Codec>(compressed: CompressedNamedArchive<'_, C>) -> Self {
+        match Self::try_load(compressed) {
+            Ok(archive) => archive,
+            Err(err) => panic!("couldn't load asset archive: {err}"),
+        }
+    }
+
+    /// Leak the archive, so that it and the assets it contains live for the rest of the program.
+    ///
+    /// Lookups in the returned archive return `&'static [u8]`, which many APIs require, e.g. for fonts and shaders.
+    pub fn leak(self) -> &'static Self {
+        alloc::boxed::Box::leak(alloc::boxed::Box::new(self))
+    }
+
+    /// Load (decompress) compressed asset archive at runtime, returning an error if loading fails.
+    ///
+    /// See [`NamedArchive::load`].
+    pub fn try_load<C: Codec>(compressed: CompressedNamedArchive<'_, C>) -> Result<Self, LoadError> {
+        Self::try_load_with(compressed, Lookup::Hash)
+    }
+
+    #[cfg(feature = "std")]
+    /// Start loading (decompressing) compressed asset archive on a new thread.
+    ///
+    /// This hides the time needed for loading behind other work, e.g. creating a window or showing a splash screen.
+    /// Use [`ArchiveHandle::join`] to wait for the archive.
+    ///
+    /// On targets without threads (WebAssembly without the atomics proposal), the archive is loaded immediately on the current thread instead.
+    ///
     /// # Panics
     ///
-    /// Panics if loading fails.
-    /// This is only possible in the case of internal bugs, assuming that the compressed asset were created with the `include_dir!` macro.
-    pub fn load<C: Codec>(compressed: CompressedNamedArchive<C>) -> Self {
+    /// Panics if the thread cannot be spawned.
+    pub fn load_in_background<C: Codec + Send + 'static>(compressed: CompressedNamedArchive<'static, C>) -> ArchiveHandle {
+        if !HAS_THREADS {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| Self::try_load(compressed)));
+            let shared = alloc::sync::Arc::new(std::sync::Mutex::new(Shared {
+                result: Some(result),
+                waker: None,
+            }));
+            return ArchiveHandle { thread: None, shared };
+        }
+        let shared = alloc::sync::Arc::new(std::sync::Mutex::new(Shared { result: None, waker: None }));
+        let thread_shared = shared.clone();
+        let thread = std::thread::Builder::new()
+            .name("include_assets loader".into())
+            .spawn(move || {
+                // Panics are caught so that a waiting future is woken up either way.
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| Self::try_load(compressed)));
+                let waker = {
+                    let mut shared = thread_shared.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+                    shared.result = Some(result);
+                    shared.waker.take()
+                };
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            })
+            .expect("should be able to spawn a thread");
+        ArchiveHandle { thread: Some(thread), shared }
+    }
+
+    #[cfg(feature = "std")]
+    /// Load (decompress) compressed asset archive on a new thread, returning a future that resolves to the archive.
+    ///
+    /// This works with any async runtime, and doesn't block the runtime's threads while decompressing.
+    /// The returned future is an [`ArchiveHandle`], see [`NamedArchive::load_in_background`].
+    pub fn load_async<C: Codec + Send + 'static>(
+        compressed: CompressedNamedArchive<'static, C>,
+    ) -> impl core::future::Future<Output = Result<Self, LoadError>> + Send + Unpin + 'static {
+        Self::load_in_background(compressed)
+    }
+
+    /// Load (decompress) compressed asset archive at runtime, using the given data structure to look up assets by name.
+    ///
+    /// See [`NamedArchive::try_load`] and [`Lookup`].
+    pub fn try_load_with<C: Codec>(compressed: CompressedNamedArchive<'_, C>, lookup: Lookup) -> Result<Self, LoadError> {
+        Self::load_into_buffer(
+            compressed,
+            lookup,
+            vec![],
+            &mut |codec, src, shards, dst| decompress_shards(codec, src, shards, dst, &mut |_, _| {}),
+            available_threads(),
+            false,
+        )
+    }
+
+    /// Load (decompress) compressed asset archive at runtime, reporting progress, e.g. to show a progress bar.
+    ///
+    /// `progress` is called with the number of bytes decompressed so far and the total number of bytes, before decompressing and after each shard.
+    /// Use the `shard_size` option of the `include_dir!` macro to get more than one update.
+    ///
+    /// See [`NamedArchive::try_load`].
+    pub fn try_load_with_progress<C: Codec, F: FnMut(usize, usize)>(compressed: CompressedNamedArchive<'_, C>, mut progress: F) -> Result<Self, LoadError> {
+        Self::load_into_buffer(
+            compressed,
+            Lookup::Hash,
+            vec![],
+            &mut |codec, src, shards, dst| decompress_shards(codec, src, shards, dst, &mut progress),
+            available_threads(),
+            false,
+        )
+    }
+
+    /// Load (decompress) compressed asset archive at runtime using up to `threads` threads, returning an error if loading fails.
+    ///
+    /// Shards (see the `shard_size` option of the `include_dir!` macro) are decompressed concurrently, and asset checksums are verified concurrently.
+    /// Archives without shards are decompressed on a single thread, as are all archives on targets without threads (WebAssembly without the atomics proposal).
+    /// Use [`std::thread::available_parallelism`] to get a reasonable number of threads.
+    ///
+    /// See [`NamedArchive::try_load`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread cannot be spawned.
+    pub fn try_load_parallel<C: Codec + Sync>(compressed: CompressedNamedArchive<'_, C>, threads: usize) -> Result<Self, LoadError> {
+        Self::load_into_buffer(
+            compressed,
+            Lookup::Hash,
+            vec![],
+            &mut |codec, src, shards, dst| decompress_shards_parallel(codec, src, shards, dst, threads),
+            threads,
+            false,
+        )
+    }
+
+    /// Load (decompress) compressed asset archive at runtime into the given `buffer`, returning an error if loading fails.
+    ///
+    /// The content of `buffer` is discarded, but its allocation is reused.
+    /// Since the archive doesn't keep excess capacity, the capacity of `buffer` should be exactly [`CompressedNamedArchive::uncompressed_data_size`]
+    /// (plus [`CompressedNamedArchive::align`] minus one if assets are aligned) to avoid reallocation.
+    /// This allows a memory pool or arena to provide the storage for the decompressed data, which can be retrieved with [`NamedArchive::into_buffer`] once the archive is no longer needed.
+    ///
+    /// See [`NamedArchive::try_load`].
+    pub fn try_load_into<C: Codec>(compressed: CompressedNamedArchive<'_, C>, buffer: alloc::vec::Vec<u8>) -> Result<Self, LoadError> {
+        Self::load_into_buffer(
+            compressed,
+            Lookup::Hash,
+            buffer,
+            &mut |codec, src, shards, dst| decompress_shards(codec, src, shards, dst, &mut |_, _| {}),
+            available_threads(),
+            false,
+        )
+    }
+
+    /// Load (decompress) compressed asset archive at runtime, verifying the checksum of each asset the first time it is accessed instead of all at once.
+    ///
+    /// This spreads the cost of verification across the lifetime of the program, and skips it for assets which are never used, which helps with large archives.
+    /// Listing asset names (e.g. with [`NamedArchive::names`]) doesn't count as use, but methods returning asset data do,
+    /// e.g. [`NamedArchive::assets`], [`NamedArchive::list_dir`] and [`NamedArchive::tree`] verify every asset they return.
+    /// The result of verification is cached, so later accesses cost nothing extra.
+    /// Archives with truncated checksums (see the `checksum_size` option) are verified while loading, because their full checksums have to be computed anyway.
+    ///
+    /// Accessing an asset whose checksum does not match panics, use [`NamedArchive::try_get`] to handle this error instead.
+    pub fn try_load_lazy<C: Codec>(compressed: CompressedNamedArchive<'_, C>) -> Result<Self, LoadError> {
+        Self::load_into_buffer(
+            compressed,
+            Lookup::Hash,
+            vec![],
+            &mut |codec, src, shards, dst| decompress_shards(codec, src, shards, dst, &mut |_, _| {}),
+            available_threads(),
+            true,
+        )
+    }
+
+    /// Load (decompress) an archive from an untrusted source, e.g. a downloaded archive file, using at most about `max_memory` bytes of heap memory.
+    ///
+    /// In addition to the checks of [`NamedArchive::try_load`], this
+    /// - fails before decompressing anything if [`CompressedNamedArchive::memory_needed`] exceeds `max_memory`,
+    /// - decompresses the data into a buffer of its declared size, so that no codec can write more data than declared,
+    /// - fails if two assets have the same name.
+    ///
+    /// All offsets and sizes are checked, so malformed input results in an error instead of a panic, which makes this suitable for fuzzing.
+    /// Archives in `dev` mode are loaded without assets, since nothing is read from the file system.
+    /// Checksums only detect corruption, use [signatures](crate::signature) to check where an archive comes from.
+    pub fn try_load_untrusted<C: Codec>(mut compressed: CompressedNamedArchive<'_, C>, max_memory: usize) -> Result<Self, LoadError> {
+        compressed.directory = None;
+        let needed = compressed.memory_needed();
+        if needed > max_memory {
+            return Err(LoadError::MemoryLimit { needed, limit: max_memory });
+        }
+        let archive = Self::load_into_buffer(compressed, Lookup::Hash, vec![], &mut decompress_shards_bounded, available_threads(), false)?;
+        {
+            let mut names = hashbrown::HashSet::with_capacity(archive.entries.len());
+            for (name, _) in archive.entries.iter() {
+                if !names.insert(name) {
+                    return Err(LoadError::DuplicateName { name: name.to_owned() });
+                }
+            }
+        }
+        Ok(archive)
+    }
+
+    /// Load an archive included with `compression = "uncompressed"` without copying the asset data, which is used where it is instead.
+    ///
+    /// The assets stay in the executable image and use no heap memory.
+    /// The operating system pages them in when they are first accessed, and can evict them again under memory pressure.
+    /// This suits very large assets which are only read.
+    ///
+    /// Checksums are not verified, since that would read all assets up front.
+    /// Only archives with truncated checksums (see the `checksum_size` option) are verified, because their full checksums have to be computed.
+    /// Modifying the archive, e.g. with [`NamedArchive::take`], copies the asset data to the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if loading fails, see [`NamedArchive::load`].
+    pub fn load_static(compressed: CompressedNamedArchive<'static, crate::codec::Uncompressed>) -> Self {
+        match Self::try_load_static(compressed) {
+            Ok(archive) => archive,
+            Err(err) => panic!("couldn't load asset archive: {err}"),
+        }
+    }
+
+    /// Load an uncompressed archive without copying the asset data, returning an error if loading fails.
+    ///
+    /// See [`NamedArchive::load_static`].
+    pub fn try_load_static(compressed: CompressedNamedArchive<'static, crate::codec::Uncompressed>) -> Result<Self, LoadError> {
+        if compressed.directory.is_some() {
+            return Self::try_load(compressed);
+        }
+        if compressed.align > MAX_ALIGN || !compressed.align.max(1).is_power_of_two() {
+            return Err(LoadError::InvalidAlignment);
+        }
+        // shards of uncompressed data are just consecutive parts of it
+        let data = compressed.data;
+        if data.len() != u32_to_usize(compressed.uncompressed_data_size) {
+            return Err(LoadError::SizeMismatch {
+                section: Section::Data,
+                expected: u32_to_usize(compressed.uncompressed_data_size),
+                actual: data.len(),
+            });
+        }
+        let (data, trailer) = data.split_at(u32_to_usize(compressed.assets_size()?));
+        let Metadata { names, ranges, checksums } = compressed.metadata(trailer)?;
+        let checksums = if u32_to_usize(compressed.checksum_size) == core::mem::size_of::<checksum::Checksum>() {
+            checksums.as_chunks().0.to_vec()
+        } else {
+            verify_checksums(data, &names, &ranges, checksums, u32_to_usize(compressed.checksum_size), available_threads())?
+        };
+
+        let entries: alloc::vec::Vec<_> = ranges
+            .into_iter()
+            .zip(checksums)
+            .zip(names.iter())
+            .map(|((range, checksum), name)| Entry {
+                range,
+                checksum,
+                private: is_private(compressed.private, name),
+                unverified: false.into(),
+            })
+            .collect();
+        let private_assets = entries.iter().filter(|entry| entry.private).count();
+        let entries = Entries::with_perfect_hash(names, entries, compressed.perfect_hash, Lookup::Hash).ok_or(LoadError::InvalidPerfectHash)?;
+        Ok(Self {
+            data: buffer_from_static(data),
+            borrowed: true,
+            entries,
+            #[cfg(feature = "std")]
+            directory: None,
+            #[cfg(feature = "std")]
+            modified: std::time::SystemTime::now(),
+            mime_types: compressed.mime_types.iter().map(|(ext, mime)| ((*ext).into(), (*mime).into())).collect(),
+            private: compressed.private.iter().map(|name| (*name).into()).collect(),
+            private_assets,
+        })
+    }
+
+    /// Load an archive, decompressing the asset data with `decompress` and verifying checksums on up to `threads` threads.
+    ///
+    /// If `lazy` is set, full checksums are only verified on first access instead, see [`NamedArchive::try_load_lazy`].
+    fn load_into_buffer<C: Codec>(
+        compressed: CompressedNamedArchive<'_, C>,
+        lookup: Lookup,
+        mut buffer: alloc::vec::Vec<u8>,
+        decompress: &mut DecompressShards<'_, C>,
+        threads: usize,
+        lazy: bool,
+    ) -> Result<Self, LoadError> {
+        // names, sizes, and checksums are read by `CompressedNamedArchive::metadata`
         let CompressedNamedArchive {
-            codec,
+            codec: _,
             data: compressed_data,
             uncompressed_data_size,
-            compressed_names,
-            uncompressed_names_size,
-            checksums,
-            compressed_sizes,
+            compressed_names: _,
+            uncompressed_names_size: _,
+            checksums: _,
+            checksum_size,
+            compressed_sizes: _,
+            uncompressed_sizes_size: _,
+            align,
+            metadata_size: _,
+            signature: _,
+            directory,
+            mime_types,
+            private,
+            perfect_hash,
+            shards,
         } = compressed;
+        let mime_types = mime_types.iter().map(|(ext, mime)| ((*ext).into(), (*mime).into())).collect();
+        let private: alloc::vec::Vec<_> = private.iter().map(|name| (*name).into()).collect();
+
+        #[cfg(not(feature = "std"))]
+        if directory.is_some() {
+            panic!("`dev` mode needs the `std` feature of include_assets_decode");
+        }
+        #[cfg(feature = "std")]
+        if let Some(directory) = directory {
+            buffer.clear();
+            let mut archive = Self {
+                data: buffer_from_vec(buffer),
+                borrowed: false,
+                entries: Entries::new(lookup),
+                directory: Some(directory.into()),
+                modified: std::time::SystemTime::now(),
+                mime_types,
+                private,
+                private_assets: 0,
+            };
+            archive.reload().map_err(LoadError::Io)?;
+            return Ok(archive);
+        }
+
+        if align > MAX_ALIGN || !align.max(1).is_power_of_two() {
+            return Err(LoadError::InvalidAlignment);
+        }
+        let align = align.max(1);
+        // assets are shifted by less than `align` bytes to align the buffer
+        if uncompressed_data_size.checked_add(align - 1).is_none() {
+            return Err(LoadError::SizeMismatch {
+                section: Section::Data,
+                expected: u32_to_usize(u32::MAX),
+                actual: u32_to_usize(uncompressed_data_size) + u32_to_usize(align - 1),
+            });
+        }
 
-        // decompress data
-        let data = codec.decompress_with_length(compressed_data, u32_to_usize(uncompressed_data_size));
+        // decompress data, reserving space to align it afterwards
+        let shards = crate::common::shards(shards, compressed_data.len(), u32_to_usize(uncompressed_data_size))?;
+        if align > 1 {
+            buffer.clear();
+            buffer.reserve_exact(u32_to_usize(uncompressed_data_size) + u32_to_usize(align - 1));
+        }
+        decompress(&compressed.codec, compressed_data, &shards, &mut buffer)?;
+        let mut data = buffer;
 
-        // decompress names and data ranges
-        let names = decompress_names(&codec, compressed_names, uncompressed_names_size);
-        let ranges = decompress_ranges(&codec, compressed_sizes, checksums.len());
-        assert_eq!(names.len(), ranges.len(), "number of asset names should equal number of asset data ranges");
+        // decompress names and data ranges, or parse them from the end of the data
+        let assets_size = compressed.assets_size()?;
+        let Metadata { names, ranges, checksums } = compressed.metadata(&data[u32_to_usize(assets_size)..])?;
 
-        // Data ranges were constructed in decompress_ranges.
-        // We know that they are all non-overlapping, increasing, and don't leave any space.
-        // We know the first range starts at 0.
-        // The final range should end where the data ends.
-        assert_eq!(ranges.last().map(|range| range.end).unwrap_or(0), uncompressed_data_size);
+        let lazy = lazy && u32_to_usize(checksum_size) == core::mem::size_of::<checksum::Checksum>();
+        let checksums = if lazy {
+            checksums.as_chunks().0.to_vec()
+        } else {
+            verify_checksums(&data, &names, &ranges, checksums, u32_to_usize(checksum_size), threads)?
+        };
+
+        data.truncate(u32_to_usize(assets_size));
+        let (data, offset) = match align {
+            1 => (buffer_from_vec(data), 0),
+            _ => aligned_buffer_from_vec(data, u32_to_usize(assets_size), u32_to_usize(align)),
+        };
+        let offset = u32::try_from(offset).expect("offset is less than the alignment");
+        let entries: alloc::vec::Vec<_> = ranges
+            .into_iter()
+            .zip(checksums)
+            .zip(names.iter())
+            .map(|((range, checksum), name)| Entry {
+                range: range.start + offset..range.end + offset,
+                checksum,
+                private: is_private(&private, name),
+                unverified: lazy.into(),
+            })
+            .collect();
+        let private_assets = entries.iter().filter(|entry| entry.private).count();
+        let entries = Entries::with_perfect_hash(names, entries, perfect_hash, lookup).ok_or(LoadError::InvalidPerfectHash)?;
+
+        Ok(Self {
+            data,
+            borrowed: false,
+            entries,
+            #[cfg(feature = "std")]
+            directory: None,
+            #[cfg(feature = "std")]
+            modified: std::time::SystemTime::now(),
+            mime_types,
+            private,
+            private_assets,
+        })
+    }
 
-        let ranges: std::collections::HashMap<_, _> = names.into_iter().zip(ranges.into_iter()).collect();
+    /// Verify the signature of a compressed asset archive, then load (decompress) it.
+    ///
+    /// The archive must have been signed at compile time, see the `signing_key` option.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LoadError::Signature`] if the archive is not signed or the signature does not match `public_key`,
+    /// and any other error of [`NamedArchive::try_load`] if loading fails despite the signature being correct.
+    #[cfg(feature = "signature")]
+    pub fn load_verified<C: Codec + Copy + Into<crate::codec::AnyCodec>>(
+        compressed: CompressedNamedArchive<'_, C>,
+        public_key: &crate::signature::PublicKey,
+    ) -> Result<Self, LoadError> {
+        let message = crate::signature::message(&compressed);
+        crate::signature::verify(&message, compressed.signature, public_key).map_err(LoadError::Signature)?;
+        // The checksums are part of the signed message and are checked during loading.
+        Self::try_load(compressed)
+    }
 
-        Self { data, ranges }
+    #[cfg(feature = "std")]
+    /// Load (decompress) an archive file at runtime
+    ///
+    /// The file must be in the format described in [`crate::file`], e.g. written by [`CompressedNamedArchive::write_to`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is not a valid archive file, or cannot be loaded (see [`NamedArchive::try_load`]).
+    pub fn load_file<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+        Self::from_reader(std::fs::File::open(path)?)
+    }
+
+    #[cfg(feature = "std")]
+    /// Load (decompress) an archive file from a reader at runtime
+    ///
+    /// See [`NamedArchive::load_file`].
+    pub fn from_reader<R: std::io::Read>(mut reader: R) -> std::io::Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        Self::try_load(crate::file::parse(&bytes)?).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+
+    #[cfg(feature = "std")]
+    /// Directory from which the assets are read at runtime, if the archive was included with `dev = true` in a debug build.
+    pub fn directory(&self) -> Option<&std::path::Path> {
+        self.directory.as_deref()
+    }
+
+    #[cfg(feature = "std")]
+    /// Time when the assets were loaded, or last changed by [`NamedArchive::reload`] or [`NamedArchive::overlay_dir`].
+    ///
+    /// Included assets can't change while the program is running, so this is used for `Last-Modified` headers when serving assets over HTTP.
+    pub fn modified(&self) -> std::time::SystemTime {
+        self.modified
+    }
+
+    #[cfg(feature = "std")]
+    /// Read all assets from the directory again, if the archive was included with `dev = true` in a debug build.
+    ///
+    /// Does nothing for archives with included assets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be read, see [`NamedArchive::overlay_dir`].
+    /// In this case, the archive is empty afterwards.
+    pub fn reload(&mut self) -> std::io::Result<()> {
+        if let Some(directory) = self.directory.clone() {
+            let mut buffer = buffer_into_vec(core::mem::take(&mut self.data));
+            buffer.clear();
+            self.data = buffer_from_vec(buffer);
+            self.borrowed = false;
+            self.entries.clear();
+            self.overlay_dir(directory)?;
+        }
+        Ok(())
+    }
+
+    /// Watch the directory from which assets are read for changes, if the archive was included with `dev = true` in a debug build.
+    ///
+    /// Returns `None` for archives with included assets, which cannot change.
+    /// See [`AssetWatcher`](crate::watch::AssetWatcher).
+    #[cfg(feature = "hot-reload")]
+    pub fn watch(&self) -> Option<notify::Result<crate::watch::AssetWatcher>> {
+        self.directory.as_deref().map(crate::watch::AssetWatcher::new)
+    }
+
+    /// Mount the archive as a read-only file system at `mountpoint`, which must be an existing directory (Linux only).
+    ///
+    /// Assets are files named by their path, with directories for `/` separators.
+    /// Mounting requires the `CAP_SYS_ADMIN` capability, or the `fusermount3` (or `fusermount`) program of libfuse.
+    /// See [`Mount`](crate::fuse::Mount).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `/dev/fuse` can't be opened or mounting fails.
+    #[cfg(all(feature = "fuse", target_os = "linux"))]
+    pub fn mount<P: AsRef<std::path::Path>>(&'static self, mountpoint: P) -> std::io::Result<crate::fuse::Mount> {
+        crate::fuse::Mount::new(self, mountpoint.as_ref())
+    }
+
+    #[cfg(feature = "std")]
+    /// Shadow assets with files from a directory at runtime.
+    ///
+    /// All files in `dir` (recursively, following symbolic links) are added to the archive, named by their path relative to `dir` with `/` as separator.
+    /// Files with the same name as an included asset replace it, e.g. for local overrides or modding.
+    /// All other assets remain available.
+    ///
+    /// The original data of replaced assets is not released.
+    /// Since the buffer holding the assets grows, they are no longer aligned as set by the `align` option of the `include_dir!` macro.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or any file in it cannot be read, if a path is not UTF-8, or if the archive would exceed 4 GiB.
+    pub fn overlay_dir<P: AsRef<std::path::Path>>(&mut self, dir: P) -> std::io::Result<()> {
+        let mut files = vec![];
+        read_dir_recursive(dir.as_ref(), "", &mut vec![], &mut files)?;
+        let mut buffer = buffer_into_vec(core::mem::take(&mut self.data));
+        let mut result = Ok(());
+        for (name, data) in files {
+            let start = buffer.len();
+            let end = start + data.len();
+            let (Ok(start), Ok(end)) = (u32::try_from(start), u32::try_from(end)) else {
+                result = Err(std::io::Error::other("archive size would exceed 4 GiB"));
+                break;
+            };
+            buffer.extend_from_slice(&data);
+            let checksum = checksum::compute_checksum(&data);
+            let private = is_private(&self.private, &name);
+            self.entries.insert(
+                &name,
+                Entry {
+                    range: start..end,
+                    checksum,
+                    private,
+                    unverified: false.into(),
+                },
+            );
+        }
+        self.data = buffer_from_vec(buffer);
+        self.borrowed = false;
+        self.entries.shrink_to_fit();
+        self.private_assets = self.entries.iter().filter(|(_, entry)| entry.private).count();
+        self.modified = std::time::SystemTime::now();
+        result
     }
 
     /// Get the content of the asset with the given `name`.
     ///
     /// Returns `None` if the archive does not contain an asset with this `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the archive was loaded with [`NamedArchive::try_load_lazy`] and the checksum of the asset does not match.
     pub fn get<'a>(&'a self, name: &str) -> Option<&'a [u8]> {
-        self.ranges.get(name).map(|range| &self.data[u32_to_usize_range(range)])
+        let (name, entry) = self.entries.get_key_value(name)?;
+        Some(checked_data(&self.data, name, entry))
+    }
+
+    /// Get the content of the asset with the given `name`, returning an error if its checksum does not match.
+    ///
+    /// Only archives loaded with [`NamedArchive::try_load_lazy`] verify checksums on access, this is the same as [`NamedArchive::get`] otherwise.
+    pub fn try_get<'a>(&'a self, name: &str) -> Result<Option<&'a [u8]>, LoadError> {
+        self.entries
+            .get_key_value(name)
+            .map(|(name, entry)| verified_data(&self.data, name, entry))
+            .transpose()
+    }
+
+    /// Get part of the content of the asset with the given `name`, e.g. `archive.slice("video.webm", 1024..2048)`.
+    ///
+    /// Returns `None` if the archive does not contain an asset with this `name`, or if the range is out of bounds.
+    pub fn slice<R: core::ops::RangeBounds<usize>>(&self, name: &str, range: R) -> Option<&[u8]> {
+        self.get(name)?.get((range.start_bound().cloned(), range.end_bound().cloned()))
+    }
+
+    /// Get the contents of the assets with the given `names`, in the same order.
+    ///
+    /// Returns `None` if the archive does not contain an asset with one of these names.
+    pub fn get_many<const N: usize>(&self, names: [&str; N]) -> Option<[&[u8]; N]> {
+        let mut result = [&[][..]; N];
+        for (data, name) in result.iter_mut().zip(names) {
+            *data = self.get(name)?;
+        }
+        Some(result)
+    }
+
+    /// Get the contents of the assets with the given `names`, in the same order.
+    ///
+    /// Like [`NamedArchive::get_many`], but for a number of names that is not known at compile time.
+    /// Returns `None` if the archive does not contain an asset with one of these names.
+    pub fn get_all<I: IntoIterator<Item = S>, S: AsRef<str>>(&self, names: I) -> Option<alloc::vec::Vec<&[u8]>> {
+        names.into_iter().map(|name| self.get(name.as_ref())).collect()
+    }
+
+    /// Get the content of the asset with the given `name` as [`bytes::Bytes`], which can be cloned cheaply.
+    ///
+    /// No data is copied, the returned `Bytes` share the archive's buffer.
+    /// Returns `None` if the archive does not contain an asset with this `name`.
+    #[cfg(feature = "bytes")]
+    pub fn get_bytes(&self, name: &str) -> Option<bytes::Bytes> {
+        let (name, entry) = self.entries.get_key_value(name)?;
+        checked_data(&self.data, name, entry);
+        Some(self.data.slice(u32_to_usize_range(&entry.range)))
+    }
+
+    /// Get the content of the asset with the given relative `path`.
+    ///
+    /// The path is normalized: `.` components are ignored, and separators match regardless of the platform the archive was built on.
+    /// Returns `None` if the archive does not contain an asset with this path, or if the path contains `..`, is absolute, or is not UTF-8.
+    ///
+    /// [`NamedArchive::get`] and [`Index`](core::ops::Index) only take `&str` names, not `AsRef<Path>` keys:
+    /// they are available without `std`, and normalizing every string lookup would slow them down.
+    /// Use this method (or [`NamedArchive::contains_path`]) for paths instead.
+    #[cfg(feature = "std")]
+    pub fn get_path<P: AsRef<std::path::Path>>(&self, path: P) -> Option<&[u8]> {
+        let name = normalize_path(path.as_ref())?;
+        self.get(&name).or_else(|| {
+            // archives built on Windows use backslashes as separators
+            self.get(&name.replace('/', "\\"))
+        })
+    }
+
+    /// Returns true if an asset with the given relative `path` is included in the archive.
+    ///
+    /// See [`NamedArchive::get_path`].
+    #[cfg(feature = "std")]
+    pub fn contains_path<P: AsRef<std::path::Path>>(&self, path: P) -> bool {
+        self.get_path(path).is_some()
+    }
+
+    /// Get the checksum of the asset with the given `name`.
+    ///
+    /// The checksum was computed at compile time, so this is cheap.
+    /// It is suitable e.g. for HTTP ETags or cache keys.
+    /// The checksum algorithm may change in future versions (see [`crate::checksum`]).
+    ///
+    /// Returns `None` if the archive does not contain an asset with this `name`.
+    pub fn checksum(&self, name: &str) -> Option<&checksum::Checksum> {
+        self.entries.get(name).map(|entry| &entry.checksum)
+    }
+
+    /// Get the checksum of the asset with the given `name` as lowercase hexadecimal string.
+    ///
+    /// See [`NamedArchive::checksum`].
+    pub fn checksum_hex(&self, name: &str) -> Option<alloc::string::String> {
+        self.checksum(name).map(|checksum| hexhex::Hex::new(checksum).to_string())
+    }
+
+    /// Recompute the checksums of all assets, including private ones, and compare them to the stored checksums, e.g. for a `--self-check` flag.
+    ///
+    /// This detects corruption of the executable or archive file that happened after loading, or that loading did not check
+    /// because checksums are verified lazily or not at all (see [`NamedArchive::try_load_lazy`] and [`NamedArchive::load_static`]).
+    /// Returns one report per asset, sorted by name. Unlike accessing a corrupted asset, this never panics.
+    pub fn verify_all(&self) -> alloc::vec::Vec<AssetCheck<'_>> {
+        let mut checks: alloc::vec::Vec<_> = self
+            .entries
+            .iter()
+            .map(|(name, entry)| {
+                let actual = checksum::compute_checksum(&self.data[u32_to_usize_range(&entry.range)]);
+                if actual == entry.checksum {
+                    entry.unverified.store(false, core::sync::atomic::Ordering::Relaxed);
+                }
+                AssetCheck {
+                    name,
+                    expected: &entry.checksum,
+                    actual,
+                }
+            })
+            .collect();
+        checks.sort_unstable_by_key(|check| check.name);
+        checks
+    }
+
+    /// Get the MIME type of the asset with the given `name`, guessed from its file extension.
+    ///
+    /// MIME types given in the `mime_types` option of the `include_dir!` macro take precedence.
+    /// Returns `"application/octet-stream"` for unknown extensions, and `None` if the archive does not contain an asset with this `name`.
+    #[cfg(feature = "mime")]
+    pub fn mime(&self, name: &str) -> Option<&str> {
+        let (name, _) = self.entries.get_key_value(name)?;
+        Some(self.mime_of(name))
+    }
+
+    #[cfg(feature = "mime")]
+    fn mime_of(&self, name: &str) -> &str {
+        let extension = file_extension(name).map(str::to_lowercase);
+        if let Some(extension) = extension {
+            if let Some((_, mime)) = self.mime_types.iter().find(|(ext, _)| *ext == extension) {
+                return mime;
+            }
+        }
+        mime_guess::from_path(name).first_raw().unwrap_or("application/octet-stream")
+    }
+
+    /// Get the content and everything else known about the asset with the given `name` at once, e.g. to serve it via HTTP.
+    ///
+    /// Returns `None` if the archive does not contain an asset with this `name`.
+    pub fn entry(&self, name: &str) -> Option<AssetEntry<'_>> {
+        let (name, entry) = self.entries.get_key_value(name)?;
+        Some(AssetEntry {
+            name,
+            data: checked_data(&self.data, name, entry),
+            checksum: &entry.checksum,
+            #[cfg(feature = "mime")]
+            mime: self.mime_of(name),
+        })
     }
 
-    /// Returns the number of assets included in the archive.
+    /// Get the content of the asset with the given `name` as a string slice.
+    ///
+    /// Returns `None` if the archive does not contain an asset with this `name`, or `Some(Err(_))` if the content is not valid UTF-8.
+    pub fn get_str<'a>(&'a self, name: &str) -> Option<Result<&'a str, core::str::Utf8Error>> {
+        self.get(name).map(core::str::from_utf8)
+    }
+
+    /// Get a copy of the content of the asset with the given `name` as a string.
+    ///
+    /// See [`NamedArchive::get_str`].
+    pub fn get_string(&self, name: &str) -> Option<Result<alloc::string::String, core::str::Utf8Error>> {
+        self.get_str(name).map(|result| result.map(ToOwned::to_owned))
+    }
+
+    /// Returns the data structure used to look up assets by name.
+    pub fn lookup(&self) -> Lookup {
+        self.entries.lookup()
+    }
+
+    /// Switch to another data structure to look up assets by name, see [`Lookup`].
+    ///
+    /// A perfect hash table generated at compile time is discarded when switching away from [`Lookup::Hash`], and cannot be restored.
+    pub fn set_lookup(&mut self, lookup: Lookup) {
+        self.entries.set_lookup(lookup);
+    }
+
+    /// Returns the number of assets included in the archive, not counting private assets.
     pub fn number_of_assets(&self) -> usize {
-        self.ranges.len()
+        self.entries.len() - self.private_assets
+    }
+
+    /// Returns the number of assets included in the archive, same as [`NamedArchive::number_of_assets`].
+    pub fn len(&self) -> usize {
+        self.number_of_assets()
+    }
+
+    /// Returns true if the archive contains no assets, except maybe private ones.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
     /// Returns an iterator of all asset names and contents in unspecified order.
-    pub fn assets(&self) -> impl Iterator<Item = (&str, &[u8])> + ExactSizeIterator + '_ {
-        self.ranges.iter().map(|(name, range)| (name.as_ref(), &self.data[u32_to_usize_range(range)]))
+    ///
+    /// Private assets (see the `private` option of the `include_dir!` macro) are left out, here and in all other methods listing assets.
+    /// They can only be looked up by name.
+    pub fn assets(&self) -> Assets<'_> {
+        Assets {
+            data: &self.data,
+            entries: self.entries.iter(),
+            remaining: self.len(),
+        }
+    }
+
+    /// Returns an iterator of all asset names and contents where the name starts with `prefix`, in unspecified order.
+    ///
+    /// This is a plain string comparison, so `iter_prefix("textures/")` yields all assets in the directory `textures` and its subdirectories, whereas `iter_prefix("tex")` would also yield `"text.txt"`.
+    pub fn iter_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a [u8])> + 'a {
+        self.assets().filter(move |(name, _)| name.starts_with(prefix))
+    }
+
+    /// Returns an iterator of all asset names and contents where the file name has the given `extension`, in unspecified order.
+    ///
+    /// The extension is given without the leading dot and compared ASCII case-insensitively, so `with_extension("png")` also yields `"logo.PNG"`.
+    pub fn with_extension<'a>(&'a self, extension: &'a str) -> impl Iterator<Item = (&'a str, &'a [u8])> + 'a {
+        self.assets()
+            .filter(move |(name, _)| file_extension(name).is_some_and(|ext| ext.eq_ignore_ascii_case(extension)))
+    }
+
+    /// Returns the entries directly in the virtual directory `dir`, sorted by name.
+    ///
+    /// Directories are separated by `/`, and `""` is the root directory.
+    /// Subdirectories are listed once, regardless of how many assets they contain.
+    /// The list is empty if there is no such directory.
+    pub fn list_dir<'a>(&'a self, dir: &str) -> alloc::vec::Vec<DirEntry<'a>> {
+        let dir = dir.trim_end_matches('/');
+        let mut entries = alloc::collections::BTreeMap::new();
+        for (name, data) in self.assets() {
+            let rest = if dir.is_empty() {
+                name
+            } else {
+                match name.strip_prefix(dir).and_then(|rest| rest.strip_prefix('/')) {
+                    Some(rest) => rest,
+                    None => continue,
+                }
+            };
+            let entry = match rest.split_once('/') {
+                None => DirEntry::File { name, data },
+                Some((subdir, _)) => DirEntry::Dir {
+                    name: &name[..name.len() - rest.len() + subdir.len()],
+                },
+            };
+            entries.insert(entry.name(), entry);
+        }
+        entries.into_values().collect()
+    }
+
+    /// Returns all assets as a tree of virtual directories.
+    ///
+    /// Directories are separated by `/`.
+    /// The root directory has the name `""`.
+    pub fn tree(&self) -> TreeDir<'_> {
+        let mut assets: alloc::vec::Vec<_> = self.assets().collect();
+        assets.sort_unstable_by_key(|(name, _)| *name);
+        let mut root = TreeDir {
+            name: "",
+            dirs: vec![],
+            files: vec![],
+        };
+        for (name, data) in assets {
+            root.insert(name, name, data);
+        }
+        root
+    }
+
+    /// Remove the asset with the given `name` from the archive and return its content.
+    ///
+    /// The memory used by the asset is released, which is useful for large assets that are only needed once.
+    /// Since all assets share one buffer, this moves the data of other assets, so it takes time proportional to the size of the archive.
+    /// Moved assets are no longer aligned as set by the `align` option of the `include_dir!` macro.
+    ///
+    /// Returns `None` if the archive does not contain an asset with this `name`.
+    pub fn take(&mut self, name: &str) -> Option<alloc::vec::Vec<u8>> {
+        checked_data(&self.data, name, self.entries.get(name)?);
+        let entry = self.entries.remove(name)?;
+        if entry.private {
+            self.private_assets -= 1;
+        }
+        let range = entry.range;
+        let (start, end) = (u32_to_usize(range.start), u32_to_usize(range.end));
+        let len = range.end - range.start;
+        let data = self.data[start..end].to_vec();
+        let mut buffer = buffer_into_vec(core::mem::take(&mut self.data));
+        buffer.copy_within(end.., start);
+        buffer.truncate(buffer.len() - data.len());
+        self.data = buffer_from_vec(buffer);
+        self.borrowed = false;
+        for other in self.entries.values_mut() {
+            if other.range.start >= range.end && len > 0 {
+                other.range.start -= len;
+                other.range.end -= len;
+            }
+        }
+        self.entries.shrink_to_fit();
+        Some(data)
+    }
+
+    /// Consume the archive and return an iterator of all asset names and copies of their contents in unspecified order, except private assets.
+    ///
+    /// Safe Rust cannot split one allocation into several, so each asset except one is copied into its own allocation as the iterator advances.
+    /// Every byte is copied at most once, but the allocation of the archive is only released once the iterator is dropped,
+    /// so the data is held up to twice in the meantime.
+    /// With feature `bytes`, [`NamedArchive::into_asset_bytes`] yields the assets without copying them.
+    pub fn into_copied_assets(self) -> CopiedAssets {
+        let (data, mut ranges) = self.into_ranges();
+        // Assets are split off the end of the data, so the last asset has to come first.
+        // Empty assets may start where the next asset starts, so they are ordered by their end as well.
+        ranges.sort_unstable_by_key(|(_, range)| core::cmp::Reverse((range.start, range.end)));
+        CopiedAssets {
+            data: buffer_into_vec(data),
+            ranges: ranges.into_iter(),
+        }
+    }
+
+    /// Consume the archive and return an iterator of all asset names and contents as [`bytes::Bytes`] in unspecified order, except private assets.
+    ///
+    /// No data is copied, the assets share the archive's buffer, which is released once all of them are dropped.
+    #[cfg(feature = "bytes")]
+    pub fn into_asset_bytes(self) -> AssetBytes {
+        let (data, mut ranges) = self.into_ranges();
+        // assets are split off the start of the data
+        ranges.sort_unstable_by_key(|(_, range)| (range.start, range.end));
+        AssetBytes {
+            data,
+            offset: 0,
+            ranges: ranges.into_iter(),
+        }
+    }
+
+    /// Verify the assets which were not accessed yet, and split the archive into its data and the names and ranges of all assets except private ones.
+    fn into_ranges(self) -> (Buffer, alloc::vec::Vec<NamedRange>) {
+        self.assets().for_each(drop);
+        let ranges = self
+            .entries
+            .into_vec()
+            .into_iter()
+            .filter(|(_, entry)| !entry.private)
+            .map(|(name, entry)| (name, entry.range))
+            .collect();
+        (self.data, ranges)
+    }
+
+    /// Consume the archive and keep only the assets with the given `names`, which are then looked up by their position in `names`.
+    ///
+    /// Asset names, checksums, and the data structure used to look them up are released, as is the data of all other assets.
+    /// This is useful in long-running processes that know which assets they need after startup.
+    /// The data of the given assets is copied once.
+    pub fn into_resolved<S: AsRef<str>>(self, names: &[S]) -> ResolvedAssets {
+        let mut data = vec![];
+        let ranges = names
+            .iter()
+            .map(|name| {
+                let asset = self.get(name.as_ref())?;
+                let start = u32::try_from(data.len()).expect("resolved assets are at most as large as the archive");
+                data.extend_from_slice(asset);
+                let end = u32::try_from(data.len()).expect("resolved assets are at most as large as the archive");
+                Some(start..end)
+            })
+            .collect();
+        ResolvedAssets {
+            data: buffer_from_vec(data),
+            ranges,
+        }
+    }
+
+    /// Returns the approximate number of bytes of heap memory used by the archive.
+    ///
+    /// This includes the data of all assets, their names, and the data structure used to look them up (see [`NamedArchive::lookup`]).
+    /// The memory used by a hash map is estimated, and overhead of the allocator is not included.
+    /// With feature `std`, the lookup data structure is only counted once it has been built by the first lookup.
+    pub fn memory_usage(&self) -> usize {
+        let mime_types: usize = self.mime_types.iter().map(|(ext, mime)| heap_size(ext) + heap_size(mime)).sum();
+        let private: usize = self.private.iter().map(heap_size).sum();
+        #[cfg(feature = "std")]
+        let directory = self.directory.as_ref().map(|directory| directory.capacity()).unwrap_or(0);
+        #[cfg(not(feature = "std"))]
+        let directory = 0;
+        (if self.borrowed { 0 } else { self.data.len() })
+            + self.entries.memory_usage()
+            + self.mime_types.capacity()
+                * core::mem::size_of::<(
+                    smartstring::SmartString<smartstring::LazyCompact>,
+                    smartstring::SmartString<smartstring::LazyCompact>,
+                )>()
+            + mime_types
+            + self.private.capacity() * core::mem::size_of::<smartstring::SmartString<smartstring::LazyCompact>>()
+            + private
+            + directory
+    }
+
+    /// Consume the archive and return the buffer holding the data of all assets, e.g. to reuse its allocation with [`NamedArchive::try_load_into`].
+    ///
+    /// With feature `bytes`, the data is copied if [`NamedArchive::get_bytes`] handles to it still exist.
+    pub fn into_buffer(self) -> alloc::vec::Vec<u8> {
+        buffer_into_vec(self.data)
     }
 
     /// Returns true if an asset with the given `name` is included in the archive.
@@ -112,9 +1425,505 @@ impl NamedArchive {
         self.get(name).is_some()
     }
 
-    /// Returns an iterator of all asset names in unspecified order.
-    pub fn names(&self) -> impl Iterator<Item = &str> + ExactSizeIterator + '_ {
-        self.ranges.keys().map(|s| s.as_ref())
+    /// Returns an iterator of all asset names in unspecified order, except private assets.
+    ///
+    /// Unlike [`NamedArchive::assets`], this never accesses asset data, so it doesn't verify checksums of lazily loaded archives.
+    pub fn names(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
+        AssetNames {
+            entries: self.entries.iter(),
+            remaining: self.len(),
+        }
+    }
+
+    /// Name of the asset at position `index` in the order of [`NamedArchive::names`].
+    ///
+    /// Takes constant time unless the archive has private assets, which have to be skipped one by one.
+    #[cfg(feature = "ffi")]
+    pub(crate) fn name_at(&self, index: usize) -> Option<&str> {
+        if self.private_assets == 0 {
+            return self.entries.get_index(index).map(|(name, _)| name);
+        }
+        self.entries.iter().filter(|(_, entry)| !entry.private).nth(index).map(|(name, _)| name)
+    }
+}
+
+#[cfg(feature = "std")]
+/// Archive being loaded on another thread, see [`NamedArchive::load_in_background`]
+///
+/// The handle is also a future resolving to the loaded archive (or the error if loading fails), which works with any async runtime.
+pub struct ArchiveHandle {
+    /// Thread loading the archive, `None` if it was loaded on the current thread
+    thread: Option<std::thread::JoinHandle<()>>,
+    shared: alloc::sync::Arc<std::sync::Mutex<Shared>>,
+}
+
+#[cfg(feature = "std")]
+/// State shared between an [`ArchiveHandle`] and the thread loading the archive
+struct Shared {
+    result: Option<std::thread::Result<Result<NamedArchive, LoadError>>>,
+    waker: Option<core::task::Waker>,
+}
+
+#[cfg(feature = "std")]
+impl ArchiveHandle {
+    /// Returns true if loading has finished, so that [`ArchiveHandle::join`] won't block.
+    pub fn is_finished(&self) -> bool {
+        self.lock().result.is_some()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Shared> {
+        self.shared.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Wait for the archive to be loaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if loading fails, just like [`NamedArchive::load`].
+    pub fn join(self) -> NamedArchive {
+        match self.try_join() {
+            Ok(archive) => archive,
+            Err(err) => panic!("couldn't load asset archive: {err}"),
+        }
+    }
+
+    /// Wait for the archive to be loaded, returning an error if loading fails.
+    pub fn try_join(self) -> Result<NamedArchive, LoadError> {
+        let Self { thread, shared } = self;
+        if let Some(Err(panic)) = thread.map(std::thread::JoinHandle::join) {
+            std::panic::resume_unwind(panic);
+        }
+        let result = shared.lock().unwrap_or_else(std::sync::PoisonError::into_inner).result.take();
+        match result.expect("loading has finished and the result was not taken by polling") {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::future::Future for ArchiveHandle {
+    type Output = Result<NamedArchive, LoadError>;
+
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let mut shared = self.lock();
+        match shared.result.take() {
+            Some(Ok(result)) => core::task::Poll::Ready(result),
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                core::task::Poll::Pending
+            }
+        }
+    }
+}
+
+/// Assets looked up by position instead of name, see [`NamedArchive::into_resolved`]
+pub struct ResolvedAssets {
+    data: Buffer,
+    ranges: alloc::vec::Vec<Option<core::ops::Range<u32>>>,
+}
+
+impl ResolvedAssets {
+    /// Get the content of the asset at position `index` in the names passed to [`NamedArchive::into_resolved`].
+    ///
+    /// Returns `None` if the index is out of bounds or the archive did not contain an asset with this name.
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        let range = self.ranges.get(index)?.as_ref()?;
+        Some(&self.data[u32_to_usize_range(range)])
+    }
+
+    /// Returns the number of names passed to [`NamedArchive::into_resolved`], including those of missing assets.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Returns true if no names were passed to [`NamedArchive::into_resolved`].
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+impl core::ops::Index<usize> for ResolvedAssets {
+    type Output = [u8];
+
+    /// Return the contents of the asset at the given position.
+    /// Panics if the asset is not present.
+    fn index(&self, index: usize) -> &[u8] {
+        match self.get(index) {
+            Some(data) => data,
+            None => panic!("asset #{index} not found"),
+        }
+    }
+}
+
+/// Iterator of asset names and contents, see [`NamedArchive::assets`]
+#[derive(Clone)]
+pub struct Assets<'a> {
+    data: &'a [u8],
+    entries: crate::lookup::Iter<'a, Entry>,
+    /// Number of assets that are not private among `entries`
+    remaining: usize,
+}
+
+impl<'a> Iterator for Assets<'a> {
+    type Item = (&'a str, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (name, entry) = self.entries.find(|(_, entry)| !entry.private)?;
+        self.remaining -= 1;
+        Some((name, checked_data(self.data, name, entry)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Assets<'_> {}
+
+/// Iterator of asset names, see [`NamedArchive::names`]
+struct AssetNames<'a> {
+    entries: crate::lookup::Iter<'a, Entry>,
+    /// Number of assets that are not private among `entries`
+    remaining: usize,
+}
+
+impl<'a> Iterator for AssetNames<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (name, _) = self.entries.find(|(_, entry)| !entry.private)?;
+        self.remaining -= 1;
+        Some(name)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for AssetNames<'_> {}
+
+/// Function decompressing the shards of the asset data, see [`NamedArchive::load_into_buffer`]
+type DecompressShards<'a, C> = dyn FnMut(&C, &[u8], &[Shard], &mut alloc::vec::Vec<u8>) -> Result<(), LoadError> + 'a;
+
+/// Name and data range of an asset, see [`NamedArchive::into_copied_assets`]
+type NamedRange = (smartstring::SmartString<smartstring::LazyCompact>, core::ops::Range<u32>);
+
+/// Iterator of asset names and copies of their contents, see [`NamedArchive::into_copied_assets`]
+pub struct CopiedAssets {
+    data: alloc::vec::Vec<u8>,
+    ranges: alloc::vec::IntoIter<NamedRange>,
+}
+
+impl Iterator for CopiedAssets {
+    type Item = (alloc::string::String, alloc::vec::Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (name, range) = self.ranges.next()?;
+        let start = u32_to_usize(range.start);
+        // Ranges don't overlap and are processed from back to front, so this only drops unused data (e.g. shadowed by an overlay) after the asset.
+        self.data.truncate(u32_to_usize(range.end));
+        let data = if start == 0 {
+            core::mem::take(&mut self.data)
+        } else {
+            self.data.split_off(start)
+        };
+        Some((name.into(), data))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ranges.size_hint()
+    }
+}
+
+impl ExactSizeIterator for CopiedAssets {}
+
+/// Iterator of asset names and contents sharing the archive's buffer, see [`NamedArchive::into_asset_bytes`]
+#[cfg(feature = "bytes")]
+pub struct AssetBytes {
+    data: bytes::Bytes,
+    /// Position of `data` in the buffer of the archive
+    offset: usize,
+    ranges: alloc::vec::IntoIter<NamedRange>,
+}
+
+#[cfg(feature = "bytes")]
+impl Iterator for AssetBytes {
+    type Item = (alloc::string::String, bytes::Bytes);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (name, range) = self.ranges.next()?;
+        let (start, end) = (u32_to_usize(range.start), u32_to_usize(range.end));
+        // Ranges don't overlap and are processed from front to back, so this only drops padding and unused data before the asset.
+        drop(self.data.split_to(start - self.offset));
+        let data = self.data.split_to(end - start);
+        self.offset = end;
+        Some((name.into(), data))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ranges.size_hint()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl ExactSizeIterator for AssetBytes {}
+
+/// Asset in a [`NamedArchive`] with its content and properties, see [`NamedArchive::entry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetEntry<'a> {
+    /// Full name of the asset
+    pub name: &'a str,
+    /// Content of the asset
+    pub data: &'a [u8],
+    /// Checksum of the content, see [`NamedArchive::checksum`]
+    pub checksum: &'a checksum::Checksum,
+    /// MIME type, see [`NamedArchive::mime`]
+    #[cfg(feature = "mime")]
+    pub mime: &'a str,
+}
+
+impl AssetEntry<'_> {
+    /// Size of the content in bytes
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Checksum of the content as lowercase hexadecimal string, see [`NamedArchive::checksum_hex`]
+    pub fn checksum_hex(&self) -> alloc::string::String {
+        hexhex::Hex::new(self.checksum).to_string()
+    }
+}
+
+/// Result of verifying the checksum of an asset, see [`NamedArchive::verify_all`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetCheck<'a> {
+    /// Full name of the asset
+    pub name: &'a str,
+    /// Checksum stored in the archive, see [`NamedArchive::checksum`]
+    pub expected: &'a checksum::Checksum,
+    /// Checksum of the current content
+    pub actual: checksum::Checksum,
+}
+
+impl AssetCheck<'_> {
+    /// Returns true if the checksums match, i.e. the asset is intact.
+    pub fn is_ok(&self) -> bool {
+        *self.expected == self.actual
+    }
+}
+
+impl core::fmt::Display for AssetCheck<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if self.is_ok() {
+            write!(f, "{}: ok", self.name)
+        } else {
+            write!(
+                f,
+                "{}: checksum mismatch: expected {}, got {}",
+                self.name,
+                hexhex::Hex::new(self.expected),
+                hexhex::Hex::new(&self.actual)
+            )
+        }
+    }
+}
+
+/// Entry of a virtual directory in a [`NamedArchive`], see [`NamedArchive::list_dir`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirEntry<'a> {
+    /// An asset
+    File {
+        /// Full name of the asset
+        name: &'a str,
+        /// Content of the asset
+        data: &'a [u8],
+    },
+    /// A subdirectory containing at least one asset
+    Dir {
+        /// Full path of the directory (without trailing `/`)
+        name: &'a str,
+    },
+}
+
+impl<'a> DirEntry<'a> {
+    /// Full name of the entry
+    pub fn name(&self) -> &'a str {
+        match self {
+            DirEntry::File { name, .. } | DirEntry::Dir { name } => name,
+        }
+    }
+
+    /// Last component of the name of the entry
+    pub fn file_name(&self) -> &'a str {
+        let name = self.name();
+        name.rsplit('/').next().unwrap_or(name)
+    }
+}
+
+/// Directory in the tree view of a [`NamedArchive`], see [`NamedArchive::tree`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeDir<'a> {
+    /// Full path of the directory (without trailing `/`)
+    pub name: &'a str,
+    /// Subdirectories, sorted by name
+    pub dirs: alloc::vec::Vec<TreeDir<'a>>,
+    /// Assets directly in this directory, sorted by name
+    pub files: alloc::vec::Vec<TreeFile<'a>>,
+}
+
+/// Asset in the tree view of a [`NamedArchive`], see [`NamedArchive::tree`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeFile<'a> {
+    /// Full name of the asset
+    pub name: &'a str,
+    /// Content of the asset
+    pub data: &'a [u8],
+}
+
+impl<'a> TreeDir<'a> {
+    // Names are inserted in sorted order, so all assets in a subdirectory are inserted consecutively.
+    fn insert(&mut self, full_name: &'a str, rest: &'a str, data: &'a [u8]) {
+        match rest.split_once('/') {
+            None => self.files.push(TreeFile { name: full_name, data }),
+            Some((subdir, rest)) => {
+                let subdir_name = &full_name[..full_name.len() - rest.len() - 1];
+                debug_assert!(subdir_name.ends_with(subdir));
+                if self.dirs.last().map(|dir| dir.name) != Some(subdir_name) {
+                    self.dirs.push(TreeDir {
+                        name: subdir_name,
+                        dirs: vec![],
+                        files: vec![],
+                    });
+                }
+                self.dirs.last_mut().expect("directory was just pushed").insert(full_name, rest, data);
+            }
+        }
+    }
+
+    /// Last component of the path of the directory
+    pub fn file_name(&self) -> &'a str {
+        self.name.rsplit('/').next().unwrap_or(self.name)
+    }
+
+    /// Total size of all assets in this directory and its subdirectories
+    pub fn size(&self) -> usize {
+        self.files.iter().map(|file| file.data.len()).sum::<usize>() + self.dirs.iter().map(TreeDir::size).sum::<usize>()
+    }
+
+    /// Total number of assets in this directory and its subdirectories
+    pub fn number_of_assets(&self) -> usize {
+        self.files.len() + self.dirs.iter().map(TreeDir::number_of_assets).sum::<usize>()
+    }
+}
+
+impl<'a> TreeFile<'a> {
+    /// Last component of the name of the asset
+    pub fn file_name(&self) -> &'a str {
+        self.name.rsplit('/').next().unwrap_or(self.name)
+    }
+}
+
+impl core::fmt::Debug for NamedArchive {
+    /// Lists the names and sizes (in bytes) of all assets, sorted by name.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // sizes are taken from the ranges, so that formatting doesn't verify lazily loaded assets
+        let sizes: alloc::collections::BTreeMap<&str, usize> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| !entry.private)
+            .map(|(name, entry)| (name, u32_to_usize_range(&entry.range).len()))
+            .collect();
+        let mut debug = f.debug_struct("NamedArchive");
+        debug.field("assets", &sizes);
+        #[cfg(feature = "std")]
+        if let Some(directory) = &self.directory {
+            debug.field("directory", directory);
+        }
+        debug.finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a NamedArchive {
+    type Item = (&'a str, &'a [u8]);
+    type IntoIter = Assets<'a>;
+
+    fn into_iter(self) -> Assets<'a> {
+        self.assets()
+    }
+}
+
+impl IntoIterator for NamedArchive {
+    type Item = (alloc::string::String, alloc::vec::Vec<u8>);
+    type IntoIter = CopiedAssets;
+
+    /// Copies each asset into its own allocation, see [`NamedArchive::into_copied_assets`].
+    fn into_iter(self) -> CopiedAssets {
+        self.into_copied_assets()
+    }
+}
+
+impl<N: AsRef<str>, D: AsRef<[u8]>> FromIterator<(N, D)> for NamedArchive {
+    /// Build an archive from asset names and contents, e.g. for tests.
+    ///
+    /// If a name occurs more than once, the last asset with this name is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the total size of all assets exceeds 4 GiB.
+    fn from_iter<I: IntoIterator<Item = (N, D)>>(assets: I) -> Self {
+        let mut data = vec![];
+        let mut entries = Entries::new(Lookup::Hash);
+        for (name, content) in assets {
+            let content = content.as_ref();
+            let start = u32::try_from(data.len()).expect("archive size should not exceed 4 GiB");
+            data.extend_from_slice(content);
+            let end = u32::try_from(data.len()).expect("archive size should not exceed 4 GiB");
+            let checksum = checksum::compute_checksum(content);
+            entries.insert(
+                name.as_ref(),
+                Entry {
+                    range: start..end,
+                    checksum,
+                    private: false,
+                    unverified: false.into(),
+                },
+            );
+        }
+        entries.shrink_to_fit();
+        Self {
+            data: buffer_from_vec(data),
+            borrowed: false,
+            entries,
+            #[cfg(feature = "std")]
+            directory: None,
+            #[cfg(feature = "std")]
+            modified: std::time::SystemTime::now(),
+            mime_types: vec![],
+            private: vec![],
+            private_assets: 0,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NamedArchive {
+    /// Serializes the archive as a map from asset names to contents (as bytes), sorted by name.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        /// Asset data, serialized as bytes instead of a sequence of integers
+        struct Data<'a>(&'a [u8]);
+
+        impl serde::Serialize for Data<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.0)
+            }
+        }
+
+        let mut assets: alloc::vec::Vec<_> = self.assets().collect();
+        assets.sort_unstable_by_key(|(name, _)| *name);
+        serializer.collect_map(assets.into_iter().map(|(name, data)| (name, Data(data))))
     }
 }
 
@@ -130,3 +1939,181 @@ impl<S: AsRef<str>> core::ops::Index<S> for NamedArchive {
         }
     }
 }
+
+/// Number of bytes of asset data below which no further thread is used to verify checksums, since spawning it would take longer
+const MIN_BYTES_PER_THREAD: usize = 1 << 20;
+
+/// Number of threads available for verifying checksums
+fn available_threads() -> usize {
+    #[cfg(feature = "std")]
+    if HAS_THREADS {
+        return std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    }
+    1
+}
+
+/// Asset names, data ranges, and (possibly truncated) checksums of an archive
+struct Metadata<'m> {
+    names: Names,
+    ranges: alloc::vec::Vec<core::ops::Range<u32>>,
+    checksums: &'m [u8],
+}
+
+/// Parse the metadata at the end of the uncompressed data of a single-stream archive (see [`CompressedNamedArchive::metadata_size`]).
+fn parse_metadata(metadata: &[u8], checksum_size: u32, align: u32) -> Result<Metadata<'_>, LoadError> {
+    let (header, rest) = metadata.split_at_checked(12).ok_or(LoadError::InvalidMetadata)?;
+    let [number_of_assets, names_size, sizes_size] =
+        [0, 4, 8].map(|i| u32_to_usize(u32::from_le_bytes(header[i..i + 4].try_into().expect("slice has length 4"))));
+    let (names, rest) = rest.split_at_checked(names_size).ok_or(LoadError::InvalidMetadata)?;
+    let (sizes, checksums) = rest.split_at_checked(sizes_size).ok_or(LoadError::InvalidMetadata)?;
+    if split_checksums(checksums, checksum_size)?.len() != number_of_assets {
+        return Err(LoadError::InvalidMetadata);
+    }
+    Ok(Metadata {
+        names: parse_names(names)?,
+        ranges: parse_ranges(sizes, true, number_of_assets, align)?,
+        checksums,
+    })
+}
+
+/// Split the checksums of a compressed archive into the (possibly truncated) checksum of each asset.
+fn split_checksums(checksums: &[u8], checksum_size: u32) -> Result<core::slice::ChunksExact<'_, u8>, LoadError> {
+    let checksum_size = u32_to_usize(checksum_size);
+    if !(1..=core::mem::size_of::<checksum::Checksum>()).contains(&checksum_size) || !checksums.len().is_multiple_of(checksum_size) {
+        return Err(LoadError::InvalidChecksums);
+    }
+    Ok(checksums.chunks_exact(checksum_size))
+}
+
+/// Check the data of all assets against their checksums of `checksum_size` bytes each, using up to `threads` threads, and return their full checksums.
+///
+/// The assets are split into one contiguous chunk of about the same number of bytes per thread.
+/// If a thread cannot be spawned, its chunk is verified on the current thread.
+fn verify_checksums(
+    data: &[u8],
+    names: &Names,
+    ranges: &[core::ops::Range<u32>],
+    checksums: &[u8],
+    checksum_size: usize,
+    threads: usize,
+) -> Result<alloc::vec::Vec<checksum::Checksum>, LoadError> {
+    let verify = |indices: core::ops::Range<usize>| {
+        indices
+            .map(|i| {
+                let actual = checksum::compute_checksum(&data[u32_to_usize_range(&ranges[i])]);
+                checksum::compare(actual, &checksums[i * checksum_size..(i + 1) * checksum_size]).map_err(|mismatch| LoadError::ChecksumMismatch {
+                    name: names.get(i).to_string(),
+                    mismatch: alloc::boxed::Box::new(mismatch),
+                })
+            })
+            .collect::<Result<alloc::vec::Vec<_>, _>>()
+    };
+    let len = ranges.len();
+    let threads = threads.min(len).min(data.len() / MIN_BYTES_PER_THREAD);
+    if threads <= 1 || !HAS_THREADS {
+        return verify(0..len);
+    }
+    #[cfg(not(feature = "std"))]
+    unreachable!("threads are never used without std");
+    #[cfg(feature = "std")]
+    let bounds: alloc::vec::Vec<usize> = (0..=threads)
+        .map(|k| match k {
+            0 => 0,
+            k if k == threads => len,
+            k => ranges.partition_point(|range| u32_to_usize(range.end) <= data.len() / threads * k),
+        })
+        .collect();
+    #[cfg(feature = "std")]
+    std::thread::scope(|scope| {
+        let chunks: alloc::vec::Vec<_> = bounds
+            .windows(2)
+            .filter(|chunk| chunk[0] < chunk[1])
+            .map(|chunk| {
+                let (start, end) = (chunk[0], chunk[1]);
+                std::thread::Builder::new()
+                    .spawn_scoped(scope, move || verify(start..end))
+                    .map_err(|_| start..end)
+            })
+            .collect();
+        let mut result = alloc::vec::Vec::with_capacity(len);
+        for chunk in chunks {
+            result.extend(match chunk {
+                Ok(handle) => handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))?,
+                Err(indices) => verify(indices)?,
+            });
+        }
+        Ok(result)
+    })
+}
+
+/// Whether the asset `name` is one of the `private` names, or in one of the `private` directories (see [`CompressedNamedArchive::private`])
+fn is_private<S: AsRef<str>>(private: &[S], name: &str) -> bool {
+    private.iter().any(|private| match private.as_ref().strip_suffix('/') {
+        Some(dir) => name.strip_prefix(dir).is_some_and(|rest| rest.starts_with('/')),
+        None => name == private.as_ref(),
+    })
+}
+
+/// Extension of the file name of an asset, without the leading dot, like [`std::path::Path::extension`]
+fn file_extension(name: &str) -> Option<&str> {
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => Some(extension),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "std")]
+/// Read all files in `dir` and its subdirectories, naming them by their path relative to the base directory.
+///
+/// Symbolic links are followed, `ancestors` holds the canonical paths of the directories being read to detect cycles.
+fn read_dir_recursive(
+    dir: &std::path::Path,
+    prefix: &str,
+    ancestors: &mut alloc::vec::Vec<std::path::PathBuf>,
+    files: &mut alloc::vec::Vec<(alloc::string::String, alloc::vec::Vec<u8>)>,
+) -> std::io::Result<()> {
+    let canonical = std::fs::canonicalize(dir)?;
+    if ancestors.contains(&canonical) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("symbolic link cycle: '{}' leads back to '{}'", dir.display(), canonical.display()),
+        ));
+    }
+    ancestors.push(canonical);
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Non-UTF-8 file name: '{}'", path.display())))?;
+        let name = if prefix.is_empty() { file_name } else { format!("{prefix}/{file_name}") };
+        if std::fs::metadata(&path)?.is_dir() {
+            read_dir_recursive(&path, &name, ancestors, files)?;
+        } else {
+            files.push((name, std::fs::read(&path)?));
+        }
+    }
+    ancestors.pop();
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+/// Convert a relative path to an asset name with `/` separators.
+pub(crate) fn normalize_path(path: &std::path::Path) -> Option<alloc::string::String> {
+    let mut name = alloc::string::String::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::Normal(part) => {
+                if !name.is_empty() {
+                    name.push('/');
+                }
+                name.push_str(part.to_str()?);
+            }
+            std::path::Component::ParentDir | std::path::Component::RootDir | std::path::Component::Prefix(_) => return None,
+        }
+    }
+    Some(name)
+}
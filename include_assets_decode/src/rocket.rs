@@ -0,0 +1,72 @@
+use crate::serve::ServeArchive;
+
+// Rocket handler serving the assets of a NamedArchive, like Rocket's own FileServer.
+// User-facing documentation is in the include_assets crate.
+#[derive(Clone)]
+pub struct FileServer {
+    serve: ServeArchive<'static>,
+    rank: isize,
+}
+
+impl FileServer {
+    /// Rank of the generated route, the same as Rocket's `FileServer` by default
+    pub const DEFAULT_RANK: isize = 10;
+
+    /// Serve assets from a `&'static NamedArchive` or a configured [`ServeArchive`].
+    pub fn new<S: Into<ServeArchive<'static>>>(serve: S) -> Self {
+        FileServer {
+            serve: serve.into(),
+            rank: Self::DEFAULT_RANK,
+        }
+    }
+
+    /// Set the rank of the generated route.
+    pub fn rank(self, rank: isize) -> Self {
+        FileServer { rank, ..self }
+    }
+}
+
+impl From<FileServer> for std::vec::Vec<rocket::Route> {
+    fn from(server: FileServer) -> Self {
+        // Rocket answers HEAD requests with GET routes, omitting the body
+        vec![rocket::Route::ranked(server.rank, rocket::http::Method::Get, "/<path..>", server)]
+    }
+}
+
+#[rocket::async_trait]
+impl rocket::route::Handler for FileServer {
+    async fn handle<'r>(&self, request: &'r rocket::Request<'_>, data: rocket::Data<'r>) -> rocket::route::Outcome<'r> {
+        // strip the mount point from the raw path, which is decoded when looking up the asset
+        let path = request.uri().path().as_str();
+        let path = match request.route() {
+            Some(route) => path.strip_prefix(route.uri.base()).unwrap_or(path),
+            None => path,
+        };
+        let headers = request
+            .headers()
+            .iter()
+            .filter_map(|header| {
+                Some((
+                    http::HeaderName::from_bytes(header.name().as_str().as_bytes()).ok()?,
+                    http::HeaderValue::from_str(header.value()).ok()?,
+                ))
+            })
+            .collect();
+        let (parts, body) = self.serve.respond_to(&http::Method::GET, path, &headers).into_parts();
+        if parts.status == http::StatusCode::NOT_FOUND {
+            return rocket::route::Outcome::forward(data, rocket::http::Status::NotFound);
+        }
+        let mut response = rocket::Response::build();
+        response.status(rocket::http::Status::new(parts.status.as_u16()));
+        for (name, value) in parts.headers.iter() {
+            // Rocket sets the content length itself
+            if name != http::header::CONTENT_LENGTH {
+                if let Ok(value) = value.to_str() {
+                    response.raw_header(name.as_str().to_owned(), value.to_owned());
+                }
+            }
+        }
+        response.sized_body(body.len(), std::io::Cursor::new(body));
+        rocket::route::Outcome::Success(response.finalize())
+    }
+}
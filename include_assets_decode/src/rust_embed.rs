@@ -0,0 +1,31 @@
+// Support code for implementing rust-embed's RustEmbed trait with a NamedArchive.
+// User-facing documentation is in the include_assets crate (include_dir_rust_embed!).
+
+use crate::named::NamedArchive;
+
+pub use rust_embed::{EmbeddedFile, Filenames, RustEmbed};
+
+/// Get an asset with its metadata in the form used by rust-embed.
+///
+/// The hash in the metadata is the beginning of the asset's checksum instead of its SHA-256 hash, which is fine for `ETag` headers.
+/// The modification time is the time the archive was loaded, see [`NamedArchive::modified`].
+pub fn get(archive: &'static NamedArchive, file_path: &str) -> Option<EmbeddedFile> {
+    let data = archive.get(file_path)?;
+    let checksum = archive.checksum(file_path)?;
+    let hash = <[u8; 32]>::try_from(&checksum[..32]).expect("checksums have at least 32 bytes");
+    let modified = archive.modified().duration_since(std::time::UNIX_EPOCH).ok().map(|duration| duration.as_secs());
+    Some(EmbeddedFile {
+        data: std::borrow::Cow::Borrowed(data),
+        // the constructor depends on the `mime-guess` feature of rust-embed, this macro drops the MIME type without it
+        metadata: rust_embed::utils::__rust_embed_metadata!(hash, modified, None, archive.mime(file_path).unwrap_or("application/octet-stream")),
+    })
+}
+
+/// Names of all assets, sorted.
+///
+/// The list is leaked, so this should only be called once per archive.
+pub fn names(archive: &'static NamedArchive) -> &'static [&'static str] {
+    let mut names: std::vec::Vec<&'static str> = archive.names().collect();
+    names.sort_unstable();
+    names.leak()
+}
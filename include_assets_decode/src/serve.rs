@@ -0,0 +1,498 @@
+use crate::named::NamedArchive;
+
+/// Response body of the services implemented by [`ServeArchive`]
+pub type Body = http_body_util::Full<bytes::Bytes>;
+
+// Service serving the assets of a NamedArchive over HTTP.
+// User-facing documentation is in the include_assets crate.
+#[derive(Clone)]
+pub struct ServeArchive<'a> {
+    archive: &'a NamedArchive,
+    /// Configuration, shared between clones since services are cloned for each request by some frameworks
+    options: std::sync::Arc<Options>,
+}
+
+/// Configuration of a [`ServeArchive`]
+#[derive(Clone)]
+struct Options {
+    /// `Cache-Control` rules in the order they were added, the first matching rule applies
+    cache_control: std::vec::Vec<(CacheRule, http::HeaderValue)>,
+    /// `Cache-Control` header of assets not matching any rule
+    default_cache_control: Option<http::HeaderValue>,
+    /// Whether to serve precompressed variants of assets
+    precompressed: bool,
+    /// Prefixes of unknown asset names and the assets served for them instead, the first matching fallback applies
+    fallbacks: std::vec::Vec<(std::string::String, std::string::String)>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            cache_control: vec![],
+            default_cache_control: None,
+            precompressed: true,
+            fallbacks: vec![],
+        }
+    }
+}
+
+/// Content codings of precompressed variants and the suffixes of their asset names, in order of preference
+const ENCODINGS: [(&str, &str); 3] = [("br", ".br"), ("zstd", ".zst"), ("gzip", ".gz")];
+
+/// Assets to which a `Cache-Control` rule applies
+#[derive(Clone)]
+enum CacheRule {
+    /// Assets with this lowercase file extension
+    Extension(std::string::String),
+    /// Assets whose name starts with this prefix
+    Prefix(std::string::String),
+}
+
+impl CacheRule {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            CacheRule::Extension(extension) => {
+                let file_name = name.rsplit('/').next().unwrap_or(name);
+                file_name.rsplit_once('.').is_some_and(|(_, ext)| ext.eq_ignore_ascii_case(extension))
+            }
+            CacheRule::Prefix(prefix) => name.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+impl<'a> From<&'a NamedArchive> for ServeArchive<'a> {
+    fn from(archive: &'a NamedArchive) -> Self {
+        ServeArchive::new(archive)
+    }
+}
+
+impl<'a> ServeArchive<'a> {
+    /// Serve the assets of the archive.
+    pub fn new(archive: &'a NamedArchive) -> Self {
+        ServeArchive {
+            archive,
+            options: Default::default(),
+        }
+    }
+
+    /// Set the `Cache-Control` header of assets with the given file `extension` (without `.`, case-insensitive), e.g. `no-cache` for `html`.
+    ///
+    /// Rules are checked in the order they were added, the first matching rule applies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not a valid header value.
+    pub fn cache_control_for_extension(self, extension: &str, value: &str) -> Self {
+        self.cache_rule(CacheRule::Extension(extension.trim_start_matches('.').to_ascii_lowercase()), value)
+    }
+
+    /// Set the `Cache-Control` header of assets whose name starts with `prefix`, e.g. `public, max-age=31536000, immutable` for `assets/`.
+    ///
+    /// Rules are checked in the order they were added, the first matching rule applies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not a valid header value.
+    pub fn cache_control_for_prefix(self, prefix: &str, value: &str) -> Self {
+        self.cache_rule(CacheRule::Prefix(prefix.trim_start_matches('/').into()), value)
+    }
+
+    /// Set the `Cache-Control` header of assets which don't match any rule.
+    ///
+    /// By default, responses have no `Cache-Control` header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not a valid header value.
+    pub fn cache_control(mut self, value: &str) -> Self {
+        std::sync::Arc::make_mut(&mut self.options).default_cache_control = Some(header_value(value));
+        self
+    }
+
+    /// Whether to serve precompressed variants of assets, e.g. `app.js.br` or `app.js.gz` for `app.js` (enabled by default).
+    ///
+    /// The variant is chosen based on the `Accept-Encoding` header of the request, preferring `br`, then `zstd`, then `gzip`.
+    /// If the client accepts none of the available variants, the asset itself is served.
+    pub fn precompressed(mut self, enabled: bool) -> Self {
+        std::sync::Arc::make_mut(&mut self.options).precompressed = enabled;
+        self
+    }
+
+    /// Serve the asset `fallback` for unknown paths, e.g. `index.html` for single-page applications with client-side routing.
+    ///
+    /// See [`ServeArchive::fallback_for_prefix`].
+    pub fn fallback(self, fallback: &str) -> Self {
+        self.fallback_for_prefix("", fallback)
+    }
+
+    /// Serve the asset `fallback` for unknown paths below `prefix`, e.g. `admin/index.html` for `admin/`.
+    ///
+    /// Fallbacks are checked in the order they were added, the first one whose prefix matches applies.
+    /// The response is the same as for a request of the fallback asset itself.
+    /// Paths containing `..` are never answered with a fallback.
+    pub fn fallback_for_prefix(mut self, prefix: &str, fallback: &str) -> Self {
+        let prefix = prefix.trim_matches('/');
+        let prefix = if prefix.is_empty() {
+            std::string::String::new()
+        } else {
+            format!("{prefix}/")
+        };
+        let fallback = fallback.trim_start_matches('/').into();
+        std::sync::Arc::make_mut(&mut self.options).fallbacks.push((prefix, fallback));
+        self
+    }
+
+    /// Name of the asset to serve for the given asset name, taking fallbacks into account
+    fn resolve(&self, name: std::string::String) -> Option<std::string::String> {
+        if self.archive.checksum(&name).is_some() {
+            return Some(name);
+        }
+        self.options
+            .fallbacks
+            .iter()
+            .find(|(prefix, _)| name.starts_with(prefix.as_str()) || Some(name.as_str()) == prefix.strip_suffix('/'))
+            .map(|(_, fallback)| fallback.clone())
+            .filter(|fallback| self.archive.checksum(fallback).is_some())
+    }
+
+    fn cache_rule(mut self, rule: CacheRule, value: &str) -> Self {
+        std::sync::Arc::make_mut(&mut self.options).cache_control.push((rule, header_value(value)));
+        self
+    }
+
+    /// `Cache-Control` header of the asset with the given name
+    fn cache_control_of(&self, name: &str) -> Option<&http::HeaderValue> {
+        self.options
+            .cache_control
+            .iter()
+            .find(|(rule, _)| rule.matches(name))
+            .map(|(_, value)| value)
+            .or(self.options.default_cache_control.as_ref())
+    }
+
+    /// Build the response to an HTTP request.
+    ///
+    /// Only the method, the URI path and the headers of the request are used.
+    pub fn respond<B>(&self, request: &http::Request<B>) -> http::Response<bytes::Bytes> {
+        self.respond_to(request.method(), request.uri().path(), request.headers())
+    }
+
+    /// Build the response to a request with the given method, URI path and headers.
+    pub(crate) fn respond_to(&self, method: &http::Method, path: &str, headers: &http::HeaderMap) -> http::Response<bytes::Bytes> {
+        if method != http::Method::GET && method != http::Method::HEAD {
+            let mut response = status_response(http::StatusCode::METHOD_NOT_ALLOWED);
+            response.headers_mut().insert(http::header::ALLOW, http::HeaderValue::from_static("GET, HEAD"));
+            return response;
+        }
+        let Some(name) = asset_name(path).and_then(|name| self.resolve(name)) else {
+            return status_response(http::StatusCode::NOT_FOUND);
+        };
+        // precompressed variants of the asset, in order of preference
+        let variants: std::vec::Vec<(&str, std::string::String)> = ENCODINGS
+            .iter()
+            .filter(|_| self.options.precompressed)
+            .map(|(coding, suffix)| (*coding, format!("{name}{suffix}")))
+            .filter(|(_, variant)| self.archive.checksum(variant).is_some())
+            .collect();
+        let encoding = preferred_encoding(headers, variants.iter().map(|(coding, _)| *coding));
+        let stored_name = variants
+            .iter()
+            .find(|(coding, _)| Some(*coding) == encoding)
+            .map_or(name.as_str(), |(_, variant)| variant.as_str());
+
+        // headers of all responses for this asset, including 304 Not Modified
+        let mut representation_headers = http::HeaderMap::new();
+        let etag = entity_tag(self.archive.checksum(stored_name).expect("variant exists"));
+        representation_headers.insert(http::header::ETAG, etag.clone());
+        let last_modified = http::HeaderValue::from_str(&httpdate::fmt_http_date(self.archive.modified())).expect("HTTP dates are valid header values");
+        representation_headers.insert(http::header::LAST_MODIFIED, last_modified);
+        if let Some(cache_control) = self.cache_control_of(&name) {
+            representation_headers.insert(http::header::CACHE_CONTROL, cache_control.clone());
+        }
+        if !variants.is_empty() {
+            representation_headers.insert(http::header::VARY, http::HeaderValue::from_static("accept-encoding"));
+        }
+
+        if !is_modified(headers, &etag, self.archive.modified()) {
+            let mut response = http::Response::new(bytes::Bytes::new());
+            *response.status_mut() = http::StatusCode::NOT_MODIFIED;
+            *response.headers_mut() = representation_headers;
+            return response;
+        }
+        let mut data = self.archive.get_bytes(stored_name).expect("variant exists");
+        let mut response = http::Response::new(bytes::Bytes::new());
+        let size = data.len();
+        if let Some(range) = headers.get(http::header::RANGE).filter(|_| is_current(headers, &etag, self.archive.modified())) {
+            match byte_range(range, size) {
+                ByteRange::Full => {}
+                ByteRange::Partial(range) => {
+                    *response.status_mut() = http::StatusCode::PARTIAL_CONTENT;
+                    let content_range = format!("bytes {}-{}/{size}", range.start, range.end - 1);
+                    response.headers_mut().insert(
+                        http::header::CONTENT_RANGE,
+                        http::HeaderValue::from_str(&content_range).expect("valid header value"),
+                    );
+                    data = data.slice(range);
+                }
+                ByteRange::Unsatisfiable => {
+                    let mut response = status_response(http::StatusCode::RANGE_NOT_SATISFIABLE);
+                    let content_range = http::HeaderValue::from_str(&format!("bytes */{size}")).expect("valid header value");
+                    response.headers_mut().insert(http::header::CONTENT_RANGE, content_range);
+                    return response;
+                }
+            }
+        }
+        let content_type = self
+            .archive
+            .mime(&name)
+            .and_then(|mime| http::HeaderValue::from_str(mime).ok())
+            .unwrap_or(http::HeaderValue::from_static("application/octet-stream"));
+        response.headers_mut().insert(http::header::CONTENT_TYPE, content_type);
+        if let Some(encoding) = encoding {
+            response
+                .headers_mut()
+                .insert(http::header::CONTENT_ENCODING, http::HeaderValue::from_static(encoding));
+        }
+        response.headers_mut().insert(http::header::CONTENT_LENGTH, http::HeaderValue::from(data.len()));
+        response
+            .headers_mut()
+            .insert(http::header::ACCEPT_RANGES, http::HeaderValue::from_static("bytes"));
+        response.headers_mut().extend(representation_headers);
+        if method != http::Method::HEAD {
+            *response.body_mut() = data;
+        }
+        response
+    }
+}
+
+#[cfg(feature = "axum")]
+impl<B> tower_service::Service<http::Request<B>> for ServeArchive<'_> {
+    type Response = http::Response<Body>;
+    type Error = core::convert::Infallible;
+    type Future = core::future::Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut core::task::Context<'_>) -> core::task::Poll<Result<(), Self::Error>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        core::future::ready(Ok(self.respond(&request).map(Body::new)))
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl<B> hyper::service::Service<http::Request<B>> for ServeArchive<'_> {
+    type Response = http::Response<Body>;
+    type Error = core::convert::Infallible;
+    type Future = core::future::Ready<Result<Self::Response, Self::Error>>;
+
+    fn call(&self, request: http::Request<B>) -> Self::Future {
+        core::future::ready(Ok(self.respond(&request).map(Body::new)))
+    }
+}
+
+/// Convert a configured header value.
+fn header_value(value: &str) -> http::HeaderValue {
+    http::HeaderValue::from_str(value).unwrap_or_else(|_| panic!("invalid header value {value:?}"))
+}
+
+/// Response with the given status and its reason as body.
+fn status_response(status: http::StatusCode) -> http::Response<bytes::Bytes> {
+    let mut response = http::Response::new(bytes::Bytes::from_static(status.canonical_reason().unwrap_or_default().as_bytes()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(http::header::CONTENT_TYPE, http::HeaderValue::from_static("text/plain; charset=utf-8"));
+    response
+}
+
+/// Strong entity tag of an asset with the given checksum.
+///
+/// The first 128 bits of the checksum are plenty to tell versions of an asset apart.
+fn entity_tag(checksum: &crate::checksum::Checksum) -> http::HeaderValue {
+    let tag = format!("\"{}\"", hexhex::Hex::new(&checksum[..16]));
+    http::HeaderValue::from_str(&tag).expect("hexadecimal entity tags are valid header values")
+}
+
+/// Evaluate the `If-None-Match` and `If-Modified-Since` headers of a `GET` or `HEAD` request.
+///
+/// Returns `false` if the client's cached copy is still valid, i.e. the response should be `304 Not Modified`.
+/// As required by RFC 9110, `If-Modified-Since` is ignored if the request has an `If-None-Match` header.
+fn is_modified(headers: &http::HeaderMap, etag: &http::HeaderValue, modified: std::time::SystemTime) -> bool {
+    let mut if_none_match = headers.get_all(http::header::IF_NONE_MATCH).iter().peekable();
+    if if_none_match.peek().is_some() {
+        // weak comparison, i.e. `W/` prefixes are ignored
+        return !if_none_match
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|tag| tag.trim())
+            .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag).as_bytes() == etag.as_bytes());
+    }
+    let Some(since) = headers
+        .get(http::header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| httpdate::parse_http_date(value).ok())
+    else {
+        return true;
+    };
+    // HTTP dates have a resolution of one second
+    let seconds = |time: std::time::SystemTime| time.duration_since(std::time::UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+    seconds(modified) > seconds(since)
+}
+
+/// Choose the content coding of the response from the available `encodings` (in order of preference) based on the `Accept-Encoding` header.
+///
+/// Returns `None` for the identity coding, i.e. the uncompressed asset.
+fn preferred_encoding<'e, I: IntoIterator<Item = &'e str>>(headers: &http::HeaderMap, encodings: I) -> Option<&'e str> {
+    let accepted: std::vec::Vec<(&str, f32)> = headers
+        .get_all(http::header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|coding| {
+            let mut parts = coding.split(';').map(str::trim);
+            let coding = parts.next().filter(|coding| !coding.is_empty())?;
+            let quality = parts
+                .find_map(|parameter| parameter.strip_prefix("q=").or_else(|| parameter.strip_prefix("Q=")))
+                .map_or(Some(1.0), |quality| quality.parse::<f32>().ok())?;
+            Some((coding, quality))
+        })
+        .collect();
+    let quality = |coding: &str| {
+        accepted
+            .iter()
+            .find(|(accepted, _)| accepted.eq_ignore_ascii_case(coding))
+            .or_else(|| accepted.iter().find(|(accepted, _)| *accepted == "*"))
+            .map(|(_, quality)| *quality)
+    };
+    // the uncompressed asset is always acceptable, but any accepted coding is preferred unless identity is listed with a higher quality
+    let mut best = (None, quality("identity").unwrap_or(0.0));
+    for encoding in encodings {
+        let quality = quality(encoding).unwrap_or(0.0);
+        if quality > 0.0 && (best.0.is_none() && quality >= best.1 || quality > best.1) {
+            best = (Some(encoding), quality);
+        }
+    }
+    best.0
+}
+
+/// Evaluate the `If-Range` header of a request with a `Range` header.
+///
+/// Returns `true` if the range should be served, i.e. there is no `If-Range` header or it matches the current version of the asset.
+fn is_current(headers: &http::HeaderMap, etag: &http::HeaderValue, modified: std::time::SystemTime) -> bool {
+    let Some(if_range) = headers.get(http::header::IF_RANGE) else {
+        return true;
+    };
+    if if_range.as_bytes().starts_with(b"\"") {
+        // strong comparison
+        return if_range == etag;
+    }
+    if_range
+        .to_str()
+        .ok()
+        .and_then(|value| httpdate::parse_http_date(value).ok())
+        .is_some_and(|date| httpdate::fmt_http_date(date) == httpdate::fmt_http_date(modified))
+}
+
+/// Result of evaluating a `Range` header
+enum ByteRange {
+    /// Serve the complete asset, e.g. because the header is malformed or requests multiple ranges
+    Full,
+    /// Serve the given part of the asset
+    Partial(core::ops::Range<usize>),
+    /// Respond with `416 Range Not Satisfiable`
+    Unsatisfiable,
+}
+
+/// Evaluate a `Range` header for an asset of the given size.
+///
+/// Only single byte ranges are supported, requests for multiple ranges are answered with the complete asset.
+fn byte_range(header: &http::HeaderValue, size: usize) -> ByteRange {
+    let Some((start, end)) = header
+        .to_str()
+        .ok()
+        .and_then(|value| value.trim().strip_prefix("bytes="))
+        .filter(|range| !range.contains(','))
+        .and_then(|range| range.split_once('-'))
+    else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+    let parse = |value: &str| {
+        if value.bytes().all(|byte| byte.is_ascii_digit()) {
+            value.parse::<usize>().ok()
+        } else {
+            None
+        }
+    };
+    let range = if start.is_empty() {
+        // suffix range with the length of the last part
+        let Some(length) = parse(end) else {
+            return ByteRange::Full;
+        };
+        size.saturating_sub(length)..size
+    } else {
+        let Some(start) = parse(start) else {
+            return ByteRange::Full;
+        };
+        let end = if end.is_empty() {
+            size
+        } else {
+            match parse(end) {
+                Some(end) if end >= start => end.saturating_add(1).min(size),
+                _ => return ByteRange::Full,
+            }
+        };
+        start..end
+    };
+    if range.start < range.end {
+        ByteRange::Partial(range)
+    } else {
+        ByteRange::Unsatisfiable
+    }
+}
+
+/// Convert the path of a request URI to an asset name.
+///
+/// Percent-encoded characters are decoded, empty and `.` segments are ignored, and `index.html` is appended to directories.
+/// Returns `None` if the path contains `..` segments or is not valid UTF-8 after decoding.
+fn asset_name(path: &str) -> Option<std::string::String> {
+    let decoded = percent_decode(path)?;
+    let mut name = std::string::String::new();
+    for segment in decoded.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => return None,
+            segment => {
+                if !name.is_empty() {
+                    name.push('/');
+                }
+                name.push_str(segment);
+            }
+        }
+    }
+    if decoded.ends_with('/') || name.is_empty() {
+        if !name.is_empty() {
+            name.push('/');
+        }
+        name.push_str("index.html");
+    }
+    Some(name)
+}
+
+/// Decode percent-encoded bytes in a URI path.
+fn percent_decode(path: &str) -> Option<std::string::String> {
+    let mut bytes = std::vec::Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2)?;
+            bytes.push(u8::from_str_radix(core::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    std::string::String::from_utf8(bytes).ok()
+}
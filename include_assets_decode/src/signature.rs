@@ -0,0 +1,99 @@
+//! ed25519 signatures of compressed archives
+//!
+//! The signature is computed over the blake2b digest of all header fields and sections of a compressed archive (see [`message`]).
+//! Since the per-asset checksums are signed as well, verifying the signature and then the checksums ensures that the decompressed data has not been tampered with.
+
+use blake2::Digest as _;
+
+use crate::codec::{AnyCodec, Codec};
+use crate::named::CompressedNamedArchive;
+
+pub type Signature = [u8; 64];
+pub type PublicKey = [u8; 32];
+
+/// Prefix of every signed message, so that signatures of archives can't be mistaken for signatures of anything else made with the same key
+pub const DOMAIN: &[u8] = b"include_assets archive signature";
+
+/// Version of the signed message, hashed after [`DOMAIN`]
+///
+/// Signatures of messages of an earlier version don't verify.
+pub const MESSAGE_VERSION: u32 = 2;
+
+/// Compute the message which is signed for a compressed archive.
+///
+/// The message is the digest of [`DOMAIN`], [`MESSAGE_VERSION`], the codec tag and level, all size fields, all sections, the names of private assets, and the MIME type overrides,
+/// in this order, with the length of each variable-size part before it.
+/// Only the signature itself, the dev mode directory, and the perfect hash table (which is checked against the names when loading) are not signed.
+pub fn message<C: Codec + Copy + Into<AnyCodec>>(archive: &CompressedNamedArchive<'_, C>) -> [u8; 64] {
+    let mut hasher = blake2::Blake2b512::new();
+    let mut update_bytes = |bytes: &[u8]| {
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(bytes);
+    };
+    update_bytes(DOMAIN);
+    let codec: AnyCodec = archive.codec.into();
+    let fields = [
+        MESSAGE_VERSION,
+        codec.tag(),
+        codec.level() as u32,
+        archive.uncompressed_data_size,
+        archive.uncompressed_names_size,
+        archive.uncompressed_sizes_size,
+        archive.checksum_size,
+        archive.align,
+        archive.metadata_size,
+    ];
+    for field in fields {
+        update_bytes(&field.to_le_bytes());
+    }
+    for section in [
+        archive.data,
+        archive.compressed_names,
+        archive.compressed_sizes,
+        archive.checksums,
+        archive.shards,
+    ] {
+        update_bytes(section);
+    }
+    update_bytes(&(archive.private.len() as u64).to_le_bytes());
+    for name in archive.private {
+        update_bytes(name.as_bytes());
+    }
+    update_bytes(&(archive.mime_types.len() as u64).to_le_bytes());
+    for (extension, mime) in archive.mime_types {
+        update_bytes(extension.as_bytes());
+        update_bytes(mime.as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Reasons why signature verification may fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The archive does not contain a signature
+    Missing,
+    /// The public key is not a valid ed25519 key
+    InvalidKey,
+    /// The signature does not match the archive contents and public key
+    Mismatch,
+}
+
+impl core::fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            VerificationError::Missing => write!(f, "archive is not signed"),
+            VerificationError::InvalidKey => write!(f, "invalid public key"),
+            VerificationError::Mismatch => write!(f, "signature mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+/// Verify the signature of a message (see [`message`]).
+pub fn verify(message: &[u8; 64], signature: Option<&Signature>, public_key: &PublicKey) -> Result<(), VerificationError> {
+    let signature = signature.ok_or(VerificationError::Missing)?;
+    let key = ed25519_dalek::VerifyingKey::from_bytes(public_key).map_err(|_| VerificationError::InvalidKey)?;
+    key.verify_strict(message, &ed25519_dalek::Signature::from_bytes(signature))
+        .map_err(|_| VerificationError::Mismatch)
+}
@@ -0,0 +1,176 @@
+// Virtual file system abstraction over archives and other asset backends.
+// User-facing documentation is in the include_assets crate.
+
+use crate::named::{DirEntry, NamedArchive};
+
+pub trait AssetSource {
+    /// Get the content of the asset with the given `name`, or `None` if there is no such asset.
+    fn read(&self, name: &str) -> Option<std::borrow::Cow<'_, [u8]>>;
+
+    /// Returns the entries directly in the virtual directory `dir`, sorted by name.
+    ///
+    /// Directories are separated by `/`, and `""` is the root directory.
+    /// The list is empty if there is no such directory.
+    fn list(&self, dir: &str) -> std::vec::Vec<SourceEntry>;
+
+    /// Open the asset with the given `name` for reading, or return `None` if there is no such asset.
+    ///
+    /// By default, this reads the whole asset with [`AssetSource::read`].
+    fn open(&self, name: &str) -> Option<std::boxed::Box<dyn std::io::Read + '_>> {
+        let data = self.read(name)?;
+        Some(std::boxed::Box::new(std::io::Cursor::new(data)))
+    }
+
+    /// Returns true if there is an asset with the given `name`.
+    ///
+    /// By default, this reads the asset with [`AssetSource::read`].
+    fn exists(&self, name: &str) -> bool {
+        self.read(name).is_some()
+    }
+}
+
+/// Entry of a virtual directory, see [`AssetSource::list`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SourceEntry {
+    /// An asset, with its full name
+    File(std::string::String),
+    /// A subdirectory containing at least one asset, with its full path (without trailing `/`)
+    Dir(std::string::String),
+}
+
+impl SourceEntry {
+    /// Full name of the asset or directory
+    pub fn name(&self) -> &str {
+        match self {
+            SourceEntry::File(name) | SourceEntry::Dir(name) => name,
+        }
+    }
+
+    /// Last component of the name
+    pub fn file_name(&self) -> &str {
+        self.name().rsplit('/').next().unwrap_or(self.name())
+    }
+}
+
+impl From<DirEntry<'_>> for SourceEntry {
+    fn from(entry: DirEntry<'_>) -> Self {
+        match entry {
+            DirEntry::File { name, .. } => SourceEntry::File(name.into()),
+            DirEntry::Dir { name } => SourceEntry::Dir(name.into()),
+        }
+    }
+}
+
+impl AssetSource for NamedArchive {
+    fn read(&self, name: &str) -> Option<std::borrow::Cow<'_, [u8]>> {
+        self.get(name).map(std::borrow::Cow::Borrowed)
+    }
+
+    fn list(&self, dir: &str) -> std::vec::Vec<SourceEntry> {
+        self.list_dir(dir).into_iter().map(SourceEntry::from).collect()
+    }
+
+    fn open(&self, name: &str) -> Option<std::boxed::Box<dyn std::io::Read + '_>> {
+        let data = self.get(name)?;
+        Some(std::boxed::Box::new(data))
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        self.contains(name)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl AssetSource for crate::file::MappedArchive {
+    fn read(&self, name: &str) -> Option<std::borrow::Cow<'_, [u8]>> {
+        self.archive().read(name)
+    }
+
+    fn list(&self, dir: &str) -> std::vec::Vec<SourceEntry> {
+        self.archive().list(dir)
+    }
+
+    fn open(&self, name: &str) -> Option<std::boxed::Box<dyn std::io::Read + '_>> {
+        self.archive().open(name)
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        self.archive().exists(name)
+    }
+}
+
+// Two asset sources, where assets of the top source shadow those of the base source.
+// User-facing documentation is in the include_assets crate.
+#[derive(Debug, Clone)]
+pub struct Overlay<T, B> {
+    /// Source whose assets take precedence
+    pub top: T,
+    /// Source for all assets which are not in `top`
+    pub base: B,
+}
+
+impl<T: AssetSource, B: AssetSource> Overlay<T, B> {
+    /// Shadow the assets of `base` with those of `top`.
+    pub fn new(top: T, base: B) -> Self {
+        Self { top, base }
+    }
+}
+
+impl<T: AssetSource, B: AssetSource> AssetSource for Overlay<T, B> {
+    fn read(&self, name: &str) -> Option<std::borrow::Cow<'_, [u8]>> {
+        self.top.read(name).or_else(|| self.base.read(name))
+    }
+
+    /// Lists the entries of both sources, where a name is listed once even if it is in both.
+    fn list(&self, dir: &str) -> std::vec::Vec<SourceEntry> {
+        let mut entries = self.top.list(dir);
+        let top_names: std::collections::BTreeSet<std::string::String> = entries.iter().map(|entry| entry.name().to_owned()).collect();
+        entries.extend(self.base.list(dir).into_iter().filter(|entry| !top_names.contains(entry.name())));
+        entries.sort_unstable_by(|a, b| a.name().cmp(b.name()));
+        entries
+    }
+
+    fn open(&self, name: &str) -> Option<std::boxed::Box<dyn std::io::Read + '_>> {
+        self.top.open(name).or_else(|| self.base.open(name))
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        self.top.exists(name) || self.base.exists(name)
+    }
+}
+
+impl<S: AssetSource + ?Sized> AssetSource for &S {
+    fn read(&self, name: &str) -> Option<std::borrow::Cow<'_, [u8]>> {
+        (**self).read(name)
+    }
+
+    fn list(&self, dir: &str) -> std::vec::Vec<SourceEntry> {
+        (**self).list(dir)
+    }
+
+    fn open(&self, name: &str) -> Option<std::boxed::Box<dyn std::io::Read + '_>> {
+        (**self).open(name)
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        (**self).exists(name)
+    }
+}
+
+impl<S: AssetSource + ?Sized> AssetSource for std::boxed::Box<S> {
+    fn read(&self, name: &str) -> Option<std::borrow::Cow<'_, [u8]>> {
+        (**self).read(name)
+    }
+
+    fn list(&self, dir: &str) -> std::vec::Vec<SourceEntry> {
+        (**self).list(dir)
+    }
+
+    fn open(&self, name: &str) -> Option<std::boxed::Box<dyn std::io::Read + '_>> {
+        (**self).open(name)
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        (**self).exists(name)
+    }
+}
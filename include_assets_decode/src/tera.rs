@@ -0,0 +1,31 @@
+use crate::named::NamedArchive;
+
+/// Create a Tera instance with the assets whose names start with `prefix` as templates, see [`add_templates`].
+///
+/// # Errors
+///
+/// Returns an error if a template is not valid UTF-8 or cannot be parsed.
+pub fn load(archive: &NamedArchive, prefix: &str) -> tera::Result<tera::Tera> {
+    let mut tera = tera::Tera::default();
+    add_templates(&mut tera, archive, prefix)?;
+    Ok(tera)
+}
+
+/// Add the assets whose names start with `prefix` as templates, named by the rest of their name.
+///
+/// For example, with `prefix = "templates/"`, the asset `templates/base.html` is added as the template `base.html`.
+/// All templates are added at once, so they can extend and include each other regardless of their order.
+///
+/// # Errors
+///
+/// Returns an error if a template is not valid UTF-8 or cannot be parsed.
+pub fn add_templates(tera: &mut tera::Tera, archive: &NamedArchive, prefix: &str) -> tera::Result<()> {
+    let templates = archive
+        .iter_prefix(prefix)
+        .map(|(name, data)| {
+            let content = core::str::from_utf8(data).map_err(|err| tera::Error::chain(format!("template '{name}' is not valid UTF-8"), err))?;
+            Ok((&name[prefix.len()..], content))
+        })
+        .collect::<tera::Result<std::vec::Vec<_>>>()?;
+    tera.add_raw_templates(templates)
+}
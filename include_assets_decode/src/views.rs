@@ -0,0 +1,55 @@
+use crate::error::MissingAssetError;
+use crate::named::NamedArchive;
+use core::borrow::Borrow;
+
+/// Trait for enums whose variants are looked up in a [`NamedArchive`] at runtime.
+///
+/// This should _never_ be implemented manually, only derived.
+pub trait AssetView: Sized {
+    /// Name of the asset for each enum variant
+    const PATHS: &'static [&'static str];
+
+    /// Maps an enum variant to its position in declaration order.
+    fn index(&self) -> usize;
+}
+
+// Assets of a NamedArchive for each variant of an AssetView.
+// User-facing documentation is in the include_assets crate.
+pub struct EnumView<'a, E> {
+    data: alloc::vec::Vec<&'a [u8]>,
+    _spooky: core::marker::PhantomData<E>,
+}
+
+impl<'a, E: AssetView> EnumView<'a, E> {
+    /// Look up the assets of all variants in the archive, returning an error for the first missing asset (in declaration order).
+    pub fn bind(archive: &'a NamedArchive) -> Result<Self, MissingAssetError> {
+        let data: Result<_, MissingAssetError> = E::PATHS.iter().map(|&path| archive.get(path).ok_or(MissingAssetError { path })).collect();
+        Ok(EnumView {
+            data: data?,
+            _spooky: core::marker::PhantomData,
+        })
+    }
+
+    /// Look up the asset data corresponding to the enum variant.
+    pub fn get(&self, e: impl Borrow<E>) -> &'a [u8] {
+        self.data[e.borrow().index()]
+    }
+}
+
+impl<E: AssetView> core::ops::Index<E> for EnumView<'_, E> {
+    type Output = [u8];
+
+    /// Look up the asset data corresponding to the enum variant.
+    fn index(&self, e: E) -> &[u8] {
+        self.data[e.index()]
+    }
+}
+
+impl<E: AssetView> core::ops::Index<&E> for EnumView<'_, E> {
+    type Output = [u8];
+
+    /// Look up the asset data corresponding to the enum variant.
+    fn index(&self, e: &E) -> &[u8] {
+        self.data[e.index()]
+    }
+}
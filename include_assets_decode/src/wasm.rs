@@ -0,0 +1,20 @@
+// Helpers for passing assets to JavaScript on wasm32-unknown-unknown.
+// User-facing documentation is in the include_assets crate.
+
+/// Copy `data` into a new JavaScript `Uint8Array`.
+pub fn uint8_array(data: &[u8]) -> js_sys::Uint8Array {
+    js_sys::Uint8Array::from(data)
+}
+
+/// Create a JavaScript `Uint8Array` viewing `data` in WebAssembly memory, without copying.
+///
+/// # Safety
+///
+/// The view must not be modified from JavaScript, since Rust assumes the data to be immutable.
+/// Additionally, the view becomes empty (detached) when WebAssembly memory grows, i.e. when Rust code allocates memory,
+/// so it should be used (e.g. uploaded to WebGL or copied) before calling into Rust again.
+#[allow(unsafe_code)]
+pub unsafe fn uint8_array_view(data: &'static [u8]) -> js_sys::Uint8Array {
+    // SAFETY: the data lives for the rest of the program, the caller ensures it isn't modified
+    unsafe { js_sys::Uint8Array::view(data) }
+}
@@ -0,0 +1,66 @@
+//! Hot reloading of assets in `dev` mode
+//!
+//! Archives included with `dev = true` read their assets from a directory in debug builds.
+//! An [`AssetWatcher`] reports when files in this directory change, so the application can call [`NamedArchive::reload`](crate::named::NamedArchive::reload) and pick up the new data.
+
+use notify::Watcher as _;
+
+/// Watches the directory of a `dev` mode archive, see [`NamedArchive::watch`](crate::named::NamedArchive::watch)
+///
+/// Changes are reported as asset names (relative paths with `/` as separator).
+/// Watching stops when the watcher is dropped.
+pub struct AssetWatcher {
+    _watcher: notify::RecommendedWatcher,
+    changes: std::sync::mpsc::Receiver<std::string::String>,
+}
+
+impl AssetWatcher {
+    /// Start watching `directory` recursively.
+    pub fn new(directory: &std::path::Path) -> notify::Result<Self> {
+        let directory = directory.to_owned();
+        let (sender, changes) = std::sync::mpsc::channel();
+        let base = directory.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if !(event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove()) {
+                return;
+            }
+            for path in event.paths {
+                if let Some(name) = asset_name(&base, &path) {
+                    // the receiver may have been dropped, in which case nobody is interested in changes anymore
+                    let _ = sender.send(name);
+                }
+            }
+        })?;
+        watcher.watch(&directory, notify::RecursiveMode::Recursive)?;
+        Ok(Self { _watcher: watcher, changes })
+    }
+
+    /// Returns the names of all assets that changed since the last call, without blocking.
+    ///
+    /// An asset may be reported more than once.
+    pub fn changes(&self) -> impl Iterator<Item = std::string::String> + '_ {
+        self.changes.try_iter()
+    }
+
+    /// Blocks until an asset changes and returns its name.
+    ///
+    /// Returns `None` if the watcher stopped.
+    pub fn wait(&self) -> Option<std::string::String> {
+        self.changes.recv().ok()
+    }
+}
+
+/// Name of the asset at `path` within `base`, with `/` as separator
+fn asset_name(base: &std::path::Path, path: &std::path::Path) -> Option<std::string::String> {
+    // paths reported by notify may or may not be canonical
+    let relative = path
+        .strip_prefix(base)
+        .ok()
+        .map(std::path::Path::to_owned)
+        .or_else(|| path.canonicalize().ok()?.strip_prefix(base).ok().map(std::path::Path::to_owned))?;
+    let components: Option<std::vec::Vec<&str>> = relative.components().map(|component| component.as_os_str().to_str()).collect();
+    Some(components?.join("/"))
+}
@@ -1,79 +1,7 @@
-use anyhow::Context as _;
+pub use include_assets_build::do_not_use_this_directly::checksums_tokens;
+use include_assets_decode::codec::AnyCodec;
 
-use include_assets_decode::checksum::compute_checksum;
-use include_assets_decode::codec::Codec;
-
-pub fn compress_sizes<C: Codec + ?Sized, S: AsRef<str>, I: Iterator<Item = (S, usize)>>(codec: &C, sizes: I) -> anyhow::Result<std::vec::Vec<u8>> {
-    let mut sizes_vec = vec![];
-    for (name, size) in sizes {
-        let size: u32 = size
-            .try_into()
-            .with_context(|| format!("asset {} is too big ({} bytes)", name.as_ref(), size))?;
-        sizes_vec.extend_from_slice(&size.to_le_bytes());
-    }
-    // ensure that the uncompressed lengths aren't longer than 4 GiB (i.e. the length fits in a u32)
-    if u32::try_from(sizes_vec.len()).is_err() {
-        return Err(anyhow::Error::msg(format!(
-            "too many assets: size of uncompressed asset sizes is too big ({} bytes)",
-            sizes_vec.len()
-        )));
-    }
-    codec.compress(sizes_vec.as_slice()).context("couldn't compress asset data sizes")
-}
-
-pub fn compress_names<C: Codec + ?Sized, S: AsRef<str>, I: Iterator<Item = S>>(codec: &C, mut names: I) -> anyhow::Result<(std::vec::Vec<u8>, u32)> {
-    let mut uncompressed_names = vec![];
-    if let Some(first) = names.next() {
-        assert!(!first.as_ref().as_bytes().contains(&0));
-        uncompressed_names.extend_from_slice(first.as_ref().as_bytes());
-        for name in names {
-            assert!(!name.as_ref().as_bytes().contains(&0));
-            uncompressed_names.extend_from_slice(&[0u8]);
-            uncompressed_names.extend_from_slice(name.as_ref().as_bytes());
-        }
-    };
-    let uncompressed_size = u32::try_from(uncompressed_names.len())
-        .map_err(|_| anyhow::Error::msg(format!("uncompressed names are too long ({} bytes)", uncompressed_names.len())))?;
-    let compressed_names = codec.compress(uncompressed_names.as_slice()).context("couldn't compress asset names")?;
-    Ok((compressed_names, uncompressed_size))
-}
-
-/// Wrapper for `anyhow::Error`, required because `anyhow::Error` doesn't `impl std::error::Error`.
-#[derive(thiserror::Error, Debug)]
-#[error(transparent)]
-pub struct MyError(anyhow::Error);
-
-pub struct DynCodec<C> {
-    codec: C,
-}
-
-impl<C> DynCodec<C> {
-    pub fn new(codec: C) -> Self {
-        Self { codec }
-    }
-}
-
-impl<C: Codec> Codec for DynCodec<C> {
-    type CompressionError = MyError;
-    type DecompressionError = MyError;
-
-    fn compress(&self, data: &[u8]) -> Result<std::vec::Vec<u8>, MyError> {
-        self.codec.compress(data).map_err(anyhow::Error::msg).map_err(MyError)
-    }
-
-    fn decompress_checked(&self, src: &[u8], dst: &mut [u8]) -> Result<(), MyError> {
-        self.codec.decompress_checked(src, dst).map_err(anyhow::Error::msg).map_err(MyError)
-    }
-}
-
-pub fn parse_codec(
-    compression: Option<syn::Lit>,
-    level: Option<syn::Lit>,
-) -> (
-    Box<dyn Codec<CompressionError = MyError, DecompressionError = MyError>>,
-    proc_macro2::TokenStream,
-    proc_macro2::TokenStream,
-) {
+pub fn parse_codec(compression: Option<syn::Lit>, level: Option<syn::Lit>) -> (AnyCodec, proc_macro2::TokenStream, proc_macro2::TokenStream) {
     let compression_string = if let Some(lit) = compression {
         if let syn::Lit::Str(s) = lit {
             s.value()
@@ -82,6 +10,8 @@ pub fn parse_codec(
         }
     } else {
         let available = [
+            #[cfg(feature = "wasm")]
+            "deflate",
             #[cfg(feature = "zstd")]
             "zstd",
             #[cfg(feature = "lz4")]
@@ -98,11 +28,10 @@ pub fn parse_codec(
             if level.is_some() {
                 panic!("compression 'uncompressed' does not have levels");
             } else {
-                let codec = DynCodec::new(include_assets_decode::codec::Uncompressed {});
+                let codec = AnyCodec::Uncompressed(include_assets_decode::codec::Uncompressed {});
                 let expr = quote::quote! { ::include_assets::do_not_use_this_directly::codec::Uncompressed{} };
                 let type_expr = quote::quote! { ::include_assets::do_not_use_this_directly::codec::Uncompressed };
-                let boxed_codec: Box<dyn Codec<CompressionError = MyError, DecompressionError = MyError>> = Box::new(codec);
-                (boxed_codec, expr, type_expr)
+                (codec, expr, type_expr)
             }
         }
         #[cfg(feature = "lz4")]
@@ -110,11 +39,10 @@ pub fn parse_codec(
             if level.is_some() {
                 panic!("compression 'lz4' does not (currently) support levels");
             } else {
-                let codec = DynCodec::new(include_assets_decode::codec::Lz4 {});
+                let codec = AnyCodec::Lz4(include_assets_decode::codec::Lz4 {});
                 let expr = quote::quote! {::include_assets::do_not_use_this_directly::codec::Lz4{} };
                 let type_expr = quote::quote! { ::include_assets::do_not_use_this_directly::codec::Lz4 };
-                let boxed_codec: Box<dyn Codec<CompressionError = MyError, DecompressionError = MyError>> = Box::new(codec);
-                (boxed_codec, expr, type_expr)
+                (codec, expr, type_expr)
             }
         }
         #[cfg(feature = "deflate")]
@@ -130,11 +58,10 @@ pub fn parse_codec(
                 }
                 _ => panic!("Invalid compression level"),
             };
-            let codec = DynCodec::new(include_assets_decode::codec::Deflate { level });
+            let codec = AnyCodec::Deflate(include_assets_decode::codec::Deflate { level });
             let expr = quote::quote! {::include_assets::do_not_use_this_directly::codec::Deflate{ level: #level } };
             let type_expr = quote::quote! { ::include_assets::do_not_use_this_directly::codec::Deflate };
-            let boxed_codec: Box<dyn Codec<CompressionError = MyError, DecompressionError = MyError>> = Box::new(codec);
-            (boxed_codec, expr, type_expr)
+            (codec, expr, type_expr)
         }
         #[cfg(feature = "zstd")]
         "zstd" => {
@@ -149,20 +76,107 @@ pub fn parse_codec(
                 }
                 _ => panic!("Invalid compression level"),
             };
-            let codec = DynCodec::new(include_assets_decode::codec::Zstd { level });
+            let codec = AnyCodec::Zstd(include_assets_decode::codec::Zstd { level });
             let expr = quote::quote_spanned! {proc_macro2::Span::mixed_site()=> ::include_assets::do_not_use_this_directly::codec::Zstd{ level: #level } };
             let type_expr = quote::quote! { ::include_assets::do_not_use_this_directly::codec::Zstd };
-            let boxed_codec: Box<dyn Codec<CompressionError = MyError, DecompressionError = MyError>> = Box::new(codec);
-            (boxed_codec, expr, type_expr)
+            (codec, expr, type_expr)
         }
         s => panic!("invalid/unsupported compression '{s}'"),
     }
 }
 
-pub fn checksums_tokens<T: AsRef<[u8]>, I: Iterator<Item = T>>(asset_data: I) -> proc_macro2::TokenStream {
-    let checksums: std::vec::Vec<_> = asset_data
-        .map(|data| compute_checksum(data.as_ref()))
-        .map(|checksum| quote::quote! { [#(#checksum),*] })
-        .collect();
-    quote::quote! {&[#(#checksums),*]}
+pub fn parse_link_section(lit: Option<syn::Lit>) -> Option<syn::LitStr> {
+    match lit {
+        None => None,
+        Some(syn::Lit::Str(s)) => {
+            if s.value().is_empty() {
+                panic!("link_section must not be empty");
+            }
+            Some(s)
+        }
+        Some(_) => panic!("invalid link_section option (expected a string literal)"),
+    }
+}
+
+pub fn parse_align(lit: Option<syn::Lit>) -> Option<u32> {
+    match lit {
+        None => None,
+        Some(syn::Lit::Int(int)) => match int.base10_parse::<u32>() {
+            // 2^29 is the largest alignment supported by `#[repr(align)]`
+            Ok(n) if n.is_power_of_two() && n <= 1 << 29 => Some(n),
+            _ => panic!("invalid alignment {int} (expected a power of two no greater than 2^29)"),
+        },
+        Some(_) => panic!("invalid align option (expected an integer literal)"),
+    }
+}
+
+/// Data up to this size is embedded as byte string literal, larger data is written to a file and included with `include_bytes!`
+const MAX_LITERAL_SIZE: usize = 4096;
+
+/// Directory for data included with `include_bytes!` and for cached shards: `OUT_DIR` if the crate has a build script,
+/// otherwise the target directory (`CARGO_TARGET_DIR`, or `target` in the crate's directory).
+///
+/// A shared directory (like the system temp directory) is avoided, since other users could plant files there.
+/// Files are never removed, but they are named after their contents, so the directory only grows when assets change, and `cargo clean` removes it.
+pub fn include_files_dir() -> std::path::PathBuf {
+    let dir = match (std::env::var_os("OUT_DIR"), std::env::var_os("CARGO_TARGET_DIR")) {
+        (Some(out_dir), _) => std::path::PathBuf::from(out_dir),
+        (None, Some(target_dir)) => std::path::PathBuf::from(target_dir),
+        (None, None) => std::path::PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default()).join("target"),
+    };
+    dir.join("include_assets")
+}
+
+/// Write `data` to a file named after its checksum (unless it already exists), and return its absolute path.
+fn write_include_file(data: &[u8]) -> std::io::Result<std::path::PathBuf> {
+    let dir = include_files_dir();
+    std::fs::create_dir_all(&dir)?;
+    let checksum = include_assets_decode::checksum::compute_checksum(data);
+    let name: std::string::String = checksum[..16].iter().map(|byte| format!("{byte:02x}")).collect();
+    let path = std::path::absolute(dir.join(format!("{name}.bin")))?;
+    // reuse an existing file only if its contents are exactly the data, e.g. not truncated by an interrupted build
+    if std::fs::read(&path).is_ok_and(|existing| existing == data) {
+        return Ok(path);
+    }
+    // write to a temporary file first, so that concurrent builds never include a partially written file
+    let temporary = dir.join(format!("{name}.{}.tmp", std::process::id()));
+    std::fs::write(&temporary, data)?;
+    std::fs::rename(&temporary, &path)?;
+    Ok(path)
+}
+
+/// Tokens for an expression of type `&'static [u8; N]` containing `data`.
+///
+/// Large data is written to a file and included with `include_bytes!`, since huge literal tokens need a lot of memory and time in rustc.
+pub fn bytes_tokens(data: &[u8]) -> proc_macro2::TokenStream {
+    if data.len() <= MAX_LITERAL_SIZE {
+        let literal = syn::LitByteStr::new(data, proc_macro2::Span::call_site());
+        return quote::quote! { #literal };
+    }
+    let path = write_include_file(data).unwrap_or_else(|err| panic!("couldn't write compressed data to {}: {err}", include_files_dir().display()));
+    let path = path.to_str().expect("temp directory path should be UTF-8");
+    quote::quote! { ::core::include_bytes!(#path) }
+}
+
+/// Tokens for an expression of type `&'static [u8]` containing `data`.
+///
+/// If a link section or alignment is given, the data is placed in a `static` with these properties.
+/// Otherwise, the expression is a plain byte string (see [`bytes_tokens`]).
+pub fn data_tokens(data: &[u8], link_section: Option<&syn::LitStr>, align: Option<u32>) -> proc_macro2::TokenStream {
+    let literal = bytes_tokens(data);
+    if link_section.is_none() && align.is_none() {
+        return literal;
+    }
+    let len = data.len();
+    let align = proc_macro2::Literal::u32_unsuffixed(align.unwrap_or(1));
+    let link_section_attr = link_section.map(|section| quote::quote! { #[unsafe(link_section = #section)] });
+    quote::quote! {
+        {
+            #[repr(C, align(#align))]
+            struct Aligned<T: ?Sized>(T);
+            #link_section_attr
+            static DATA: Aligned<[u8; #len]> = Aligned(*#literal);
+            &DATA.0
+        }
+    }
 }
@@ -2,10 +2,102 @@ use include_assets_decode::codec::Codec;
 
 pub struct AssetEnumOptions {
     pub enum_name: syn::Ident,
+    pub vis: syn::Visibility,
     pub base_path: syn::LitStr,
     pub compression_lit: Option<syn::Lit>,
     pub level_lit: Option<syn::Lit>,
-    pub variant_paths: std::vec::Vec<syn::LitStr>,
+    pub link_section_lit: Option<syn::Lit>,
+    pub align_lit: Option<syn::Lit>,
+    pub iterable: bool,
+    pub methods: bool,
+    pub per_asset: bool,
+    pub dedup: bool,
+    pub allow_outside_base: bool,
+    pub variants: std::vec::Vec<VariantOptions>,
+}
+
+/// Options given in the `asset` attribute of a variant
+pub struct VariantOptions {
+    /// Name of the variant
+    pub ident: syn::Ident,
+    /// Path of the asset relative to the base path
+    pub path: syn::LitStr,
+    /// Base path of this asset, overriding the base path of the enum
+    pub base: Option<syn::LitStr>,
+    /// Whether the asset is stored uncompressed, regardless of the compression of the archive
+    pub raw: bool,
+    /// Whether the file may be missing
+    pub optional: bool,
+    /// Whether the asset must be valid UTF-8
+    pub is_str: bool,
+    /// Format and type into which the asset is deserialized
+    pub deserialize: Option<(syn::Ident, syn::Type)>,
+}
+
+/// Parse the `deserialize` and `type` options of a variant, which must be given together.
+fn parse_deserialize(name: &str, format: Option<syn::Lit>, ty: Option<syn::Lit>) -> Option<(syn::Ident, syn::Type)> {
+    match (format, ty) {
+        (None, None) => None,
+        (Some(syn::Lit::Str(format)), Some(syn::Lit::Str(ty))) => {
+            if !["toml", "json"].contains(&format.value().as_str()) {
+                panic!("invalid deserialize option for variant {name} (expected \"toml\" or \"json\")");
+            }
+            let ty = ty.parse().unwrap_or_else(|err| panic!("invalid type for variant {name}: {err}"));
+            Some((syn::Ident::new(&format.value(), format.span()), ty))
+        }
+        (Some(_), None) | (None, Some(_)) => panic!("variant {name} needs both 'deserialize' and 'type' options, or neither"),
+        _ => panic!("invalid deserialize or type option for variant {name} (expected string literals)"),
+    }
+}
+
+/// Convert a variant name in CamelCase to a field name in snake_case.
+///
+/// A run of capitals is an acronym, which ends before a capital followed by a lowercase letter, e.g. `HTMLPage` becomes `html_page`.
+pub fn field_name(variant: &syn::Ident) -> syn::Ident {
+    let chars: std::vec::Vec<char> = variant.to_string().chars().collect();
+    let mut name = std::string::String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let word_start = match previous {
+            Some(previous) if c.is_uppercase() => {
+                previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            }
+            _ => false,
+        };
+        if word_start {
+            name.push('_');
+        }
+        name.extend(c.to_lowercase());
+    }
+    syn::parse_str(&name).unwrap_or_else(|_| syn::Ident::new_raw(&name, variant.span()))
+}
+
+/// Parse the per-variant `compression` option, which may only disable compression.
+fn parse_variant_compression(name: &str, lit: Option<syn::Lit>) -> bool {
+    match lit {
+        None => false,
+        Some(syn::Lit::Str(s)) if s.value() == "uncompressed" => true,
+        Some(_) => panic!("invalid compression for variant {name} (only \"uncompressed\" can be chosen per variant)"),
+    }
+}
+
+/// Parse the `kind` option of a variant, returning true for `"str"`.
+fn parse_kind(name: &str, lit: Option<syn::Lit>) -> bool {
+    match lit {
+        None => false,
+        Some(syn::Lit::Str(s)) if s.value() == "bytes" => false,
+        Some(syn::Lit::Str(s)) if s.value() == "str" => true,
+        Some(_) => panic!("invalid kind for variant {name} (expected \"bytes\" or \"str\")"),
+    }
+}
+
+fn parse_bool(option: &str, lit: Option<syn::Lit>) -> bool {
+    match lit {
+        None => false,
+        Some(syn::Lit::Bool(b)) => b.value,
+        Some(_) => panic!("invalid {option} option (expected true or false)"),
+    }
 }
 
 pub fn check_enum_and_return_options(e: syn::ItemEnum) -> AssetEnumOptions {
@@ -22,7 +114,23 @@ pub fn check_enum_and_return_options(e: syn::ItemEnum) -> AssetEnumOptions {
             syn::Meta::List(list) => {
                 if list.path.is_ident("archive") {
                     let kv_opts: crate::parse::KVList = syn::parse2(list.tokens.clone()).unwrap();
-                    for (k, v) in crate::parse::kv_args_to_hashmap(kv_opts.kvs.into_iter(), ["base_path", "compression", "level"].into_iter().collect()) {
+                    for (k, v) in crate::parse::kv_args_to_hashmap(
+                        kv_opts.kvs.into_iter(),
+                        [
+                            "base_path",
+                            "compression",
+                            "level",
+                            "link_section",
+                            "align",
+                            "iterable",
+                            "methods",
+                            "per_asset",
+                            "dedup",
+                            "allow_outside_base",
+                        ]
+                        .into_iter()
+                        .collect(),
+                    ) {
                         opts.insert(k, v);
                     }
                 } else if list.path.is_ident("asset") {
@@ -52,18 +160,17 @@ pub fn check_enum_and_return_options(e: syn::ItemEnum) -> AssetEnumOptions {
     };
 
     // collect relative path of all variants.
-    // while we're at it, ensure that all variants are unit and (most importantly) have no explicit discriminator.
-    // we need enums to have discriminators 0..N!
-    let mut variant_paths = vec![];
+    // while we're at it, ensure that all variants are unit.
+    // explicit discriminants are fine, assets are indexed in declaration order.
+    let mut variants = vec![];
     for var in e.variants {
         let name = var.ident.to_string();
         if !matches!(var.fields, syn::Fields::Unit) {
             panic!("{name} is not a unit variant");
         }
-        if var.discriminant.is_some() {
-            panic!("variant {name} has an explicit discriminant, which is not allowed");
-        }
-        match &var.attrs[..] {
+        // other attributes (doc comments, lint attributes, ...) are ignored
+        let asset_attrs: std::vec::Vec<&syn::Attribute> = var.attrs.iter().filter(|attr| attr.path().is_ident("asset")).collect();
+        match &asset_attrs[..] {
             [] => panic!("variant {name} is missing attribute"),
             [attr] => match &attr.meta {
                 syn::Meta::Path(_) => panic!("invalid attribute for variant {name}"),
@@ -73,57 +180,333 @@ pub fn check_enum_and_return_options(e: syn::ItemEnum) -> AssetEnumOptions {
                         panic!("invalid attribute for variant {name}, expected 'asset'");
                     }
                     let kv_opts: crate::parse::KVList = syn::parse2(list.tokens.clone()).unwrap();
-                    let mut opts = crate::parse::kv_args_to_hashmap(kv_opts.kvs.into_iter(), ["path"].into_iter().collect());
-                    match opts.remove("path") {
+                    let mut opts = crate::parse::kv_args_to_hashmap(
+                        kv_opts.kvs.into_iter(),
+                        ["path", "base", "compression", "optional", "kind", "deserialize", "type"].into_iter().collect(),
+                    );
+                    let path = match opts.remove("path") {
                         None => panic!("variant {name} is missing attribute 'path'"),
-                        Some(syn::Lit::Str(s)) => {
-                            variant_paths.push(s);
-                        }
+                        Some(syn::Lit::Str(s)) => s,
                         Some(_) => panic!("invalid attribute for variant {name}"),
-                    }
+                    };
+                    let base = match opts.remove("base") {
+                        None => None,
+                        Some(syn::Lit::Str(s)) => Some(s),
+                        Some(_) => panic!("invalid base option for variant {name} (expected a string literal)"),
+                    };
+                    let raw = parse_variant_compression(&name, opts.remove("compression"));
+                    let optional = parse_bool("optional", opts.remove("optional"));
+                    let is_str = parse_kind(&name, opts.remove("kind"));
+                    let deserialize = parse_deserialize(&name, opts.remove("deserialize"), opts.remove("type"));
+                    variants.push(VariantOptions {
+                        ident: var.ident.clone(),
+                        path,
+                        base,
+                        raw,
+                        optional,
+                        is_str,
+                        deserialize,
+                    });
                 }
             },
-            _ => panic!("variant {name} has more than one attribute"),
+            _ => panic!("variant {name} has more than one 'asset' attribute (use cfg_attr to choose one per target)"),
         }
     }
 
     AssetEnumOptions {
         enum_name: e.ident,
+        vis: e.vis,
         base_path,
         compression_lit: opts.remove("compression"),
         level_lit: opts.remove("level"),
-        variant_paths,
+        link_section_lit: opts.remove("link_section"),
+        align_lit: opts.remove("align"),
+        iterable: parse_bool("iterable", opts.remove("iterable")),
+        methods: parse_bool("methods", opts.remove("methods")),
+        per_asset: parse_bool("per_asset", opts.remove("per_asset")),
+        dedup: opts.remove("dedup").is_none_or(|lit| parse_bool("dedup", Some(lit))),
+        allow_outside_base: parse_bool("allow_outside_base", opts.remove("allow_outside_base")),
+        variants,
+    }
+}
+
+/// Compressed data of an enum which is shared with other enums in an asset group
+pub struct GroupMember {
+    /// Expression for the compressed data of the whole group
+    pub data: proc_macro2::TokenStream,
+    /// Position of the enum's data within the uncompressed data of the group
+    pub offset: usize,
+    /// Size of the uncompressed data of the group
+    pub size: usize,
+    /// Expression for the static holding the decompressed data of the group
+    pub cache: proc_macro2::TokenStream,
+    /// Offsets and raw data of the enum, without compressed data
+    pub archive: EnumArchive,
+}
+
+/// Remove the `archive` and `asset` attributes from an enum declared in an asset group, which are only allowed in derive macros.
+pub fn strip_asset_attributes(mut e: syn::ItemEnum) -> syn::ItemEnum {
+    e.attrs.retain(|attr| !attr.path().is_ident("archive"));
+    for variant in e.variants.iter_mut() {
+        variant.attrs.retain(|attr| !attr.path().is_ident("asset"));
+    }
+    e
+}
+
+/// Base path of a variant
+fn variant_base(base_path: &syn::LitStr, variant: &VariantOptions) -> std::path::PathBuf {
+    std::path::PathBuf::from(variant.base.as_ref().unwrap_or(base_path).value())
+}
+
+/// Path of the file of a variant
+fn file_path(base_path: &syn::LitStr, variant: &VariantOptions) -> std::path::PathBuf {
+    variant_base(base_path, variant).join(variant.path.value())
+}
+
+/// Ensure that the file of a variant is inside its base path, after resolving `..` and symbolic links.
+///
+/// Missing files are not checked, since nothing is read from them.
+fn check_inside_base(base_path: &syn::LitStr, variant: &VariantOptions) {
+    let base = variant_base(base_path, variant);
+    let file = file_path(base_path, variant);
+    let (Ok(base), Ok(file)) = (std::fs::canonicalize(&base), std::fs::canonicalize(&file)) else {
+        return;
+    };
+    if !file.starts_with(&base) {
+        panic!(
+            "file {} of variant {} is outside the base path {} (use allow_outside_base = true if this is intended)",
+            file.display(),
+            variant.ident,
+            base.display()
+        );
     }
 }
 
-pub fn get_files(base_path: syn::LitStr, variant_paths: std::vec::Vec<syn::LitStr>) -> std::vec::Vec<std::vec::Vec<u8>> {
-    let base = std::path::PathBuf::from(base_path.value());
+/// Read the files of all variants, returning `None` for missing optional files.
+///
+/// Unless `allow_outside_base`, files must be inside the base path.
+pub fn get_files(base_path: &syn::LitStr, variants: &[VariantOptions], allow_outside_base: bool) -> std::vec::Vec<Option<std::vec::Vec<u8>>> {
     let mut data = vec![];
-    for var_path in variant_paths {
-        let name = base.join(var_path.value());
+    for variant in variants {
+        if !allow_outside_base {
+            check_inside_base(base_path, variant);
+        }
+        let name = file_path(base_path, variant);
         match std::fs::read(&name) {
-            Ok(blob) => data.push(blob),
+            Ok(blob) => {
+                if variant.is_str && std::str::from_utf8(&blob).is_err() {
+                    panic!("file {} is not valid UTF-8, but is declared with kind = \"str\"", name.display());
+                }
+                data.push(Some(blob))
+            }
+            Err(err) if variant.optional && err.kind() == std::io::ErrorKind::NotFound => data.push(None),
             Err(err) => panic!("Couldn't read file {}: {}", name.display(), err),
         }
     }
     data
 }
 
+/// Files of all variants of an enum
+pub struct EnumFiles {
+    /// Data of each variant as stored in the archive, empty for missing files and for variants sharing the data of another variant
+    pub data: std::vec::Vec<std::vec::Vec<u8>>,
+    /// Whether each variant is an optional asset whose file is missing
+    pub missing: std::vec::Vec<bool>,
+    /// Size of the file of each variant
+    pub sizes: std::vec::Vec<usize>,
+    /// Checksum of the file of each variant
+    pub checksums: std::vec::Vec<include_assets_decode::checksum::Checksum>,
+    /// Index of the variant whose data each variant uses
+    pub aliases: std::vec::Vec<usize>,
+}
+
+/// Read the files of all variants.
+///
+/// With `dedup`, a variant whose file is the same as that of an earlier variant uses the data of the earlier variant instead of storing it again.
+pub fn read_files(base_path: &syn::LitStr, variants: &[VariantOptions], dedup: bool, allow_outside_base: bool) -> EnumFiles {
+    let files = get_files(base_path, variants, allow_outside_base);
+    let missing: std::vec::Vec<bool> = files.iter().map(Option::is_none).collect();
+    let mut data: std::vec::Vec<_> = files.into_iter().map(Option::unwrap_or_default).collect();
+    let sizes = data.iter().map(std::vec::Vec::len).collect();
+    let checksums = data.iter().map(|data| include_assets_decode::checksum::compute_checksum(data)).collect();
+    let mut aliases: std::vec::Vec<usize> = (0..variants.len()).collect();
+    if dedup {
+        let mut seen = std::collections::HashMap::new();
+        for (i, variant) in variants.iter().enumerate() {
+            if missing[i] {
+                continue;
+            }
+            let path = file_path(base_path, variant);
+            let path = std::fs::canonicalize(&path).unwrap_or(path);
+            match seen.get(&path) {
+                Some(&original) => {
+                    aliases[i] = original;
+                    data[i] = vec![];
+                }
+                None => {
+                    seen.insert(path, i);
+                }
+            }
+        }
+    }
+    EnumFiles {
+        data,
+        missing,
+        sizes,
+        checksums,
+        aliases,
+    }
+}
+
+/// Tokens for the struct holding deserialized assets (if any variant has a `deserialize` option), its type, and the body of `AssetEnum::deserialize`.
+pub fn typed_tokens(
+    enum_name: &syn::Ident,
+    vis: &syn::Visibility,
+    variants: &[VariantOptions],
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let typed: std::vec::Vec<_> = variants
+        .iter()
+        .filter_map(|variant| variant.deserialize.as_ref().map(|(format, ty)| (variant, format, ty)))
+        .collect();
+    if typed.is_empty() {
+        return (quote::quote! {}, quote::quote! { () }, quote::quote! { ::core::result::Result::Ok(()) });
+    }
+
+    let typed_name = quote::format_ident!("{}Typed", enum_name);
+    let mut fields = vec![];
+    let mut values = vec![];
+    for (variant, format, ty) in typed {
+        let field = field_name(&variant.ident);
+        let ident = &variant.ident;
+        let name = ident.to_string();
+        let doc = format!("Deserialized content of [`{enum_name}::{ident}`]");
+        let parse = |data: proc_macro2::TokenStream| {
+            quote::quote! {
+                include_assets::do_not_use_this_directly::deserialize::#format(#data).map_err(|source| include_assets::error::DeserializeError {
+                    variant: #name,
+                    source,
+                })?
+            }
+        };
+        if variant.optional {
+            let value = parse(quote::quote! { data });
+            fields.push(quote::quote! { #[doc = #doc] pub #field: ::core::option::Option<#ty> });
+            values.push(quote::quote! {
+                #field: match archive.get(#enum_name::#ident) {
+                    ::core::option::Option::Some(data) => ::core::option::Option::Some(#value),
+                    ::core::option::Option::None => ::core::option::Option::None,
+                }
+            });
+        } else {
+            let value = parse(quote::quote! { &archive[#enum_name::#ident] });
+            fields.push(quote::quote! { #[doc = #doc] pub #field: #ty });
+            values.push(quote::quote! { #field: #value });
+        }
+    }
+    let doc = format!("Assets of [`{enum_name}`] deserialized with the `deserialize` option, see `EnumArchive::load_typed`");
+    let item = quote::quote! {
+        #[doc = #doc]
+        #vis struct #typed_name {
+            #(#fields),*
+        }
+    };
+    let body = quote::quote! {
+        ::core::result::Result::Ok(#typed_name {
+            #(#values),*
+        })
+    };
+    (item, quote::quote! { #typed_name }, body)
+}
+
+/// Tokens for the function `variants()` and the implementation of `IterableAssetEnum`.
+pub fn iterable_tokens(enum_name: &syn::Ident, vis: &syn::Visibility, variants: &[VariantOptions]) -> proc_macro2::TokenStream {
+    let idents: std::vec::Vec<&syn::Ident> = variants.iter().map(|variant| &variant.ident).collect();
+    let indices = 0..idents.len();
+    let len = idents.len();
+    quote::quote! {
+        impl #enum_name {
+            /// Returns an iterator of all variants in declaration order.
+            #[allow(dead_code)]
+            #vis fn variants() -> impl ::core::iter::ExactSizeIterator<Item = Self> {
+                (0..#len).map(<Self as include_assets::IterableAssetEnum>::from_index)
+            }
+        }
+
+        impl include_assets::IterableAssetEnum for #enum_name {
+            fn from_index(index: usize) -> Self {
+                match index {
+                    #(#indices => #enum_name::#idents,)*
+                    _ => ::core::panic!("invalid variant index {}", index),
+                }
+            }
+        }
+    }
+}
+
 pub struct EnumArchive {
     pub compressed_data: std::vec::Vec<u8>,
+    pub raw_data: std::vec::Vec<u8>,
     pub data_end_offsets: std::vec::Vec<u32>,
+    /// End offsets of each variant's compressed data, empty unless compressing per asset
+    pub compressed_end_offsets: std::vec::Vec<u32>,
 }
 
-pub fn prepare_asset_archive<C: Codec + ?Sized>(codec: &C, data: std::vec::Vec<std::vec::Vec<u8>>) -> EnumArchive {
-    let mut uncompressed_data = vec![];
+/// Compress the data of all variants except those stored uncompressed (for which `raw` is true).
+///
+/// With `per_asset`, each asset is compressed independently.
+/// Otherwise, the data of all variants is fed to the codec in parts (see [`Codec::compress_parts`]) without concatenating it first.
+/// The data of each variant is dropped as soon as it has been compressed.
+pub fn prepare_asset_archive<C: Codec + ?Sized>(codec: &C, data: std::vec::Vec<std::vec::Vec<u8>>, raw: &[bool], per_asset: bool) -> EnumArchive {
+    if !per_asset {
+        let (mut archive, parts) = split_asset_data(data, raw);
+        archive.compressed_data = codec.compress_parts(&mut parts.into_iter()).expect("compression should succeed");
+        return archive;
+    }
+    let mut raw_data = vec![];
+    let mut compressed_data = vec![];
     let mut data_end_offsets = vec![];
-    for blob in data {
-        uncompressed_data.extend_from_slice(blob.as_slice());
-        data_end_offsets.push(u32::try_from(uncompressed_data.len()).unwrap());
+    let mut compressed_end_offsets = vec![];
+    let mut end = 0usize;
+    for (blob, &raw) in data.into_iter().zip(raw) {
+        end += blob.len();
+        if raw {
+            raw_data.extend_from_slice(blob.as_slice());
+        } else {
+            compressed_data.extend(codec.compress(blob.as_slice()).expect("compression should succeed"));
+        }
+        data_end_offsets.push(u32::try_from(end).unwrap());
+        compressed_end_offsets.push(u32::try_from(compressed_data.len()).unwrap());
     }
-    let compressed_data = codec.compress(uncompressed_data.as_slice()).expect("compression should succeed");
     EnumArchive {
         compressed_data,
+        raw_data,
         data_end_offsets,
+        compressed_end_offsets,
     }
 }
+
+/// Separate the data of the variants stored uncompressed (for which `raw` is true) from the data to be compressed, without compressing it.
+///
+/// Returns the archive without compressed data, and the data of the other variants in order, e.g. to compress it together with other enums of an asset group.
+pub fn split_asset_data(data: std::vec::Vec<std::vec::Vec<u8>>, raw: &[bool]) -> (EnumArchive, std::vec::Vec<std::vec::Vec<u8>>) {
+    let mut parts = vec![];
+    let mut raw_data = vec![];
+    let mut data_end_offsets = vec![];
+    let mut end = 0usize;
+    for (blob, &raw) in data.into_iter().zip(raw) {
+        end += blob.len();
+        if raw {
+            raw_data.extend_from_slice(blob.as_slice());
+        } else {
+            parts.push(blob);
+        }
+        data_end_offsets.push(u32::try_from(end).unwrap());
+    }
+    let archive = EnumArchive {
+        compressed_data: vec![],
+        raw_data,
+        data_end_offsets,
+        compressed_end_offsets: vec![],
+    };
+    (archive, parts)
+}
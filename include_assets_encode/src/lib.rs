@@ -2,9 +2,10 @@ pub(crate) mod common;
 pub(crate) mod enums;
 pub(crate) mod named;
 pub(crate) mod parse;
+pub(crate) mod structs;
+pub(crate) mod views;
 
 use include_assets_decode::codec::Codec;
-use std::borrow::Borrow as _;
 
 #[proc_macro]
 pub fn include_dir(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -12,33 +13,175 @@ pub fn include_dir(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
     std::env::set_current_dir(manifest_dir).unwrap();
 
     let args = syn::parse_macro_input!(tokens as parse::IncludeDirArgs);
-    let opts = parse::kv_args_to_hashmap(args.opts.into_iter(), ["compression", "level", "links"].into_iter().collect());
+    let opts = parse::kv_args_to_hashmap(
+        args.opts.into_iter(),
+        [
+            "compression",
+            "level",
+            "links",
+            "allow_outside_base",
+            "link_section",
+            "align",
+            "signing_key",
+            "dev",
+            "mime_types",
+            "private",
+            "shard_size",
+            "checksum_size",
+            "single_stream",
+            "names_module",
+        ]
+        .into_iter()
+        .collect(),
+    );
 
     //println!("current directory: {}", std::env::current_dir().unwrap().display());
     //println!("path: {}", args.path.value());
 
-    let (codec, codec_tokens, _codec_type_tokens) = common::parse_codec(opts.get("compression").cloned(), opts.get("level").cloned());
+    let (codec, codec_tokens, codec_type_tokens) = common::parse_codec(opts.get("compression").cloned(), opts.get("level").cloned());
     let symlink_rules = named::parse_symlink_rules(opts.get("links").cloned());
+    let allow_outside_base = named::parse_allow_outside_base(opts.get("allow_outside_base").cloned());
+    let link_section = common::parse_link_section(opts.get("link_section").cloned());
+    let align = common::parse_align(opts.get("align").cloned());
+    let signing_key = named::read_signing_key(opts.get("signing_key").cloned());
+    let shard_size = named::parse_shard_size(opts.get("shard_size").cloned());
+    let checksum_size = named::parse_checksum_size(opts.get("checksum_size").cloned());
+    let single_stream = named::parse_single_stream(opts.get("single_stream").cloned());
+    let (mime_extensions, mime_types): (std::vec::Vec<_>, std::vec::Vec<_>) = named::parse_mime_types(opts.get("mime_types").cloned()).into_iter().unzip();
+    let mime_types_token = quote::quote! { &[#((#mime_extensions, #mime_types)),*] };
+    let private = named::parse_private(opts.get("private").cloned());
+    let private_token = quote::quote! { &[#(#private),*] };
+    let names_module = named::parse_names_module(opts.get("names_module").cloned());
+
+    // only lists the files, so that dev mode doesn't read them
+    let assets = named::read_dir(args.path.value(), symlink_rules, allow_outside_base).unwrap();
+    named::check_private(&private, assets.iter().map(|(name, _)| name.as_str()));
+
+    if named::parse_dev_mode(opts.get("dev").cloned()) && cfg!(debug_assertions) {
+        // don't embed anything, read the directory at runtime instead
+        let directory = std::fs::canonicalize(args.path.value()).unwrap_or_else(|err| panic!("Couldn't find directory {}: {}", args.path.value(), err));
+        let directory = directory.to_str().expect("directory path should be UTF-8");
+        let archive = quote::quote! {
+            ::include_assets::CompressedNamedArchive {
+                codec: #codec_tokens,
+                data: b"",
+                uncompressed_data_size: 0,
+                compressed_names: b"",
+                uncompressed_names_size: 0,
+                checksums: b"",
+                checksum_size: 64,
+                compressed_sizes: b"",
+                uncompressed_sizes_size: 0,
+                align: 1,
+                metadata_size: 0,
+                signature: ::core::option::Option::None,
+                directory: ::core::option::Option::Some(#directory),
+                mime_types: #mime_types_token,
+                private: #private_token,
+                perfect_hash: ::core::option::Option::None,
+                shards: b""
+            }
+        };
+        return match names_module {
+            Some(module) => named::names_module_tokens(&module, assets.iter().map(|(name, _)| name.as_str()), &codec_type_tokens, archive),
+            None => archive,
+        }
+        .into();
+    }
 
+    let perfect_hash_token = named::perfect_hash_tokens(assets.iter().map(|(name, _)| name.as_str()));
+    let names: std::vec::Vec<_> = assets.iter().map(|(name, _)| name.clone()).collect();
+    // assets are aligned like the compressed data, up to the alignment of any primitive or SIMD type
+    let asset_align = align.unwrap_or(1).min(include_assets_decode::named::MAX_ALIGN);
+    let archive = named::prepare_named_archive(
+        &codec,
+        assets,
+        shard_size,
+        Some(&common::include_files_dir()),
+        asset_align,
+        checksum_size,
+        single_stream,
+    )
+    .unwrap();
     let named::NamedArchive {
         compressed_data,
         uncompressed_data_size,
         compressed_names,
         uncompressed_names_size,
         compressed_sizes,
+        uncompressed_sizes_size,
         checksums,
-    } = named::prepare_named_archive(
-        codec.borrow() as &dyn Codec<CompressionError = common::MyError, DecompressionError = common::MyError>,
-        named::read_dir(args.path.value(), symlink_rules).unwrap(),
-    )
-    .unwrap();
+        checksum_size,
+        align: asset_align,
+        metadata_size,
+        shards,
+    } = archive;
+    let compressed = include_assets_decode::named::CompressedNamedArchive {
+        codec,
+        data: &compressed_data,
+        uncompressed_data_size,
+        compressed_names: &compressed_names,
+        uncompressed_names_size,
+        checksums: &checksums,
+        checksum_size,
+        compressed_sizes: &compressed_sizes,
+        uncompressed_sizes_size,
+        align: asset_align,
+        metadata_size,
+        signature: None,
+        directory: None,
+        mime_types: &mime_extensions
+            .iter()
+            .zip(&mime_types)
+            .map(|(ext, mime)| (ext.as_str(), mime.as_str()))
+            .collect::<std::vec::Vec<_>>(),
+        private: &private.iter().map(std::string::String::as_str).collect::<std::vec::Vec<_>>(),
+        perfect_hash: None,
+        shards: &shards,
+    };
+    let signature = signing_key.map(|key| named::sign(&compressed, &key));
 
-    let data_token = syn::LitByteStr::new(&compressed_data, proc_macro2::Span::call_site());
-    let names_token = syn::LitByteStr::new(&compressed_names, proc_macro2::Span::call_site());
-    let checksums_token = common::checksums_tokens(checksums.into_iter());
-    let sizes_token = syn::LitByteStr::new(&compressed_sizes, proc_macro2::Span::call_site());
+    // with a link section, the whole archive is placed there in the archive file format, so that tools can find it in the executable
+    let (linked_archive, [data_token, names_token, checksums_token, sizes_token, shards_token]) = match link_section.as_ref() {
+        Some(section) => {
+            let file = include_assets_decode::named::CompressedNamedArchive {
+                signature: signature.as_ref(),
+                mime_types: &[],
+                private: &[],
+                ..compressed
+            }
+            .to_file_bytes()
+            .expect("number of assets should fit into u32");
+            // these are the trailing sections of the archive file, in order
+            let signature_bytes = signature.map(std::vec::Vec::from).unwrap_or_default();
+            let sections = [
+                &checksums[..],
+                &signature_bytes,
+                &compressed_data,
+                &compressed_names,
+                &compressed_sizes,
+                &shards,
+            ];
+            let (item, [checksums, _signature, data, names, sizes, shards]) = named::linked_archive_tokens(&file, sections, section, align);
+            (Some(item), [data, names, checksums, sizes, shards])
+        }
+        None => (
+            None,
+            [
+                common::data_tokens(&compressed_data, None, align),
+                common::bytes_tokens(&compressed_names),
+                common::bytes_tokens(&checksums),
+                common::bytes_tokens(&compressed_sizes),
+                common::bytes_tokens(&shards),
+            ],
+        ),
+    };
+    let signature_token = match signature {
+        Some(signature) => quote::quote! { ::core::option::Option::Some(&[#(#signature),*]) },
+        None => quote::quote! { ::core::option::Option::None },
+    };
 
-    quote::quote! {
+    let archive = quote::quote! {
         ::include_assets::CompressedNamedArchive {
             codec: #codec_tokens,
             data: #data_token,
@@ -46,9 +189,77 @@ pub fn include_dir(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
             compressed_names: #names_token,
             uncompressed_names_size: #uncompressed_names_size,
             checksums: #checksums_token,
-            compressed_sizes: #sizes_token
+            checksum_size: #checksum_size,
+            compressed_sizes: #sizes_token,
+            uncompressed_sizes_size: #uncompressed_sizes_size,
+            align: #asset_align,
+            metadata_size: #metadata_size,
+            signature: #signature_token,
+            directory: ::core::option::Option::None,
+            mime_types: #mime_types_token,
+            private: #private_token,
+            perfect_hash: ::core::option::Option::Some(#perfect_hash_token),
+            shards: #shards_token
+        }
+    };
+    let archive = match linked_archive {
+        Some(item) => quote::quote! { { #item #archive } },
+        None => archive,
+    };
+    match names_module {
+        Some(module) => named::names_module_tokens(&module, names.iter().map(|name| name.as_str()), &codec_type_tokens, archive),
+        None => archive,
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn include_locales(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    std::env::set_current_dir(manifest_dir).unwrap();
+
+    let args = syn::parse_macro_input!(tokens as parse::IncludeDirArgs);
+    for opt in args.opts.iter() {
+        if opt.ident == "names_module" {
+            panic!("The names_module option is not supported for include_locales!");
         }
     }
+    let opts: std::vec::Vec<_> = args
+        .opts
+        .iter()
+        .map(|parse::KVIdentLit { ident, lit }| quote::quote! { #ident = #lit })
+        .collect();
+
+    // each subdirectory is a locale, files directly in the directory are ignored
+    let path = args.path.value();
+    let entries = std::fs::read_dir(&path).unwrap_or_else(|err| panic!("Couldn't read directory {path}: {err}"));
+    let mut locales = vec![];
+    for entry in entries {
+        let entry = entry.unwrap_or_else(|err| panic!("Couldn't read directory {path}: {err}"));
+        if std::fs::metadata(entry.path())
+            .unwrap_or_else(|err| panic!("Couldn't read {}: {err}", entry.path().display()))
+            .is_dir()
+        {
+            let tag = entry
+                .file_name()
+                .into_string()
+                .unwrap_or_else(|name| panic!("Non-UTF-8 locale name: '{}'", name.to_string_lossy()));
+            locales.push(tag);
+        }
+    }
+    if locales.is_empty() {
+        panic!("Directory {path} doesn't contain any locale subdirectories");
+    }
+    // sort to get the same output independent of the order of directory entries
+    locales.sort_unstable();
+
+    let archives = locales.iter().map(|tag| {
+        let locale_path = format!("{}/{tag}", path.trim_end_matches('/'));
+        quote::quote! { (#tag, ::include_assets::include_dir!(#locale_path, #(#opts),*)) }
+    });
+    quote::quote! {
+        ::include_assets::LocaleArchive::new([#(#archives),*])
+    }
     .into()
 }
 
@@ -58,39 +269,234 @@ pub fn derive_asset_enum(tokens: proc_macro::TokenStream) -> proc_macro::TokenSt
     std::env::set_current_dir(manifest_dir).unwrap();
 
     let e = syn::parse_macro_input!(tokens as syn::ItemEnum);
+    let options = enums::check_enum_and_return_options(e);
+    let files = enums::read_files(&options.base_path, &options.variants, options.dedup, options.allow_outside_base);
+    asset_enum_tokens(options, files, None).into()
+}
+
+#[proc_macro]
+pub fn asset_group(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    std::env::set_current_dir(manifest_dir).unwrap();
 
+    let parse::EnumList { enums } = syn::parse_macro_input!(tokens as parse::EnumList);
+    let options: std::vec::Vec<_> = enums.iter().cloned().map(enums::check_enum_and_return_options).collect();
+    let Some(first) = options.first() else {
+        panic!("an asset group needs at least one enum");
+    };
+    let lit_string = |lit: &Option<syn::Lit>| lit.as_ref().map(|lit| quote::quote!(#lit).to_string());
+    for other in options.iter() {
+        if lit_string(&other.compression_lit) != lit_string(&first.compression_lit) || lit_string(&other.level_lit) != lit_string(&first.level_lit) {
+            panic!("all enums in an asset group must have the same compression and level options");
+        }
+        if lit_string(&other.link_section_lit) != lit_string(&first.link_section_lit) || lit_string(&other.align_lit) != lit_string(&first.align_lit) {
+            panic!("all enums in an asset group must have the same link_section and align options");
+        }
+        if other.per_asset {
+            panic!("per_asset is not supported for enums in an asset group");
+        }
+    }
+
+    // concatenate the compressed assets of all enums into a single stream
+    let mut group_parts = vec![];
+    let mut group_size = 0;
+    let mut members = vec![];
+    for enum_options in options.iter() {
+        let mut files = enums::read_files(
+            &enum_options.base_path,
+            &enum_options.variants,
+            enum_options.dedup,
+            enum_options.allow_outside_base,
+        );
+        let raw: std::vec::Vec<bool> = enum_options.variants.iter().map(|variant| variant.raw).collect();
+        let (archive, parts) = enums::split_asset_data(std::mem::take(&mut files.data), &raw);
+        members.push((files, archive, group_size));
+        group_size += parts.iter().map(std::vec::Vec::len).sum::<usize>();
+        group_parts.extend(parts);
+    }
+    let (codec, _codec_expr, _codec_type) = common::parse_codec(first.compression_lit.clone(), first.level_lit.clone());
+    let compressed_data = codec.compress_parts(&mut group_parts.into_iter()).expect("compression should succeed");
+    let link_section = common::parse_link_section(first.link_section_lit.clone());
+    let align = common::parse_align(first.align_lit.clone());
+    let data_token = common::data_tokens(&compressed_data, link_section.as_ref(), align);
+    let static_name = quote::format_ident!("__{}_ASSET_GROUP", first.enum_name.to_string().to_uppercase());
+    let cache_name = quote::format_ident!("__{}_ASSET_GROUP_DATA", first.enum_name.to_string().to_uppercase());
+
+    let enums = enums.into_iter().map(enums::strip_asset_attributes);
+    let impls: std::vec::Vec<_> = options
+        .into_iter()
+        .zip(members)
+        .map(|(enum_options, (files, archive, offset))| {
+            let member = enums::GroupMember {
+                data: quote::quote! { #static_name },
+                offset,
+                size: group_size,
+                cache: quote::quote! { #cache_name },
+                archive,
+            };
+            asset_enum_tokens(enum_options, files, Some(member))
+        })
+        .collect();
+    quote::quote! {
+        #[doc(hidden)]
+        static #static_name: &[u8] = #data_token;
+        #[doc(hidden)]
+        static #cache_name: include_assets::do_not_use_this_directly::AssetGroup = include_assets::do_not_use_this_directly::AssetGroup::new();
+
+        #(#enums)*
+        #(#impls)*
+    }
+    .into()
+}
+
+#[proc_macro_derive(AssetView, attributes(archive, asset))]
+pub fn derive_asset_view(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    std::env::set_current_dir(manifest_dir).unwrap();
+
+    let e = syn::parse_macro_input!(tokens as syn::ItemEnum);
+    views::view_tokens(&e).into()
+}
+
+#[proc_macro_derive(AssetStruct, attributes(archive, asset))]
+pub fn derive_asset_struct(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    std::env::set_current_dir(manifest_dir).unwrap();
+
+    let s = syn::parse_macro_input!(tokens as syn::ItemStruct);
+    let (e, is_str) = structs::struct_to_enum(&s);
+    let mut options = enums::check_enum_and_return_options(e);
+    for (variant, is_str) in options.variants.iter_mut().zip(is_str) {
+        variant.is_str |= is_str;
+    }
+    let struct_tokens = structs::struct_tokens(&s, &options);
+    let files = enums::read_files(&options.base_path, &options.variants, options.dedup, options.allow_outside_base);
+    let enum_tokens = asset_enum_tokens(options, files, None);
+    quote::quote! {
+        #struct_tokens
+        #enum_tokens
+    }
+    .into()
+}
+
+/// Generate the implementation of `AssetEnum` (and related items) for an enum.
+///
+/// `files` are the files of the enum's variants, see [`enums::read_files`].
+/// For a member of an asset group, the compressed data is shared with the other members of the group.
+fn asset_enum_tokens(options: enums::AssetEnumOptions, files: enums::EnumFiles, group: Option<enums::GroupMember>) -> proc_macro2::TokenStream {
     let enums::AssetEnumOptions {
         enum_name,
-        base_path,
+        vis,
+        base_path: _,
         compression_lit,
         level_lit,
-        variant_paths,
-    } = enums::check_enum_and_return_options(e);
+        link_section_lit,
+        align_lit,
+        iterable,
+        methods,
+        per_asset,
+        dedup: _,
+        allow_outside_base: _,
+        variants,
+    } = options;
 
     let (codec, codec_expr, codec_type) = common::parse_codec(compression_lit, level_lit);
 
-    let file_data = enums::get_files(base_path, variant_paths);
-    let checksums_token = common::checksums_tokens(file_data.iter());
+    let enums::EnumFiles {
+        data: file_data,
+        missing,
+        sizes,
+        checksums,
+        aliases,
+    } = files;
+    let raw: std::vec::Vec<bool> = variants.iter().map(|variant| variant.raw).collect();
+    let is_str: std::vec::Vec<bool> = variants.iter().map(|variant| variant.is_str).collect();
+    let checksums_token = common::checksums_tokens(checksums.into_iter());
+    let link_section = common::parse_link_section(link_section_lit);
+    let align = common::parse_align(align_lit);
+    // the data of a group member was split off and compressed by the group
+    let (archive, data_token, group_offset, group_size, group_token) = match group {
+        Some(enums::GroupMember {
+            data,
+            offset,
+            size,
+            cache,
+            archive,
+        }) => (archive, data, offset, size, quote::quote! { ::core::option::Option::Some(&#cache) }),
+        None => {
+            let compressed_size: usize = file_data.iter().zip(&raw).filter(|(_, raw)| !**raw).map(|(data, _)| data.len()).sum();
+            let archive = enums::prepare_asset_archive(&codec, file_data, &raw, per_asset);
+            let data_token = common::data_tokens(&archive.compressed_data, link_section.as_ref(), align);
+            (archive, data_token, 0, compressed_size, quote::quote! { ::core::option::Option::None })
+        }
+    };
     let enums::EnumArchive {
-        compressed_data,
+        raw_data,
         data_end_offsets,
-    } = enums::prepare_asset_archive(
-        codec.borrow() as &dyn Codec<CompressionError = common::MyError, DecompressionError = common::MyError>,
-        file_data,
-    );
-    let data_token = syn::LitByteStr::new(&compressed_data, proc_macro2::Span::call_site());
+        compressed_end_offsets,
+        ..
+    } = archive;
+    let group_offset = u32::try_from(group_offset).expect("asset data should be less than 4 GiB");
+    let group_size = u32::try_from(group_size).expect("asset data should be less than 4 GiB");
+    let raw_data_token = common::data_tokens(&raw_data, link_section.as_ref(), align);
+    let (typed_item, typed_type, deserialize_body) = enums::typed_tokens(&enum_name, &vis, &variants);
+    let iterable_item = iterable.then(|| enums::iterable_tokens(&enum_name, &vis, &variants));
+    let paths: std::vec::Vec<&syn::LitStr> = variants.iter().map(|variant| &variant.path).collect();
+    let variant_idents: std::vec::Vec<&syn::Ident> = variants.iter().map(|variant| &variant.ident).collect();
+    let indices = 0..variant_idents.len();
+    let methods_item = methods.then(|| {
+        quote::quote! {
+            impl #enum_name {
+                /// Returns the path of the asset file, relative to its base path.
+                #[allow(dead_code)]
+                #vis fn path(&self) -> &'static str {
+                    <Self as include_assets::AssetEnum>::PATHS[include_assets::AssetEnum::index(self)]
+                }
+
+                /// Returns the size of the asset in bytes, known at compile time.
+                #[allow(dead_code)]
+                #vis const fn size(&self) -> usize {
+                    match self {
+                        #(#enum_name::#variant_idents => #sizes,)*
+                    }
+                }
+            }
+        }
+    });
 
     quote::quote! {
+        #typed_item
+        #iterable_item
+
+        #methods_item
+
         impl include_assets::AssetEnum for #enum_name {
             const DATA: &'static [u8] = #data_token;
+            const RAW_DATA: &'static [u8] = #raw_data_token;
+            const RAW: &'static [bool] = &[#(#raw),*];
+            const MISSING: &'static [bool] = &[#(#missing),*];
+            const STR: &'static [bool] = &[#(#is_str),*];
             const DATA_END_OFFSETS: &'static [u32] = &[#(#data_end_offsets),*];
+            const COMPRESSED_END_OFFSETS: &'static [u32] = &[#(#compressed_end_offsets),*];
+            const GROUP_OFFSET: u32 = #group_offset;
+            const GROUP_SIZE: u32 = #group_size;
+            const GROUP: ::core::option::Option<&'static include_assets::do_not_use_this_directly::AssetGroup> = #group_token;
+            const ALIASES: &'static [usize] = &[#(#aliases),*];
             const CHECKSUMS: &'static [include_assets::do_not_use_this_directly::Checksum] = #checksums_token;
+            const PATHS: &'static [&'static str] = &[#(#paths),*];
+            const SIZES: &'static [usize] = &[#(#sizes),*];
             type C = #codec_type;
             const CODEC: Self::C = #codec_expr;
-            fn index(self) -> usize {
-                self as usize
+            type Typed = #typed_type;
+            fn index(&self) -> usize {
+                match self {
+                    #(#enum_name::#variant_idents => #indices,)*
+                }
+            }
+
+            fn deserialize(archive: &include_assets::EnumArchive<Self>) -> ::core::result::Result<Self::Typed, include_assets::error::DeserializeError> {
+                #deserialize_body
             }
         }
     }
-    .into()
 }
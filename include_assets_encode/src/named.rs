@@ -1,75 +1,229 @@
-use anyhow::Context as _;
-
-use crate::common::{compress_names, compress_sizes};
-use include_assets_decode::checksum::{compute_checksum, Checksum};
-use include_assets_decode::codec::Codec;
-
-pub struct NamedArchive {
-    /// Compressed data
-    ///
-    /// All assets are concatenated
-    /// The order of asset data must match the order of assets in `compressed_names`.
-    pub compressed_data: std::vec::Vec<u8>,
-    /// Size of the data after decompression
-    pub uncompressed_data_size: u32,
-    /// Compressed names of the assets in ascending order, with separating null bytes
-    pub compressed_names: std::vec::Vec<u8>,
-    /// Size of the uncompressed names (including separating null bytes)
-    pub uncompressed_names_size: u32,
-    /// Sizes of asset data, in the same order as `compressed_names`.
-    pub compressed_sizes: std::vec::Vec<u8>,
-    /// Asset checksums, in the same order as `compressed_names`.
-    pub checksums: std::vec::Vec<Checksum>,
-}
-
-pub fn prepare_named_archive<C: Codec + ?Sized>(
-    codec: &C,
-    assets: std::vec::Vec<(smartstring::SmartString<smartstring::LazyCompact>, std::vec::Vec<u8>)>,
-) -> anyhow::Result<NamedArchive> {
-    // ensure that names are unique
-    {
-        let mut names = std::collections::HashSet::new();
-        for (name, _) in assets.iter() {
-            let is_new = names.insert(name);
-            if !is_new {
-                panic!("duplicate asset name: {name}")
-            }
+pub use include_assets_build::do_not_use_this_directly::{perfect_hash_tokens, prepare_named_archive, read_dir, NamedArchive, SymlinkRules};
+
+/// Tokens for an archive file placed in a link section, see the `link_section` option.
+///
+/// Returns the item declaring the static holding the file, and for each of `sections`, an expression of type `&'static [u8]` for it.
+/// The sections must be the trailing parts of the file, in order.
+/// If an alignment is given, the file is preceded by padding such that the last sections (the compressed data onwards) are aligned.
+pub fn linked_archive_tokens<const N: usize>(
+    file: &[u8],
+    sections: [&[u8]; N],
+    link_section: &syn::LitStr,
+    align: Option<u32>,
+) -> (proc_macro2::TokenStream, [proc_macro2::TokenStream; N]) {
+    let sections_len: usize = sections.iter().map(|section| section.len()).sum();
+    let data_offset = file.len() - sections.iter().rev().take(4).map(|section| section.len()).sum::<usize>();
+    let align_value = usize::try_from(align.unwrap_or(1)).expect("alignment should fit into usize");
+    let padding = (align_value - data_offset % align_value) % align_value;
+    let mut start = padding + file.len() - sections_len;
+    let section_tokens = sections.map(|section| {
+        let (section_start, section_len) = (start, section.len());
+        start += section_len;
+        quote::quote! { section(#section_start, #section_len) }
+    });
+
+    let mut bytes = vec![0u8; padding];
+    bytes.extend_from_slice(file);
+    let len = bytes.len();
+    let literal = crate::common::bytes_tokens(&bytes);
+    let align = proc_macro2::Literal::u32_unsuffixed(align.unwrap_or(1));
+    let item = quote::quote! {
+        #[repr(C, align(#align))]
+        struct Aligned<T: ?Sized>(T);
+        #[unsafe(link_section = #link_section)]
+        static ARCHIVE: Aligned<[u8; #len]> = Aligned(*#literal);
+        const fn section(start: usize, len: usize) -> &'static [u8] {
+            ARCHIVE.0.split_at(start).1.split_at(len).0
         }
+    };
+    (item, section_tokens)
+}
+
+/// Read a hex-encoded ed25519 secret key from a file.
+pub fn read_signing_key(lit: Option<syn::Lit>) -> Option<[u8; 32]> {
+    let path = match lit {
+        None => return None,
+        Some(syn::Lit::Str(s)) => s.value(),
+        Some(_) => panic!("invalid signing_key option (expected a string literal)"),
+    };
+    if cfg!(not(feature = "signature")) {
+        panic!("signing_key requires feature 'signature'");
+    }
+    let hex = std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("Couldn't read signing key {path}: {err}"));
+    let hex = hex.trim();
+    let bytes: Option<std::vec::Vec<u8>> = (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|digits| u8::from_str_radix(digits, 16).ok()))
+        .collect();
+    match bytes.map(<[u8; 32]>::try_from) {
+        Some(Ok(key)) => Some(key),
+        _ => panic!("signing key {path} must consist of exactly 64 hexadecimal digits"),
+    }
+}
+
+#[cfg(feature = "signature")]
+pub fn sign(archive: &include_assets_decode::named::CompressedNamedArchive<'_, include_assets_decode::codec::AnyCodec>, key: &[u8; 32]) -> [u8; 64] {
+    use ed25519_dalek::Signer as _;
+
+    let message = include_assets_decode::signature::message(archive);
+    ed25519_dalek::SigningKey::from_bytes(key).sign(&message).to_bytes()
+}
+
+#[cfg(not(feature = "signature"))]
+pub fn sign(_archive: &include_assets_decode::named::CompressedNamedArchive<'_, include_assets_decode::codec::AnyCodec>, _key: &[u8; 32]) -> [u8; 64] {
+    unreachable!("signing keys are rejected without feature 'signature'")
+}
+
+/// Parse the size (in bytes of uncompressed data) of independently compressed shards.
+pub fn parse_shard_size(lit: Option<syn::Lit>) -> Option<u32> {
+    match lit {
+        None => None,
+        Some(syn::Lit::Int(int)) => match int.base10_parse::<u32>() {
+            Ok(n) if n > 0 => Some(n),
+            _ => panic!("invalid shard size {int} (expected a positive integer less than 2^32)"),
+        },
+        Some(_) => panic!("invalid shard_size option (expected an integer literal)"),
     }
+}
 
-    // compress asset names, sizes, and compute checksums
-    let (compressed_names, uncompressed_names_size) = compress_names(codec, assets.iter().map(|(name, _)| name)).context("couldn't compress asset names")?;
-    let compressed_sizes = compress_sizes(codec, assets.iter().map(|(name, data)| (name, data.len()))).context("couldn't compress asset sizes")?;
-    let checksums: std::vec::Vec<Checksum> = assets.iter().map(|(_, data)| compute_checksum(data.as_ref())).collect();
+/// Parse the size of checksums in bytes, 64 (full checksums) by default.
+pub fn parse_checksum_size(lit: Option<syn::Lit>) -> u32 {
+    match lit {
+        None => 64,
+        Some(syn::Lit::Int(int)) => match int.base10_parse::<u32>() {
+            Ok(n) if (8..=64).contains(&n) => n,
+            _ => panic!("invalid checksum size {int} (expected an integer between 8 and 64)"),
+        },
+        Some(_) => panic!("invalid checksum_size option (expected an integer literal)"),
+    }
+}
 
-    // compress data
-    let mut uncompressed_data = vec![];
-    for (_, asset_data) in assets.iter() {
-        uncompressed_data.extend_from_slice(asset_data.as_slice());
+pub fn parse_single_stream(lit: Option<syn::Lit>) -> bool {
+    match lit {
+        None => false,
+        Some(syn::Lit::Bool(b)) => b.value,
+        Some(_) => panic!("invalid single_stream option (expected true or false)"),
     }
-    let compressed_data = codec.compress(uncompressed_data.as_slice()).context("couldn't compress asset data")?;
+}
 
-    // ensure that the uncompressed data isn't too big
-    let uncompressed_data_size: u32 = uncompressed_data
-        .len()
-        .try_into()
-        .map_err(|_| anyhow::Error::msg(format!("too much data ({} bytes)", uncompressed_data.len())))?;
+pub fn parse_allow_outside_base(lit: Option<syn::Lit>) -> bool {
+    match lit {
+        None => false,
+        Some(syn::Lit::Bool(b)) => b.value,
+        Some(_) => panic!("invalid allow_outside_base option (expected true or false)"),
+    }
+}
 
-    Ok(NamedArchive {
-        compressed_data,
-        uncompressed_data_size,
-        compressed_names,
-        uncompressed_names_size,
-        compressed_sizes,
-        checksums,
-    })
+pub fn parse_dev_mode(lit: Option<syn::Lit>) -> bool {
+    match lit {
+        None => false,
+        Some(syn::Lit::Bool(b)) => b.value,
+        Some(_) => panic!("invalid dev option (expected true or false)"),
+    }
 }
 
-#[derive(Clone, Copy)]
-pub enum SymlinkRules {
-    Forbid,
-    Ignore,
-    Follow,
+pub fn parse_names_module(lit: Option<syn::Lit>) -> Option<syn::Ident> {
+    match lit {
+        None => None,
+        Some(syn::Lit::Str(s)) => Some(
+            s.parse()
+                .unwrap_or_else(|_| panic!("invalid names_module option {:?} (expected a module name)", s.value())),
+        ),
+        Some(_) => panic!("invalid names_module option (expected a string literal)"),
+    }
+}
+
+/// Name of the constant for an asset name, e.g. `TEXTURES_PLAYER_PNG` for `textures/player.png`
+fn name_constant(name: &str) -> syn::Ident {
+    let mut constant: std::string::String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    if !constant.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        constant.insert(0, '_');
+    }
+    syn::Ident::new(&constant, proc_macro2::Span::call_site())
+}
+
+/// Generate a module containing a constant for the name of each asset and the compressed archive.
+pub fn names_module_tokens<'a, I: Iterator<Item = &'a str>>(
+    module: &syn::Ident,
+    names: I,
+    codec_type: &proc_macro2::TokenStream,
+    archive: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let mut constants = std::collections::BTreeMap::new();
+    for name in names {
+        if let Some(other) = constants.insert(name_constant(name).to_string(), name) {
+            panic!("assets {other} and {name} have the same constant name {}", name_constant(name));
+        }
+    }
+    let (idents, names): (std::vec::Vec<_>, std::vec::Vec<_>) = constants
+        .into_iter()
+        .map(|(constant, name)| (syn::Ident::new(&constant, proc_macro2::Span::call_site()), name))
+        .unzip();
+    let docs = names.iter().map(|name| format!("Name of the asset `{name}`"));
+    quote::quote! {
+        pub mod #module {
+            #(
+                #[doc = #docs]
+                pub const #idents: &str = #names;
+            )*
+
+            /// The compressed asset archive
+            pub fn archive() -> ::include_assets::CompressedNamedArchive<'static, #codec_type> {
+                #archive
+            }
+        }
+    }
+}
+
+/// Parse MIME type overrides of the form `"ext=type/subtype, ext2=type2/subtype2"`.
+///
+/// Extensions are returned in lowercase.
+pub fn parse_mime_types(lit: Option<syn::Lit>) -> std::vec::Vec<(std::string::String, std::string::String)> {
+    let s = match lit {
+        None => return vec![],
+        Some(syn::Lit::Str(s)) => s.value(),
+        Some(_) => panic!("invalid mime_types option (expected a string literal)"),
+    };
+    let mut result: std::vec::Vec<(std::string::String, std::string::String)> = vec![];
+    for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let Some((ext, mime)) = pair.split_once('=') else {
+            panic!("invalid mime_types entry '{pair}' (expected 'extension=type/subtype')");
+        };
+        let (ext, mime) = (ext.trim().trim_start_matches('.').to_lowercase(), mime.trim());
+        if ext.is_empty() || !mime.contains('/') {
+            panic!("invalid mime_types entry '{pair}' (expected 'extension=type/subtype')");
+        }
+        if result.iter().any(|(other, _)| *other == ext) {
+            panic!("duplicate extension '{ext}' in mime_types");
+        }
+        result.push((ext, mime.to_owned()));
+    }
+    result
+}
+
+/// Parse the names of private assets and directories of the form `"secrets.toml, internal/"`.
+pub fn parse_private(lit: Option<syn::Lit>) -> std::vec::Vec<std::string::String> {
+    match lit {
+        None => vec![],
+        Some(syn::Lit::Str(s)) => s.value().split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_owned).collect(),
+        Some(_) => panic!("invalid private option (expected a string literal)"),
+    }
+}
+
+/// Ensure that each private name or directory matches at least one asset, to catch typos that would leave assets listed.
+pub fn check_private<'a, I: Iterator<Item = &'a str> + Clone>(private: &[std::string::String], names: I) {
+    for private in private {
+        let matches = |name: &str| match private.strip_suffix('/') {
+            Some(dir) => name.strip_prefix(dir).is_some_and(|rest| rest.starts_with('/')),
+            None => name == private,
+        };
+        if !names.clone().any(matches) {
+            panic!("private asset '{private}' is not in the directory");
+        }
+    }
 }
 
 pub fn parse_symlink_rules(lit: Option<syn::Lit>) -> SymlinkRules {
@@ -84,40 +238,3 @@ pub fn parse_symlink_rules(lit: Option<syn::Lit>) -> SymlinkRules {
         Some(_) => panic!("invalid/unsupported rule for symbolic links (supported rules are : forbid, ignore, follow)"),
     }
 }
-
-pub fn read_dir<P: AsRef<std::path::Path>>(
-    base: P,
-    symlink_rules: SymlinkRules,
-) -> anyhow::Result<std::vec::Vec<(smartstring::SmartString<smartstring::LazyCompact>, std::vec::Vec<u8>)>> {
-    let (follow_symlinks, ignore_symlinks) = match symlink_rules {
-        SymlinkRules::Forbid => (false, false),
-        SymlinkRules::Ignore => (false, true),
-        SymlinkRules::Follow => (true, false),
-    };
-    let mut assets = vec![];
-    for dirent in walkdir::WalkDir::new(base.as_ref()).sort_by_file_name().follow_links(follow_symlinks) {
-        // Note: sorting by file name is important to ensure the same compressed data independent of the creation/modification order of assets
-        let ent = dirent?;
-        if ent.file_type().is_dir() {
-            continue; // ignore
-        } else if ent.file_type().is_file() {
-            let filename = ent
-                .path()
-                .strip_prefix(base.as_ref())
-                .expect("child path should have parent as prefix")
-                .to_str()
-                .with_context(|| format!("Non-UTF-8 file name: '{}'", ent.path().display()))?;
-            let data = std::fs::read(ent.path()).with_context(|| format!("Couldn't read file '{}'", ent.path().display()))?;
-            assets.push((filename.into(), data))
-        } else if ent.file_type().is_symlink() {
-            if ignore_symlinks {
-                continue; // ignore
-            } else {
-                return Err(anyhow::Error::msg(format!("Encountered a symbolic link: {}", ent.path().display())));
-            }
-        } else {
-            panic!("File {} is neither directory, file, nor symbolic link.", ent.path().display());
-        }
-    }
-    Ok(assets)
-}
@@ -6,7 +6,8 @@ pub struct KVIdentLit {
 
 impl syn::parse::Parse for KVIdentLit {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let ident: syn::Ident = input.parse()?;
+        // keywords are allowed as keys, e.g. `type = "..."`
+        let ident = syn::ext::IdentExt::parse_any(input)?;
         let _: syn::token::Eq = input.parse()?;
         let lit: syn::Lit = input.parse()?;
         Ok(KVIdentLit { ident, lit })
@@ -34,6 +35,21 @@ impl syn::parse::Parse for IncludeDirArgs {
     }
 }
 
+/// A number of enum declarations
+pub struct EnumList {
+    pub enums: std::vec::Vec<syn::ItemEnum>,
+}
+
+impl syn::parse::Parse for EnumList {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut enums = vec![];
+        while !input.is_empty() {
+            enums.push(input.parse()?);
+        }
+        Ok(EnumList { enums })
+    }
+}
+
 pub struct KVList {
     pub kvs: std::vec::Vec<KVIdentLit>,
 }
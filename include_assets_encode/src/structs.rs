@@ -0,0 +1,91 @@
+/// Whether a field type is `&'static str` (true) or `&'static [u8]` (false)
+fn is_str_field(name: &str, ty: &syn::Type) -> bool {
+    let syn::Type::Reference(reference) = ty else {
+        panic!("field {name} must be &'static [u8] or &'static str");
+    };
+    if reference.mutability.is_some() || reference.lifetime.as_ref().is_none_or(|lifetime| lifetime.ident != "static") {
+        panic!("field {name} must be &'static [u8] or &'static str");
+    }
+    match &*reference.elem {
+        syn::Type::Path(path) if path.qself.is_none() && path.path.is_ident("str") => true,
+        syn::Type::Slice(slice) if matches!(&*slice.elem, syn::Type::Path(path) if path.qself.is_none() && path.path.is_ident("u8")) => false,
+        _ => panic!("field {name} must be &'static [u8] or &'static str"),
+    }
+}
+
+/// Convert a struct with asset fields into an equivalent enum with one variant per field, which is then handled like an `AssetEnum`.
+///
+/// Returns the enum and, for each field, whether it is a `&'static str`.
+pub fn struct_to_enum(s: &syn::ItemStruct) -> (syn::ItemEnum, std::vec::Vec<bool>) {
+    if !s.generics.params.is_empty() {
+        panic!("AssetStruct can't be derived for generic structs");
+    }
+    let syn::Fields::Named(fields) = &s.fields else {
+        panic!("AssetStruct can only be derived for structs with named fields");
+    };
+    let mut variants = syn::punctuated::Punctuated::<syn::Variant, syn::Token![,]>::new();
+    let mut is_str = vec![];
+    for field in fields.named.iter() {
+        let ident = field.ident.clone().expect("named fields have names");
+        is_str.push(is_str_field(&ident.to_string(), &field.ty));
+        variants.push(syn::Variant {
+            attrs: field.attrs.clone(),
+            ident,
+            fields: syn::Fields::Unit,
+            discriminant: None,
+        });
+    }
+    let e = syn::ItemEnum {
+        attrs: s.attrs.clone(),
+        vis: s.vis.clone(),
+        enum_token: Default::default(),
+        ident: quote::format_ident!("__{}Asset", s.ident),
+        generics: Default::default(),
+        brace_token: Default::default(),
+        variants,
+    };
+    (e, is_str)
+}
+
+/// Generate the hidden enum and the `load` function of the struct.
+pub fn struct_tokens(s: &syn::ItemStruct, options: &crate::enums::AssetEnumOptions) -> proc_macro2::TokenStream {
+    for variant in options.variants.iter() {
+        if variant.optional {
+            panic!("field {} can't be optional in an AssetStruct", variant.ident);
+        }
+        if variant.deserialize.is_some() {
+            panic!("field {} can't be deserialized in an AssetStruct", variant.ident);
+        }
+    }
+    let struct_name = &s.ident;
+    let enum_name = &options.enum_name;
+    let vis = &options.vis;
+    let fields: std::vec::Vec<&syn::Ident> = options.variants.iter().map(|variant| &variant.ident).collect();
+    let getters = options.variants.iter().map(|variant| {
+        let field = &variant.ident;
+        if variant.is_str {
+            quote::quote! { archive.get_str(#enum_name::#field) }
+        } else {
+            quote::quote! { archive.get(#enum_name::#field).expect("asset is not optional") }
+        }
+    });
+    quote::quote! {
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #[derive(Clone, Copy)]
+        #vis enum #enum_name {
+            #(#fields,)*
+        }
+
+        impl #struct_name {
+            /// Load (decompress) the assets on the first call, and return the same assets on later calls.
+            #vis fn load() -> Self {
+                static ARCHIVE: ::std::sync::OnceLock<include_assets::StaticEnumArchive<#enum_name>> = ::std::sync::OnceLock::new();
+                let archive = *ARCHIVE.get_or_init(|| include_assets::EnumArchive::load().leak());
+                Self {
+                    #(#fields: #getters,)*
+                }
+            }
+        }
+    }
+}
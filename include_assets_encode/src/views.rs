@@ -0,0 +1,68 @@
+/// Parse the `archive` and `asset` attributes of an enum deriving `AssetView`, returning the base path (if any) and the name and path of each variant.
+fn parse_view(e: &syn::ItemEnum) -> (Option<syn::LitStr>, std::vec::Vec<(syn::Ident, syn::LitStr)>) {
+    let mut base_path = None;
+    for attr in e.attrs.iter().filter(|attr| attr.path().is_ident("archive")) {
+        let syn::Meta::List(list) = &attr.meta else {
+            panic!("invalid attribute 'archive' for AssetView");
+        };
+        let kv_opts: crate::parse::KVList = syn::parse2(list.tokens.clone()).unwrap();
+        let mut opts = crate::parse::kv_args_to_hashmap(kv_opts.kvs.into_iter(), ["base_path"].into_iter().collect());
+        base_path = match opts.remove("base_path") {
+            None => None,
+            Some(syn::Lit::Str(s)) => Some(s),
+            Some(_) => panic!("unexpected value for attribute base_path, expected a string literal"),
+        };
+    }
+
+    let mut variants = vec![];
+    for var in e.variants.iter() {
+        let name = var.ident.to_string();
+        if !matches!(var.fields, syn::Fields::Unit) {
+            panic!("{name} is not a unit variant");
+        }
+        let asset_attrs: std::vec::Vec<&syn::Attribute> = var.attrs.iter().filter(|attr| attr.path().is_ident("asset")).collect();
+        let [attr] = &asset_attrs[..] else {
+            panic!("variant {name} needs exactly one 'asset' attribute");
+        };
+        let syn::Meta::List(list) = &attr.meta else {
+            panic!("invalid attribute for variant {name}");
+        };
+        let kv_opts: crate::parse::KVList = syn::parse2(list.tokens.clone()).unwrap();
+        let mut opts = crate::parse::kv_args_to_hashmap(kv_opts.kvs.into_iter(), ["path"].into_iter().collect());
+        match opts.remove("path") {
+            Some(syn::Lit::Str(s)) => variants.push((var.ident.clone(), s)),
+            Some(_) => panic!("invalid attribute for variant {name}"),
+            None => panic!("variant {name} is missing attribute 'path'"),
+        }
+    }
+    (base_path, variants)
+}
+
+/// Generate the implementation of `AssetView` for an enum.
+///
+/// With a base path, the files of all variants are checked to exist at compile time.
+pub fn view_tokens(e: &syn::ItemEnum) -> proc_macro2::TokenStream {
+    let (base_path, variants) = parse_view(e);
+    if let Some(base_path) = base_path {
+        for (_, path) in variants.iter() {
+            let file = std::path::PathBuf::from(base_path.value()).join(path.value());
+            if !file.is_file() {
+                panic!("file {} doesn't exist", file.display());
+            }
+        }
+    }
+    let enum_name = &e.ident;
+    let (variant_idents, paths): (std::vec::Vec<_>, std::vec::Vec<_>) = variants.into_iter().unzip();
+    let indices = 0..variant_idents.len();
+    quote::quote! {
+        impl include_assets::AssetView for #enum_name {
+            const PATHS: &'static [&'static str] = &[#(#paths),*];
+
+            fn index(&self) -> usize {
+                match self {
+                    #(#enum_name::#variant_idents => #indices,)*
+                }
+            }
+        }
+    }
+}